@@ -0,0 +1,438 @@
+//! Flow metrics computed from task history.
+//!
+//! * **Lead time** - from the moment a task was created until it was done.
+//! * **Cycle time** - from the moment work on a task started until it was done.
+//!
+//! "Started" and "done" follow the workflow's start and done columns, see
+//! [`Workflow::start_column`] and [`Workflow::done_column`]. A task that is
+//! reopened and finished again counts as done when it last entered the done
+//! column; every time it left the done column counts as rework.
+//!
+//! Time a task spent flagged as blocked is tracked from its `blocked` and
+//! `unblocked` events. **Flow efficiency** is the share of cycle time the
+//! task was actively worked on, i.e. not blocked.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::Task;
+use crate::history::{EventKind, TaskEvent};
+use crate::workflow::Workflow;
+
+/// Key moments in a task's journey through the workflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskTimeline {
+    pub task_id: u32,
+    /// when the task was created
+    pub created_at: DateTime<Utc>,
+    /// when the task first reached the start column (or a later one)
+    pub started_at: Option<DateTime<Utc>>,
+    /// when the task last entered the done column, if it is done
+    pub done_at: Option<DateTime<Utc>>,
+    /// for finished tasks with a due date, whether they finished late
+    pub completed_late: Option<bool>,
+    /// number of times the task left the done column again
+    pub reopened: usize,
+    /// periods the task was flagged as blocked, oldest first
+    pub blocked: Vec<BlockedPeriod>,
+}
+
+/// A period during which a task was flagged as blocked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedPeriod {
+    /// when the task was flagged
+    pub from: DateTime<Utc>,
+    /// when the flag was cleared; `None` while the task is still blocked
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TaskTimeline {
+    /// Reconstructs the timeline of `task` from its history.
+    pub fn from_history(task: &Task, history: &[TaskEvent], workflow: &Workflow) -> Self {
+        let moves = history.iter().filter(|e| e.kind.is_move()).filter_map(|e| {
+            e.new_value
+                .as_deref()
+                .map(|to| (e.old_value.as_deref(), to, e.at))
+        });
+
+        let mut started_at = None;
+        let mut done_at = None;
+        let mut reopened = 0;
+        for (from, to, at) in moves {
+            if started_at.is_none() && workflow.is_started(to) {
+                started_at = Some(at);
+            }
+            if workflow.is_done(to) {
+                done_at = Some(at);
+            } else if from.is_some_and(|from| workflow.is_done(from)) {
+                reopened += 1;
+            }
+        }
+
+        // tasks finished before history was recorded fall back to their
+        // last update
+        let done_at = if workflow.is_done(task.status.as_str()) {
+            done_at.or(task.updated_at)
+        } else {
+            None
+        };
+
+        let completed_late = done_at.and(task.due_at).map(|_| task.completed_late);
+
+        let mut blocked: Vec<BlockedPeriod> = Vec::new();
+        for event in history {
+            match event.kind {
+                EventKind::Blocked => blocked.push(BlockedPeriod {
+                    from: event.at,
+                    until: None,
+                }),
+                EventKind::Unblocked => {
+                    if let Some(period) = blocked.last_mut().filter(|p| p.until.is_none()) {
+                        period.until = Some(event.at);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        TaskTimeline {
+            task_id: task.id.unwrap_or_default(),
+            created_at: task.created_at,
+            started_at,
+            done_at,
+            completed_late,
+            reopened,
+            blocked,
+        }
+    }
+
+    /// Time from creation to done.
+    pub fn lead_time(&self) -> Option<Duration> {
+        self.done_at.map(|done| done - self.created_at)
+    }
+
+    /// Time from start of work to done.
+    pub fn cycle_time(&self) -> Option<Duration> {
+        self.done_at
+            .zip(self.started_at)
+            .map(|(done, start)| done - start)
+    }
+
+    /// Total time the task spent blocked. A period that is still open ends
+    /// when the task was done or, for unfinished tasks, at `now`.
+    pub fn blocked_time(&self, now: DateTime<Utc>) -> Duration {
+        let end = self.done_at.unwrap_or(now);
+        self.blocked
+            .iter()
+            .map(|period| {
+                let until = period.until.unwrap_or(end).min(end);
+                (until - period.from).max(Duration::zero())
+            })
+            .sum()
+    }
+
+    /// Cycle time the task was not blocked, for finished tasks.
+    pub fn active_time(&self) -> Option<Duration> {
+        let done = self.done_at?;
+        let cycle = self.cycle_time()?;
+        Some(cycle - self.blocked_time(done).min(cycle))
+    }
+
+    /// Share of cycle time the task was not blocked, from 0 to 1; `None` for
+    /// unfinished tasks or a zero cycle time.
+    pub fn flow_efficiency(&self) -> Option<f64> {
+        let cycle = self
+            .cycle_time()
+            .filter(|cycle| *cycle > Duration::zero())?;
+        let active = self.active_time()?;
+        Some(active.num_seconds() as f64 / cycle.num_seconds() as f64)
+    }
+}
+
+/// Distribution summary of a set of durations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    pub p50: Duration,
+    pub p85: Duration,
+    pub p95: Duration,
+}
+
+impl Percentiles {
+    /// Computes nearest-rank percentiles; `None` if there are no samples.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        samples.sort();
+        Some(Percentiles {
+            p50: percentile(&samples, 50),
+            p85: percentile(&samples, 85),
+            p95: percentile(&samples, 95),
+        })
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty samples.
+pub fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Lead and cycle time of the tasks finished within a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowStats {
+    /// start of the window (inclusive)
+    pub from: DateTime<Utc>,
+    /// end of the window (exclusive)
+    pub until: DateTime<Utc>,
+    /// number of tasks finished in the window
+    pub completed: usize,
+    /// number of finished tasks that had a due date
+    pub due_dated: usize,
+    /// number of finished tasks that met their due date
+    pub on_time: usize,
+    /// number of finished tasks that had been reopened before
+    pub reworked: usize,
+    /// cycle time of finished tasks spent blocked, summed
+    pub blocked_time: Duration,
+    /// cycle time of finished tasks not spent blocked, summed
+    pub active_time: Duration,
+    pub lead_time: Option<Percentiles>,
+    pub cycle_time: Option<Percentiles>,
+}
+
+impl FlowStats {
+    /// Summarises the timelines of tasks finished within `[from, until)`.
+    pub fn compute(timelines: &[TaskTimeline], from: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        let finished: Vec<&TaskTimeline> = timelines
+            .iter()
+            .filter(|t| t.done_at.is_some_and(|done| done >= from && done < until))
+            .collect();
+
+        FlowStats {
+            from,
+            until,
+            completed: finished.len(),
+            due_dated: finished
+                .iter()
+                .filter(|t| t.completed_late.is_some())
+                .count(),
+            on_time: finished
+                .iter()
+                .filter(|t| t.completed_late == Some(false))
+                .count(),
+            reworked: finished.iter().filter(|t| t.reopened > 0).count(),
+            blocked_time: finished
+                .iter()
+                .filter_map(|t| Some(t.cycle_time()? - t.active_time()?))
+                .sum(),
+            active_time: finished.iter().filter_map(|t| t.active_time()).sum(),
+            lead_time: Percentiles::from_samples(
+                finished.iter().filter_map(|t| t.lead_time()).collect(),
+            ),
+            cycle_time: Percentiles::from_samples(
+                finished.iter().filter_map(|t| t.cycle_time()).collect(),
+            ),
+        }
+    }
+
+    /// Share of finished tasks with a due date that met it, from 0 to 1;
+    /// `None` if none of them had a due date.
+    pub fn on_time_rate(&self) -> Option<f64> {
+        (self.due_dated > 0).then(|| self.on_time as f64 / self.due_dated as f64)
+    }
+
+    /// Share of finished tasks that had been reopened, from 0 to 1; `None`
+    /// if no task was finished.
+    pub fn rework_rate(&self) -> Option<f64> {
+        (self.completed > 0).then(|| self.reworked as f64 / self.completed as f64)
+    }
+
+    /// Share of the finished tasks' total cycle time they were not blocked,
+    /// from 0 to 1; `None` if they had no cycle time.
+    pub fn flow_efficiency(&self) -> Option<f64> {
+        let cycle = (self.active_time + self.blocked_time).num_seconds();
+        (cycle > 0).then(|| self.active_time.num_seconds() as f64 / cycle as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Status;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, day, hour, 0, 0).unwrap()
+    }
+
+    fn moved(task_id: u32, from: &str, to: &str, at: DateTime<Utc>) -> TaskEvent {
+        let mut event = TaskEvent::new(
+            task_id,
+            EventKind::Moved,
+            Some(from.to_string()),
+            Some(to.to_string()),
+            "tester",
+        );
+        event.at = at;
+        event
+    }
+
+    fn task(id: u32, status: Status, created_at: DateTime<Utc>) -> Task {
+        let mut task = Task::new(format!("task{}", id), String::new());
+        task.id = Some(id);
+        task.status = status;
+        task.created_at = created_at;
+        task
+    }
+
+    #[test]
+    fn timeline_uses_transition_times() {
+        let task = task(1, Status::DONE, at(1, 9));
+        let history = vec![
+            moved(1, "Todo", "Doing", at(2, 9)),
+            moved(1, "Doing", "Done", at(4, 9)),
+        ];
+
+        let timeline = TaskTimeline::from_history(&task, &history, &Workflow::default());
+        assert_eq!(timeline.started_at, Some(at(2, 9)));
+        assert_eq!(timeline.done_at, Some(at(4, 9)));
+        assert_eq!(timeline.lead_time(), Some(Duration::days(3)));
+        assert_eq!(timeline.cycle_time(), Some(Duration::days(2)));
+    }
+
+    #[test]
+    fn unfinished_task_has_no_times() {
+        let task = task(1, Status::DOING, at(1, 9));
+        let history = vec![moved(1, "Todo", "Doing", at(2, 9))];
+
+        let timeline = TaskTimeline::from_history(&task, &history, &Workflow::default());
+        assert_eq!(timeline.started_at, Some(at(2, 9)));
+        assert_eq!(timeline.lead_time(), None);
+        assert_eq!(timeline.cycle_time(), None);
+    }
+
+    #[test]
+    fn reopened_task_is_done_when_it_last_finished() {
+        let task = task(1, Status::DONE, at(1, 9));
+        let mut reopened = moved(1, "Done", "Doing", at(5, 9));
+        reopened.kind = EventKind::Reopened;
+        let mut stopped = moved(1, "Doing", "Todo", at(6, 9));
+        stopped.kind = EventKind::Stopped;
+        let history = vec![
+            moved(1, "Todo", "Doing", at(2, 9)),
+            moved(1, "Doing", "Done", at(4, 9)),
+            reopened,
+            stopped,
+            moved(1, "Todo", "Doing", at(7, 9)),
+            moved(1, "Doing", "Done", at(8, 9)),
+        ];
+
+        let timeline = TaskTimeline::from_history(&task, &history, &Workflow::default());
+        assert_eq!(timeline.started_at, Some(at(2, 9)));
+        assert_eq!(timeline.done_at, Some(at(8, 9)));
+        assert_eq!(timeline.reopened, 1);
+        assert_eq!(timeline.cycle_time(), Some(Duration::days(6)));
+    }
+
+    #[test]
+    fn blocked_periods_reduce_flow_efficiency() {
+        let task = task(1, Status::DONE, at(1, 9));
+        let mut blocked = TaskEvent::new(1, EventKind::Blocked, None, Some("ops".into()), "tester");
+        blocked.at = at(3, 9);
+        let mut unblocked =
+            TaskEvent::new(1, EventKind::Unblocked, Some("ops".into()), None, "tester");
+        unblocked.at = at(4, 9);
+        let history = vec![
+            moved(1, "Todo", "Doing", at(2, 9)),
+            blocked,
+            unblocked,
+            moved(1, "Doing", "Done", at(6, 9)),
+        ];
+
+        let timeline = TaskTimeline::from_history(&task, &history, &Workflow::default());
+        assert_eq!(timeline.blocked.len(), 1);
+        assert_eq!(timeline.blocked_time(at(20, 0)), Duration::days(1));
+        assert_eq!(timeline.active_time(), Some(Duration::days(3)));
+        assert_eq!(timeline.flow_efficiency(), Some(0.75));
+
+        let stats = FlowStats::compute(&[timeline], at(1, 0), at(10, 0));
+        assert_eq!(stats.blocked_time, Duration::days(1));
+        assert_eq!(stats.active_time, Duration::days(3));
+        assert_eq!(stats.flow_efficiency(), Some(0.75));
+    }
+
+    #[test]
+    fn open_blocked_period_runs_until_now() {
+        let task = task(1, Status::DOING, at(1, 9));
+        let mut blocked = TaskEvent::new(1, EventKind::Blocked, None, Some("ops".into()), "tester");
+        blocked.at = at(3, 9);
+        let history = vec![moved(1, "Todo", "Doing", at(2, 9)), blocked];
+
+        let timeline = TaskTimeline::from_history(&task, &history, &Workflow::default());
+        assert_eq!(timeline.blocked[0].until, None);
+        assert_eq!(timeline.blocked_time(at(3, 21)), Duration::hours(12));
+        assert_eq!(timeline.flow_efficiency(), None);
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples: Vec<_> = (1..=20).map(Duration::hours).collect();
+        let p = Percentiles::from_samples(samples).unwrap();
+
+        assert_eq!(p.p50, Duration::hours(10));
+        assert_eq!(p.p85, Duration::hours(17));
+        assert_eq!(p.p95, Duration::hours(19));
+        assert_eq!(Percentiles::from_samples(vec![]), None);
+
+        let single = Percentiles::from_samples(vec![Duration::hours(3)]).unwrap();
+        assert_eq!(single.p95, Duration::hours(3));
+    }
+
+    #[test]
+    fn stats_only_count_tasks_finished_in_window() {
+        let timelines = vec![
+            TaskTimeline {
+                task_id: 1,
+                created_at: at(1, 0),
+                started_at: Some(at(2, 0)),
+                done_at: Some(at(3, 0)),
+                completed_late: Some(true),
+                reopened: 1,
+                blocked: vec![],
+            },
+            TaskTimeline {
+                task_id: 2,
+                created_at: at(1, 0),
+                started_at: Some(at(5, 0)),
+                done_at: Some(at(9, 0)),
+                completed_late: Some(false),
+                reopened: 0,
+                blocked: vec![],
+            },
+            TaskTimeline {
+                task_id: 3,
+                created_at: at(1, 0),
+                started_at: Some(at(2, 0)),
+                done_at: None,
+                completed_late: None,
+                reopened: 0,
+                blocked: vec![],
+            },
+        ];
+
+        let stats = FlowStats::compute(&timelines, at(1, 0), at(5, 0));
+        assert_eq!(stats.completed, 1);
+        assert_eq!(stats.on_time_rate(), Some(0.0));
+        assert_eq!(stats.rework_rate(), Some(1.0));
+        assert_eq!(stats.lead_time.unwrap().p50, Duration::days(2));
+        assert_eq!(stats.cycle_time.unwrap().p95, Duration::days(1));
+
+        let empty = FlowStats::compute(&timelines, at(20, 0), at(25, 0));
+        assert_eq!(empty.completed, 0);
+        assert_eq!(empty.on_time_rate(), None);
+        assert_eq!(empty.rework_rate(), None);
+        assert_eq!(empty.flow_efficiency(), None);
+        assert_eq!(empty.lead_time, None);
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KanbanError, Result};
+use crate::workflow::Workflow;
+
+/// ID of the board every workspace starts with. Tasks stored before boards
+/// existed belong to it.
+pub const DEFAULT_BOARD_ID: u32 = 1;
+
+/// Name of the board every workspace starts with.
+pub const DEFAULT_BOARD_NAME: &str = "Default";
+
+/// A board groups tasks that share a workflow.
+///
+/// Every task belongs to exactly one board and task names are unique within
+/// their board.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Board {
+    /// unique identifier
+    pub id: Option<u32>,
+    /// board name, unique within the workspace
+    pub name: String,
+    /// columns and transitions tasks on the board follow
+    pub workflow: Workflow,
+    /// when the board was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl Board {
+    /// Creates a new board with the given name and workflow.
+    pub fn new(name: String, workflow: Workflow) -> Self {
+        Board {
+            id: None,
+            name,
+            workflow,
+            created_at: Utc::now(),
+        }
+    }
+
+    // validations for a new or renamed board
+    pub fn before_save(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(KanbanError::Validation {
+                field: "board",
+                reason: String::from("name must not be empty"),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Board {
+    /// The board every workspace starts with, using the default workflow.
+    fn default() -> Self {
+        Board {
+            id: Some(DEFAULT_BOARD_ID),
+            ..Board::new(DEFAULT_BOARD_NAME.to_string(), Workflow::default())
+        }
+    }
+}
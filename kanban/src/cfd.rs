@@ -0,0 +1,347 @@
+//! Cumulative flow diagram data reconstructed from task history.
+//!
+//! For every day in a range the board is replayed up to the end of that day
+//! (UTC) and the tasks in each workflow column are counted. The result can
+//! be exported as CSV or rendered as a stacked-area chart, with the done
+//! column at the bottom and earlier columns stacked above it.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+
+use crate::history::{EventKind, TaskEvent};
+use crate::workflow::Workflow;
+use crate::{Status, Task};
+
+/// Symbols used for the columns of the ASCII chart, in board order.
+const ASCII_SYMBOLS: &[char] = &['.', '+', '#', '%', '=', '@', '*', 'o'];
+
+/// Fill colours used for the columns of the SVG chart, in board order.
+const SVG_COLOURS: &[&str] = &[
+    "#9ecae1", "#fdae6b", "#a1d99b", "#bcbddc", "#fdd0a2", "#c7e9c0", "#dadaeb", "#fcbba1",
+];
+
+/// Task counts per column on one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CfdDay {
+    pub date: NaiveDate,
+    /// number of tasks in each column, in the same order as
+    /// [`CumulativeFlow::columns`]
+    pub counts: Vec<usize>,
+}
+
+impl CfdDay {
+    /// Number of tasks on the board that day.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+/// Daily task counts per workflow column over a date range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CumulativeFlow {
+    /// workflow columns in board order
+    pub columns: Vec<Status>,
+    /// one entry per day, oldest first
+    pub days: Vec<CfdDay>,
+}
+
+impl CumulativeFlow {
+    /// Replays `tasks` and their histories for every day in `[from, to]`.
+    pub fn build(
+        tasks: &[(Task, Vec<TaskEvent>)],
+        workflow: &Workflow,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Self {
+        let columns = workflow.columns().to_vec();
+
+        let days = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| {
+                let end_of_day = (date + Duration::days(1))
+                    .and_time(NaiveTime::MIN)
+                    .and_utc();
+
+                let mut counts = vec![0; columns.len()];
+                for (task, history) in tasks {
+                    let status = status_at(task, history, end_of_day);
+                    if let Some(pos) = status.and_then(|s| workflow.position(s)) {
+                        counts[pos] += 1;
+                    }
+                }
+
+                CfdDay { date, counts }
+            })
+            .collect();
+
+        CumulativeFlow { columns, days }
+    }
+
+    /// Exports the data as CSV with a `date` column followed by one column
+    /// per workflow column.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date");
+        for column in &self.columns {
+            csv.push(',');
+            csv.push_str(&csv_field(column.as_str()));
+        }
+        csv.push('\n');
+
+        for day in &self.days {
+            csv.push_str(&day.date.to_string());
+            for count in &day.counts {
+                let _ = write!(csv, ",{}", count);
+            }
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// Renders a stacked-area chart `height` rows tall with one character
+    /// per day, followed by a legend.
+    pub fn render_ascii(&self, height: usize) -> String {
+        let height = height.max(1);
+        let max_total = self
+            .days
+            .iter()
+            .map(CfdDay::total)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        // scaled cumulative boundaries per day, done column at the bottom
+        let stacks: Vec<Vec<usize>> = self
+            .days
+            .iter()
+            .map(|day| {
+                let mut running = 0;
+                let mut tops = vec![0; day.counts.len()];
+                for pos in (0..day.counts.len()).rev() {
+                    running += day.counts[pos];
+                    tops[pos] = (running * height).div_ceil(max_total);
+                }
+                tops
+            })
+            .collect();
+
+        let mut chart = String::new();
+        for row in (0..height).rev() {
+            // only the top of the scale is labelled; lower rows round poorly
+            if row + 1 == height {
+                let _ = write!(chart, "{:>4} |", max_total);
+            } else {
+                chart.push_str("     |");
+            }
+            for tops in &stacks {
+                // topmost column whose band covers this row
+                let symbol = (0..tops.len())
+                    .find(|&pos| {
+                        row < tops[pos] && tops.get(pos + 1).is_none_or(|&below| row >= below)
+                    })
+                    .map_or(' ', |pos| ASCII_SYMBOLS[pos % ASCII_SYMBOLS.len()]);
+                chart.push(symbol);
+            }
+            chart.push('\n');
+        }
+
+        let _ = writeln!(chart, "     +{}", "-".repeat(self.days.len()));
+        if let (Some(first), Some(last)) = (self.days.first(), self.days.last()) {
+            let _ = writeln!(chart, "      {} .. {}", first.date, last.date);
+        }
+        for (pos, column) in self.columns.iter().enumerate() {
+            let _ = writeln!(
+                chart,
+                "      {} {}",
+                ASCII_SYMBOLS[pos % ASCII_SYMBOLS.len()],
+                column
+            );
+        }
+
+        chart
+    }
+
+    /// Renders a stacked-area chart as a standalone SVG document.
+    pub fn render_svg(&self, width: u32, height: u32) -> String {
+        let max_total = self
+            .days
+            .iter()
+            .map(CfdDay::total)
+            .max()
+            .unwrap_or(0)
+            .max(1) as f64;
+        let step = if self.days.len() > 1 {
+            f64::from(width) / (self.days.len() - 1) as f64
+        } else {
+            0.0
+        };
+        let y = |value: usize| f64::from(height) - value as f64 / max_total * f64::from(height);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        );
+
+        for pos in 0..self.columns.len() {
+            // band between the stack below this column and its own top
+            let below = |day: &CfdDay| day.counts[pos + 1..].iter().sum::<usize>();
+            let top: Vec<String> = self
+                .days
+                .iter()
+                .enumerate()
+                .map(|(i, day)| {
+                    format!(
+                        "{:.1},{:.1}",
+                        i as f64 * step,
+                        y(below(day) + day.counts[pos])
+                    )
+                })
+                .collect();
+            let bottom: Vec<String> = self
+                .days
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(i, day)| format!("{:.1},{:.1}", i as f64 * step, y(below(day))))
+                .collect();
+
+            let _ = writeln!(
+                svg,
+                r#"  <polygon fill="{}" points="{} {}"><title>{}</title></polygon>"#,
+                SVG_COLOURS[pos % SVG_COLOURS.len()],
+                top.join(" "),
+                bottom.join(" "),
+                xml_escape(self.columns[pos].as_str())
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Column a task was in at `at`, or `None` if it did not exist yet.
+fn status_at<'a>(task: &'a Task, history: &'a [TaskEvent], at: DateTime<Utc>) -> Option<&'a str> {
+    if task.created_at >= at {
+        return None;
+    }
+
+    let mut status = None;
+    for event in history.iter().filter(|e| e.at < at) {
+        if event.kind == EventKind::Created || event.kind.is_move() {
+            status = event.new_value.as_deref();
+        }
+    }
+
+    // tasks created before history was recorded only know where they are now
+    Some(status.unwrap_or(task.status.as_str()))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+    }
+
+    fn event(task_id: u32, kind: EventKind, to: &str, day: u32) -> TaskEvent {
+        let mut event = TaskEvent::new(task_id, kind, None, Some(to.to_string()), "tester");
+        event.at = Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap();
+        event
+    }
+
+    fn board() -> Vec<(Task, Vec<TaskEvent>)> {
+        let mut first = Task::new("first".to_string(), String::new());
+        first.id = Some(1);
+        first.created_at = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        first.status = Status::DONE;
+
+        let mut second = Task::new("second".to_string(), String::new());
+        second.id = Some(2);
+        second.created_at = Utc.with_ymd_and_hms(2025, 6, 2, 12, 0, 0).unwrap();
+        second.status = Status::DOING;
+
+        vec![
+            (
+                first,
+                vec![
+                    event(1, EventKind::Created, "Todo", 1),
+                    event(1, EventKind::Moved, "Doing", 2),
+                    event(1, EventKind::Moved, "Done", 3),
+                ],
+            ),
+            (
+                second,
+                vec![
+                    event(2, EventKind::Created, "Todo", 2),
+                    event(2, EventKind::Moved, "Doing", 4),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn counts_are_replayed_per_day() {
+        let cfd = CumulativeFlow::build(&board(), &Workflow::default(), date(1), date(4));
+
+        let counts: Vec<_> = cfd.days.iter().map(|d| d.counts.clone()).collect();
+        assert_eq!(
+            counts,
+            vec![vec![1, 0, 0], vec![1, 1, 0], vec![1, 0, 1], vec![0, 1, 1]]
+        );
+    }
+
+    #[test]
+    fn csv_has_header_and_row_per_day() {
+        let cfd = CumulativeFlow::build(&board(), &Workflow::default(), date(2), date(3));
+
+        assert_eq!(
+            cfd.to_csv(),
+            "date,Todo,Doing,Done\n2025-06-02,1,1,0\n2025-06-03,1,0,1\n"
+        );
+    }
+
+    #[test]
+    fn ascii_chart_stacks_done_at_the_bottom() {
+        let cfd = CumulativeFlow::build(&board(), &Workflow::default(), date(1), date(4));
+        let chart = cfd.render_ascii(2);
+        let rows: Vec<&str> = chart.lines().collect();
+
+        assert_eq!(rows[0], "   2 | ..+");
+        assert_eq!(rows[1], "     |.+##");
+        assert!(chart.contains("# Done"));
+    }
+
+    #[test]
+    fn svg_has_a_band_per_column() {
+        let cfd = CumulativeFlow::build(&board(), &Workflow::default(), date(1), date(4));
+        let svg = cfd.render_svg(300, 100);
+
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<polygon").count(), 3);
+        assert!(svg.contains("<title>Doing</title>"));
+    }
+}
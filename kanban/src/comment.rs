@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KanbanError, Result};
+
+/// A markdown comment left on a task.
+///
+/// Editing a comment keeps the text it replaced in [`Comment::revisions`],
+/// so earlier versions remain available for auditing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    /// unique identifier
+    pub id: Option<u32>,
+    /// task the comment belongs to
+    pub task_id: u32,
+    /// who wrote the comment
+    pub author: String,
+    /// current text, in markdown
+    pub body: String,
+    /// when the comment was written
+    pub created_at: DateTime<Utc>,
+    /// when the comment was last edited
+    pub updated_at: Option<DateTime<Utc>>,
+    /// who made the last edit
+    #[serde(default)]
+    pub edited_by: Option<String>,
+    /// earlier versions of the text, oldest first
+    #[serde(default)]
+    pub revisions: Vec<CommentRevision>,
+}
+
+/// A version of a comment that was replaced by an edit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommentRevision {
+    /// text of the version
+    pub body: String,
+    /// who wrote the version
+    pub author: String,
+    /// when the version was written
+    pub written_at: DateTime<Utc>,
+}
+
+impl Comment {
+    /// Creates a comment written now.
+    pub fn new(task_id: u32, author: String, body: String) -> Self {
+        Comment {
+            id: None,
+            task_id,
+            author,
+            body,
+            created_at: Utc::now(),
+            updated_at: None,
+            edited_by: None,
+            revisions: Vec::new(),
+        }
+    }
+
+    // validations for a new or edited comment
+    pub fn before_save(&self) -> Result<()> {
+        if self.body.trim().is_empty() {
+            return Err(KanbanError::Validation {
+                field: "comment",
+                reason: String::from("must not be empty"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the text, keeping the current version as a revision.
+    ///
+    /// # Arguments
+    /// * `body` - The new text.
+    /// * `editor` - Who made the edit.
+    pub fn edit(&mut self, body: String, editor: &str) {
+        let revision = CommentRevision {
+            body: std::mem::replace(&mut self.body, body),
+            author: self.edited_by.take().unwrap_or_else(|| self.author.clone()),
+            written_at: self.updated_at.unwrap_or(self.created_at),
+        };
+        self.revisions.push(revision);
+        self.updated_at = Some(Utc::now());
+        self.edited_by = Some(editor.to_string());
+    }
+
+    /// Returns `true` if the comment was edited after it was written.
+    pub fn is_edited(&self) -> bool {
+        !self.revisions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_keeps_previous_versions() {
+        let mut comment = Comment::new(1, "alice".into(), "first".into());
+        assert!(!comment.is_edited());
+
+        comment.edit("second".into(), "bob");
+        comment.edit("third".into(), "alice");
+
+        assert!(comment.is_edited());
+        assert_eq!(comment.body, "third");
+        assert_eq!(comment.author, "alice");
+        assert_eq!(comment.edited_by.as_deref(), Some("alice"));
+        let history: Vec<_> = comment
+            .revisions
+            .iter()
+            .map(|r| (r.body.as_str(), r.author.as_str()))
+            .collect();
+        assert_eq!(history, vec![("first", "alice"), ("second", "bob")]);
+        assert_eq!(comment.revisions[0].written_at, comment.created_at);
+    }
+
+    #[test]
+    fn empty_body_is_rejected() {
+        let comment = Comment::new(1, "alice".into(), " \n".into());
+
+        assert!(matches!(
+            comment.before_save(),
+            Err(KanbanError::Validation {
+                field: "comment",
+                ..
+            })
+        ));
+    }
+}
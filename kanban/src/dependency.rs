@@ -0,0 +1,176 @@
+//! Dependency graph between tasks.
+//!
+//! A link "A blocks B" means work on B cannot start until A is done. Links
+//! are stored on the blocked task (see [`Task::blocked_by`]); the graph
+//! gathers them for a set of tasks so they can be inspected or exported in
+//! Graphviz DOT format.
+
+use std::fmt::Write;
+
+use crate::{Status, Task};
+
+/// Fill colour of finished tasks in the DOT export.
+const DOT_DONE_COLOUR: &str = "#a1d99b";
+/// Outline colour of tasks waiting on open blockers in the DOT export.
+const DOT_BLOCKED_COLOUR: &str = "#d62728";
+
+/// A task in the dependency graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyNode {
+    pub id: u32,
+    pub name: String,
+    pub status: Status,
+    /// whether the task is in its board's done column
+    pub done: bool,
+}
+
+/// Tasks and the "blocks" links between them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// tasks in ID order
+    pub nodes: Vec<DependencyNode>,
+    /// `(blocker, blocked)` pairs, ordered by blocked and then blocker ID
+    pub edges: Vec<(u32, u32)>,
+}
+
+impl DependencyGraph {
+    /// Builds the graph of `tasks`; links to tasks outside the set are
+    /// left out. `is_done` tells whether a task is finished.
+    pub fn build(tasks: &[Task], is_done: impl Fn(&Task) -> bool) -> Self {
+        let mut nodes: Vec<DependencyNode> = tasks
+            .iter()
+            .map(|task| DependencyNode {
+                id: task.id.unwrap_or_default(),
+                name: task.name.clone(),
+                status: task.status.clone(),
+                done: is_done(task),
+            })
+            .collect();
+        nodes.sort_by_key(|node| node.id);
+        nodes.dedup_by_key(|node| node.id);
+
+        let mut edges: Vec<(u32, u32)> = tasks
+            .iter()
+            .flat_map(|task| {
+                let blocked = task.id.unwrap_or_default();
+                task.blocked_by
+                    .iter()
+                    .map(move |blocker| (*blocker, blocked))
+            })
+            .filter(|(blocker, _)| nodes.iter().any(|node| node.id == *blocker))
+            .collect();
+        edges.sort_by_key(|(blocker, blocked)| (*blocked, *blocker));
+        edges.dedup();
+
+        DependencyGraph { nodes, edges }
+    }
+
+    /// IDs of the unfinished tasks blocking `id`.
+    pub fn open_blockers(&self, id: u32) -> Vec<u32> {
+        self.edges
+            .iter()
+            .filter(|(_, blocked)| *blocked == id)
+            .map(|(blocker, _)| *blocker)
+            .filter(|blocker| self.node(*blocker).is_some_and(|node| !node.done))
+            .collect()
+    }
+
+    /// Returns `true` if the task is unfinished and waits on an unfinished
+    /// blocker.
+    pub fn is_blocked(&self, id: u32) -> bool {
+        self.node(id).is_some_and(|node| !node.done) && !self.open_blockers(id).is_empty()
+    }
+
+    fn node(&self, id: u32) -> Option<&DependencyNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Renders the graph as a Graphviz digraph with edges pointing from
+    /// blocker to blocked task. Finished tasks are filled green and blocked
+    /// tasks outlined red.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [shape=box];\n");
+
+        for node in &self.nodes {
+            let style = if node.done {
+                format!(r#", style=filled, fillcolor="{}""#, DOT_DONE_COLOUR)
+            } else if self.is_blocked(node.id) {
+                format!(r#", color="{}""#, DOT_BLOCKED_COLOUR)
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                dot,
+                r##"    "{}" [label="#{} {}\n{}"{}];"##,
+                node.id,
+                node.id,
+                escape(&node.name),
+                escape(node.status.as_str()),
+                style
+            );
+        }
+
+        for (blocker, blocked) in &self.edges {
+            let _ = writeln!(dot, r#"    "{}" -> "{}";"#, blocker, blocked);
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Escapes text for use inside a quoted DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: u32, name: &str, status: Status, blocked_by: &[u32]) -> Task {
+        let mut task = Task::new(name.to_string(), String::new());
+        task.id = Some(id);
+        task.status = status;
+        task.blocked_by = blocked_by.iter().copied().collect();
+        task
+    }
+
+    fn graph() -> DependencyGraph {
+        let tasks = vec![
+            task(3, "deploy", Status::TODO, &[1, 2, 9]),
+            task(1, "build", Status::DONE, &[]),
+            task(2, "say \"hi\"", Status::DOING, &[1]),
+        ];
+
+        DependencyGraph::build(&tasks, |t| t.status == Status::DONE)
+    }
+
+    #[test]
+    fn build_keeps_links_within_the_set() {
+        let graph = graph();
+
+        let ids: Vec<_> = graph.nodes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(graph.edges, vec![(1, 2), (1, 3), (2, 3)]);
+        assert_eq!(graph.open_blockers(3), vec![2]);
+        assert!(graph.is_blocked(3));
+        assert!(!graph.is_blocked(2));
+    }
+
+    #[test]
+    fn dot_export_styles_done_and_blocked_tasks() {
+        let dot = graph().to_dot();
+
+        assert!(dot.starts_with("digraph dependencies {\n"));
+        assert!(
+            dot.contains(r##""1" [label="#1 build\nDone", style=filled, fillcolor="#a1d99b"];"##)
+        );
+        assert!(dot.contains(r##""2" [label="#2 say \"hi\"\nDoing"];"##));
+        assert!(dot.contains(r##""3" [label="#3 deploy\nTodo", color="#d62728"];"##));
+        assert!(dot.contains(r#""2" -> "3";"#));
+        assert!(dot.ends_with("}\n"));
+    }
+}
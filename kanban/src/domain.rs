@@ -1,33 +1,242 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::board::DEFAULT_BOARD_ID;
+use crate::error::{KanbanError, Result};
+use crate::workflow::Workflow;
 
-/// Represents the possible states of a task during it's lifecycle
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Status {
+/// Represents the column a task sits in during it's lifecycle
+///
+/// Columns are defined by the board's [`Workflow`]; the default workflow
+/// uses [`Status::TODO`], [`Status::DOING`] and [`Status::DONE`].
+/// Serialized as the column name, e.g. `"Todo"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Status(Cow<'static, str>);
+
+impl Status {
     /// task has been created but not yet started
-    Todo,
+    pub const TODO: Status = Status(Cow::Borrowed("Todo"));
     /// task in progress
-    Doing,
+    pub const DOING: Status = Status(Cow::Borrowed("Doing"));
     /// task completed
-    Done,
-    /// invalid status
-    None,
+    pub const DONE: Status = Status(Cow::Borrowed("Done"));
+
+    /// Creates a status for a custom workflow column.
+    pub fn new(name: impl Into<String>) -> Self {
+        Status(Cow::Owned(name.into()))
+    }
+
+    /// Name of the column.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `true` if both statuses name the same column, ignoring case.
+    pub fn matches(&self, name: &str) -> bool {
+        self.0.eq_ignore_ascii_case(name)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+impl FromStr for Status {
+    type Err = KanbanError;
+
+    /// Parses a status from its name.
+    /// The default column names are recognised regardless of case.
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim();
+        if name.is_empty() {
+            return Err(KanbanError::Validation {
+                field: "status",
+                reason: String::from("must not be empty"),
+            });
+        }
+
+        let status = [Status::TODO, Status::DOING, Status::DONE]
+            .into_iter()
+            .find(|status| status.matches(name))
+            .unwrap_or_else(|| Status::new(name));
+
+        Ok(status)
+    }
+}
+
+/// How important a task is relative to others on its board.
+///
+/// Ordered from most to least urgent, so sorting ascending puts
+/// [`Priority::Critical`] first. Serialized in lowercase, e.g. `"high"`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// must be dealt with before anything else
+    Critical,
+    High,
+    #[default]
+    Medium,
+    Low,
+}
+
+impl Priority {
+    /// Stable name used when the priority is stored as text.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Critical => "critical",
+            Priority::High => "high",
+            Priority::Medium => "medium",
+            Priority::Low => "low",
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Priority {
+    type Err = KanbanError;
+
+    /// Parses `critical`, `high`, `medium` or `low` (case-insensitive).
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "critical" => Ok(Priority::Critical),
+            "high" => Ok(Priority::High),
+            "medium" => Ok(Priority::Medium),
+            "low" => Ok(Priority::Low),
+            _ => Err(KanbanError::Validation {
+                field: "priority",
+                reason: format!(
+                    "unknown priority '{}', expected critical, high, medium or low",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+/// A single step on a task's checklist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    /// what needs doing
+    pub text: String,
+    /// whether the step has been completed
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl ChecklistItem {
+    /// Creates an open checklist item.
+    pub fn new(text: String) -> Self {
+        ChecklistItem { text, done: false }
+    }
+}
+
+impl fmt::Display for ChecklistItem {
+    /// Formats the item as `[x] text` or `[ ] text`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = if self.done { 'x' } else { ' ' };
+        write!(f, "[{}] {}", mark, self.text)
+    }
+}
+
+/// How much of a task's checklist and subtasks is complete.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// completed checklist items and finished subtasks
+    pub done: usize,
+    /// all checklist items and subtasks
+    pub total: usize,
+}
+
+impl Progress {
+    /// Share of the work that is complete, from 0 to 100; `None` if the
+    /// task has neither checklist items nor subtasks.
+    pub fn percent(&self) -> Option<u8> {
+        (self.total > 0).then(|| (self.done * 100 / self.total) as u8)
+    }
+
+    /// Returns `true` if nothing is left to do.
+    pub fn is_complete(&self) -> bool {
+        self.done == self.total
+    }
+}
+
+/// Marks a task in progress as blocked, e.g. waiting on another team.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockedFlag {
+    /// what the task is waiting on
+    pub reason: String,
+    /// when the task was flagged
+    pub since: DateTime<Utc>,
 }
 
 /// Represents the properties of a struct
-#[derive(Debug, Clone)]
+///
+/// Serialized with the field names below; timestamps use RFC 3339.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     /// unique identifier
     pub id: Option<u32>,
+    /// board the task belongs to
+    #[serde(default = "default_board_id")]
+    pub board_id: u32,
     /// task name/title
     pub name: String,
     /// detailed description of the task
     pub description: String, // TODO: cast into Option<String> optional field. Can be updated later
     /// current status of the task
-    pub status: Status, // default is Todo set during creation of new task
+    pub status: Status, // default is the workflow's first column, set during creation
     /// when the task was created
     pub created_at: DateTime<Utc>,
     /// when the task was last updated
     pub updated_at: Option<DateTime<Utc>>,
+    /// whether the task was pushed past a WIP limit as an expedite item
+    #[serde(default)]
+    pub expedited: bool,
+    /// how important the task is
+    #[serde(default)]
+    pub priority: Priority,
+    /// when the task should be finished by
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    /// whether the task last reached the done column after its due date
+    #[serde(default)]
+    pub completed_late: bool,
+    /// free-form labels, e.g. `bug` or `frontend`
+    #[serde(default)]
+    pub labels: BTreeSet<String>,
+    /// IDs of the users the task is assigned to
+    #[serde(default)]
+    pub assignees: BTreeSet<u32>,
+    /// ordered steps to complete the task
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// task this one is a subtask of
+    #[serde(default)]
+    pub parent_id: Option<u32>,
+    /// IDs of the tasks that must be done before work on this one starts
+    #[serde(default)]
+    pub blocked_by: BTreeSet<u32>,
+    /// when the task was archived; archived tasks are hidden from the board
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// set while work on the task is blocked
+    #[serde(default)]
+    pub blocked: Option<BlockedFlag>,
 }
 
 impl Task {
@@ -41,69 +250,250 @@ impl Task {
     /// # Returns
     /// A new Task instance with:
     /// - Status set to Todo
+    /// - Placed on the default board
+    /// - Medium priority
     /// - Current UTC timestamp for creation
     /// - No update timestamp
     pub fn new(name: String, description: String) -> Self {
         Task {
             id: None,
+            board_id: DEFAULT_BOARD_ID,
             name,
             description,
-            status: Status::Todo,
+            status: Status::TODO,
             created_at: Utc::now(),
             updated_at: None,
+            expedited: false,
+            priority: Priority::default(),
+            due_at: None,
+            completed_late: false,
+            labels: BTreeSet::new(),
+            assignees: BTreeSet::new(),
+            checklist: Vec::new(),
+            parent_id: None,
+            blocked_by: BTreeSet::new(),
+            archived_at: None,
+            blocked: None,
         }
     }
 
     // validations for a new task
-    pub fn before_add(&self) -> Result<(), String> {
+    pub fn before_add(&self) -> Result<()> {
         if self.name.is_empty() {
-            return Err(String::from("Task name is required"));
+            return Err(KanbanError::Validation {
+                field: "name",
+                reason: String::from("must not be empty"),
+            });
         }
 
         Ok(())
     }
 
-    pub fn before_move_to_doing(&self) -> Result<(), String> {
-        if self.status != Status::Todo {
-            return Err(String::from(
-                "Task must be in the Todo state before marking as in progress",
-            ));
+    /// Checks that `due_at` is a sensible due date for the task.
+    pub fn before_schedule(&self, due_at: &DateTime<Utc>) -> Result<()> {
+        if *due_at < self.created_at {
+            return Err(KanbanError::Validation {
+                field: "due_at",
+                reason: String::from("must not be before the task was created"),
+            });
         }
 
         Ok(())
     }
 
-    pub fn before_move_to_done(&self) -> Result<(), String> {
-        if self.status != Status::Doing {
-            return Err(String::from(
-                "Task must be in progress state before marking as Done",
-            ));
+    /// Checks that `text` can be added to the checklist.
+    pub fn before_add_item(&self, text: &str) -> Result<()> {
+        if text.trim().is_empty() {
+            return Err(KanbanError::Validation {
+                field: "checklist",
+                reason: String::from("item must not be empty"),
+            });
         }
 
         Ok(())
     }
 
-    fn move_to_doing(&mut self) -> Result<&mut Self, String> {
-        match self.status {
-            Status::Todo => {
-                self.status = Status::Doing;
-                Ok(self)
-            }
-            _ => Err(String::from(
-                "Task must be in Todo state to move to in progress",
-            )),
+    /// Looks up a checklist item by its zero-based position.
+    ///
+    /// # Returns
+    /// * `Ok(&mut ChecklistItem)` - The item.
+    /// * `Err(KanbanError::Validation)` - If there is no item at `index`.
+    pub fn checklist_item(&mut self, index: usize) -> Result<&mut ChecklistItem> {
+        let len = self.checklist.len();
+        self.checklist
+            .get_mut(index)
+            .ok_or_else(|| KanbanError::Validation {
+                field: "checklist",
+                reason: format!("no item {} (the task has {} items)", index + 1, len),
+            })
+    }
+
+    /// Checklist progress, not counting subtasks.
+    pub fn checklist_progress(&self) -> Progress {
+        Progress {
+            done: self.checklist.iter().filter(|item| item.done).count(),
+            total: self.checklist.len(),
+        }
+    }
+
+    /// Returns `true` if the task carries `label` (case-insensitive).
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels.contains(&label.trim().to_lowercase())
+    }
+
+    /// Returns `true` if the task has a due date that has passed at `now`.
+    pub fn is_past_due(&self, now: DateTime<Utc>) -> bool {
+        self.due_at.is_some_and(|due| due < now)
+    }
+
+    /// Returns `true` if the task was archived and not restored since.
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// Checks that the workflow allows the task to move to the `to` column.
+    pub fn before_move(&self, workflow: &Workflow, to: &Status) -> Result<()> {
+        if self.is_archived() {
+            return Err(KanbanError::Validation {
+                field: "task",
+                reason: String::from("archived tasks cannot move; restore the task first"),
+            });
+        }
+        if !workflow.allows(&self.status, to) {
+            return Err(KanbanError::InvalidTransition {
+                from: self.status.clone(),
+                to: to.clone(),
+            });
+        }
+        if workflow.requires_assignee()
+            && workflow.is_started(to.as_str())
+            && self.assignees.is_empty()
+        {
+            return Err(KanbanError::Validation {
+                field: "assignee",
+                reason: format!("task must be assigned before it moves to {}", to),
+            });
+        }
+
+        Ok(())
+    }
+
+    // validations for moving the task to another board
+    pub fn before_transfer(&self) -> Result<()> {
+        if self.is_archived() {
+            return Err(KanbanError::Validation {
+                field: "task",
+                reason: String::from("archived tasks cannot move; restore the task first"),
+            });
+        }
+
+        Ok(())
+    }
+
+    // validations for flagging the task as blocked
+    pub fn before_block(&self, workflow: &Workflow, reason: &str) -> Result<()> {
+        let status = self.status.as_str();
+        if self.is_archived() || !workflow.is_started(status) || workflow.is_done(status) {
+            return Err(KanbanError::Validation {
+                field: "task",
+                reason: String::from("only tasks in progress can be blocked"),
+            });
         }
+        if self.blocked.is_some() {
+            return Err(KanbanError::Validation {
+                field: "task",
+                reason: String::from("task is already blocked"),
+            });
+        }
+        if reason.trim().is_empty() {
+            return Err(KanbanError::Validation {
+                field: "reason",
+                reason: String::from("must not be empty"),
+            });
+        }
+
+        Ok(())
     }
 
-    fn move_to_done(&mut self) -> Result<&mut Self, String> {
-        match self.status {
-            Status::Doing => {
-                self.status = Status::Done;
-                Ok(self)
-            }
-            _ => Err(String::from(
-                "Task must be in progress state to mark as completed",
-            )),
+    // validations for sending the task back to an earlier column
+    pub fn before_move_back(&self, reason: &str) -> Result<()> {
+        if self.is_archived() {
+            return Err(KanbanError::Validation {
+                field: "task",
+                reason: String::from("archived tasks cannot move; restore the task first"),
+            });
         }
+        if reason.trim().is_empty() {
+            return Err(KanbanError::Validation {
+                field: "reason",
+                reason: String::from("must not be empty"),
+            });
+        }
+
+        Ok(())
     }
+
+    /// Checks that the task may enter a started column given the IDs of
+    /// its unfinished blockers.
+    pub fn before_start(&self, open_blockers: &[u32]) -> Result<()> {
+        if !open_blockers.is_empty() {
+            return Err(KanbanError::Blocked {
+                id: self.id.unwrap_or_default(),
+                blockers: open_blockers.to_vec(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the task may enter the done column given the `progress`
+    /// of its checklist and subtasks.
+    pub fn before_move_to_done(&self, workflow: &Workflow, progress: &Progress) -> Result<()> {
+        if let Some(flag) = &self.blocked {
+            return Err(KanbanError::Validation {
+                field: "task",
+                reason: format!("task is blocked ({}); unblock it first", flag.reason),
+            });
+        }
+        if !workflow.allows_incomplete_subtasks() && !progress.is_complete() {
+            return Err(KanbanError::Validation {
+                field: "checklist",
+                reason: format!(
+                    "{} of {} checklist items and subtasks are still open",
+                    progress.total - progress.done,
+                    progress.total
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Normalises a label to the form it is stored in: trimmed and lowercase.
+///
+/// # Returns
+/// * `Ok(String)` - The normalised label.
+/// * `Err(KanbanError::Validation)` - If the label is empty or contains
+///   whitespace or commas.
+pub fn normalize_label(label: &str) -> Result<String> {
+    let label = label.trim().to_lowercase();
+    if label.is_empty() {
+        return Err(KanbanError::Validation {
+            field: "label",
+            reason: String::from("must not be empty"),
+        });
+    }
+    if label.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err(KanbanError::Validation {
+            field: "label",
+            reason: format!("'{}' must not contain whitespace or commas", label),
+        });
+    }
+
+    Ok(label)
+}
+
+fn default_board_id() -> u32 {
+    DEFAULT_BOARD_ID
 }
@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::Status;
+
+/// Errors produced by the domain, repositories and service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KanbanError {
+    /// no task exists with the given ID
+    NotFound { id: u32 },
+    /// another task on the same board already uses this name
+    DuplicateName { name: String },
+    /// no board exists with the given ID
+    BoardNotFound { id: u32 },
+    /// another board already uses this name
+    DuplicateBoardName { name: String },
+    /// no user exists with the given ID
+    UserNotFound { id: u32 },
+    /// another user already uses this name
+    DuplicateUserName { name: String },
+    /// no comment exists with the given ID
+    CommentNotFound { id: u32 },
+    /// a field holds a value that breaks a domain rule
+    Validation { field: &'static str, reason: String },
+    /// the task cannot move between the two statuses
+    InvalidTransition { from: Status, to: Status },
+    /// work on the task cannot start while these tasks are unfinished
+    Blocked { id: u32, blockers: Vec<u32> },
+    /// the target column already holds as many tasks as it allows
+    WipLimitExceeded { column: Status, limit: usize },
+    /// the storage backend failed to read or write tasks
+    Storage { reason: String },
+}
+
+/// Shorthand for results whose error is a [`KanbanError`].
+pub type Result<T> = std::result::Result<T, KanbanError>;
+
+impl fmt::Display for KanbanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KanbanError::NotFound { id } => write!(f, "Task {} not found", id),
+            KanbanError::DuplicateName { name } => {
+                write!(f, "Task with name '{}' already exists", name)
+            }
+            KanbanError::BoardNotFound { id } => write!(f, "Board {} not found", id),
+            KanbanError::DuplicateBoardName { name } => {
+                write!(f, "Board with name '{}' already exists", name)
+            }
+            KanbanError::UserNotFound { id } => write!(f, "User {} not found", id),
+            KanbanError::DuplicateUserName { name } => {
+                write!(f, "User with name '{}' already exists", name)
+            }
+            KanbanError::CommentNotFound { id } => write!(f, "Comment {} not found", id),
+            KanbanError::Validation { field, reason } => {
+                write!(f, "Invalid {}: {}", field, reason)
+            }
+            KanbanError::InvalidTransition { from, to } => {
+                write!(f, "Cannot move task from {} to {}", from, to)
+            }
+            KanbanError::Blocked { id, blockers } => {
+                let blockers: Vec<String> = blockers.iter().map(u32::to_string).collect();
+                write!(
+                    f,
+                    "Task {} is blocked by unfinished tasks {}",
+                    id,
+                    blockers.join(", ")
+                )
+            }
+            KanbanError::WipLimitExceeded { column, limit } => {
+                write!(
+                    f,
+                    "Column {} has reached its WIP limit of {}",
+                    column, limit
+                )
+            }
+            KanbanError::Storage { reason } => write!(f, "Storage error: {}", reason),
+        }
+    }
+}
+
+impl Error for KanbanError {}
+
+impl From<std::io::Error> for KanbanError {
+    fn from(err: std::io::Error) -> Self {
+        KanbanError::Storage {
+            reason: err.to_string(),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for KanbanError {
+    fn from(err: rusqlite::Error) -> Self {
+        KanbanError::Storage {
+            reason: err.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for KanbanError {
+    fn from(err: serde_json::Error) -> Self {
+        KanbanError::Storage {
+            reason: err.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_messages() {
+        assert_eq!(
+            KanbanError::NotFound { id: 7 }.to_string(),
+            "Task 7 not found"
+        );
+        assert_eq!(
+            KanbanError::DuplicateName {
+                name: "task1".to_string()
+            }
+            .to_string(),
+            "Task with name 'task1' already exists"
+        );
+        assert_eq!(
+            KanbanError::BoardNotFound { id: 3 }.to_string(),
+            "Board 3 not found"
+        );
+        assert_eq!(
+            KanbanError::Validation {
+                field: "name",
+                reason: "must not be empty".to_string()
+            }
+            .to_string(),
+            "Invalid name: must not be empty"
+        );
+        assert_eq!(
+            KanbanError::InvalidTransition {
+                from: Status::DONE,
+                to: Status::DOING
+            }
+            .to_string(),
+            "Cannot move task from Done to Doing"
+        );
+        assert_eq!(
+            KanbanError::Blocked {
+                id: 4,
+                blockers: vec![1, 2]
+            }
+            .to_string(),
+            "Task 4 is blocked by unfinished tasks 1, 2"
+        );
+    }
+}
@@ -0,0 +1,1249 @@
+//! Event-sourced task repository.
+//!
+//! Instead of the current state, the workspace is stored as an append-only
+//! log of domain events in a JSON Lines file, one record per line:
+//!
+//! ```json
+//! {"seq":1,"at":"2025-06-01T09:30:00Z","type":"task_created","task":{"id":1,"name":"write docs",...}}
+//! {"seq":2,"at":"2025-06-01T09:30:00Z","type":"history_recorded","event":{"task_id":1,"kind":"created",...}}
+//! {"seq":3,"at":"2025-06-02T14:00:00Z","type":"task_moved","id":1,"status":"Doing","updated_at":"2025-06-02T14:00:00Z"}
+//! ```
+//!
+//! `seq` numbers the records from 1 without gaps and `at` is when the
+//! record was appended. Tasks, boards, users and comments are recorded
+//! whole when they are created or changed (`task_updated`, `board_updated`,
+//! ...); a change of nothing but a task's column is recorded as the smaller
+//! `task_moved`. `history_recorded` appends to a task's history. The
+//! workspace starts out holding only the default board.
+//!
+//! The records of a unit of work (see [`TaskRepository::begin`]) are
+//! written together; every one but the last carries `"more": true`. They
+//! are only replayed once the last one is read, so a unit of work cut short
+//! is dropped like a torn line.
+//!
+//! Opening the log rebuilds the state by replaying every record. To bound
+//! startup time, the state is also written to a snapshot file next to the
+//! log (the log's path with `.snapshot` appended) every
+//! [`SNAPSHOT_INTERVAL`] records. The snapshot remembers how far into the
+//! log it reaches and which record it ends with, so only the records after
+//! it are replayed; a snapshot that does not end with a record of the log
+//! is ignored and the whole log is replayed instead. A last line without its
+//! terminating newline is what is left of an append that was cut short,
+//! e.g. by a crash; it is ignored on open and cut off by the next append.
+//! Records appended by another handle after this one read the log are never
+//! cut off: appending fails instead, and the log has to be reopened.
+//!
+//! Replaying only the records up to a point in time shows the workspace as
+//! it was then, see [`EventSourcedTaskRepository::open_until`].
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::board::{Board, DEFAULT_BOARD_ID};
+use crate::comment::Comment;
+use crate::error::{KanbanError, Result};
+use crate::history::TaskEvent;
+use crate::query::TaskQuery;
+use crate::repository::TaskRepository;
+use crate::user::User;
+use crate::{Status, Task};
+
+/// Number of records appended between two snapshots.
+pub const SNAPSHOT_INTERVAL: u64 = 100;
+
+/// Version of the snapshot format written by this repository.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A change to the workspace, as stored in the log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    TaskCreated {
+        task: Task,
+    },
+    /// the task changed column and nothing else
+    TaskMoved {
+        id: u32,
+        status: Status,
+        updated_at: Option<DateTime<Utc>>,
+    },
+    TaskUpdated {
+        task: Task,
+    },
+    /// the task is removed along with its history and comments
+    TaskDeleted {
+        id: u32,
+    },
+    HistoryRecorded {
+        event: TaskEvent,
+    },
+    BoardCreated {
+        board: Board,
+    },
+    BoardUpdated {
+        board: Board,
+    },
+    BoardDeleted {
+        id: u32,
+    },
+    UserCreated {
+        user: User,
+    },
+    UserUpdated {
+        user: User,
+    },
+    CommentAdded {
+        comment: Comment,
+    },
+    CommentUpdated {
+        comment: Comment,
+    },
+    CommentDeleted {
+        id: u32,
+    },
+}
+
+/// A line of the log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// position of the record in the log, starting at 1
+    pub seq: u64,
+    /// when the record was appended
+    pub at: DateTime<Utc>,
+    /// more records written in the same unit of work follow
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub more: bool,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+}
+
+/// State rebuilt from the log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Workspace {
+    boards: Vec<Board>,
+    tasks: Vec<Task>,
+    events: Vec<TaskEvent>,
+    users: Vec<User>,
+    comments: Vec<Comment>,
+    next_task_id: u32,
+    next_board_id: u32,
+    next_user_id: u32,
+    next_comment_id: u32,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Workspace {
+            boards: vec![Board::default()],
+            tasks: Vec::new(),
+            events: Vec::new(),
+            users: Vec::new(),
+            comments: Vec::new(),
+            next_task_id: 1,
+            next_board_id: DEFAULT_BOARD_ID + 1,
+            next_user_id: 1,
+            next_comment_id: 1,
+        }
+    }
+}
+
+impl Workspace {
+    /// Applies a recorded change. Records were checked before they were
+    /// appended, so one that no longer matches the state is skipped.
+    fn apply(&mut self, event: DomainEvent) {
+        match event {
+            DomainEvent::TaskCreated { task } => {
+                let id = task.id.unwrap_or_default();
+                self.next_task_id = self.next_task_id.max(id + 1);
+                self.tasks.push(task);
+            }
+            DomainEvent::TaskMoved {
+                id,
+                status,
+                updated_at,
+            } => {
+                if let Some(task) = self.tasks.iter_mut().find(|t| t.id == Some(id)) {
+                    task.status = status;
+                    task.updated_at = updated_at;
+                }
+            }
+            DomainEvent::TaskUpdated { task } => {
+                if let Some(pos) = self.tasks.iter().position(|t| t.id == task.id) {
+                    self.tasks[pos] = task;
+                }
+            }
+            DomainEvent::TaskDeleted { id } => {
+                self.tasks.retain(|t| t.id != Some(id));
+                self.events.retain(|e| e.task_id != id);
+                self.comments.retain(|c| c.task_id != id);
+            }
+            DomainEvent::HistoryRecorded { event } => self.events.push(event),
+            DomainEvent::BoardCreated { board } => {
+                let id = board.id.unwrap_or_default();
+                self.next_board_id = self.next_board_id.max(id + 1);
+                self.boards.push(board);
+            }
+            DomainEvent::BoardUpdated { board } => {
+                if let Some(pos) = self.boards.iter().position(|b| b.id == board.id) {
+                    self.boards[pos] = board;
+                }
+            }
+            DomainEvent::BoardDeleted { id } => self.boards.retain(|b| b.id != Some(id)),
+            DomainEvent::UserCreated { user } => {
+                let id = user.id.unwrap_or_default();
+                self.next_user_id = self.next_user_id.max(id + 1);
+                self.users.push(user);
+            }
+            DomainEvent::UserUpdated { user } => {
+                if let Some(pos) = self.users.iter().position(|u| u.id == user.id) {
+                    self.users[pos] = user;
+                }
+            }
+            DomainEvent::CommentAdded { comment } => {
+                let id = comment.id.unwrap_or_default();
+                self.next_comment_id = self.next_comment_id.max(id + 1);
+                self.comments.push(comment);
+            }
+            DomainEvent::CommentUpdated { comment } => {
+                if let Some(pos) = self.comments.iter().position(|c| c.id == comment.id) {
+                    self.comments[pos] = comment;
+                }
+            }
+            DomainEvent::CommentDeleted { id } => self.comments.retain(|c| c.id != Some(id)),
+        }
+    }
+}
+
+/// The document stored in the snapshot file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    /// sequence number of the last record included
+    seq: u64,
+    /// when that record was appended
+    at: Option<DateTime<Utc>>,
+    /// length of the log, in bytes, up to and including that record
+    offset: u64,
+    /// position in the log, in bytes, where that record starts
+    record_start: u64,
+    state: Workspace,
+}
+
+/// Records of a unit of work that are not written yet, along with the
+/// state from before it.
+#[derive(Debug)]
+struct UnitOfWork {
+    records: Vec<LogRecord>,
+    state: Workspace,
+    seq: u64,
+    last_at: Option<DateTime<Utc>>,
+}
+
+/// Task repository persisted as an append-only log of domain events.
+/// The state is held in memory; every change appends one record to the log,
+/// or the records of a unit of work are appended together.
+#[derive(Debug)]
+pub struct EventSourcedTaskRepository {
+    path: PathBuf,
+    state: Workspace,
+    /// sequence number of the last applied record
+    seq: u64,
+    /// when the last applied record was appended
+    last_at: Option<DateTime<Utc>>,
+    /// length of the log, in bytes, up to the last applied record
+    offset: u64,
+    /// position in the log, in bytes, where the last applied record starts
+    record_start: u64,
+    /// sequence number of the last record covered by a snapshot
+    snapshot_seq: u64,
+    snapshot_interval: u64,
+    /// point in time the state was replayed up to; the repository is read
+    /// only when set
+    until: Option<DateTime<Utc>>,
+    /// unit of work in progress, if any
+    unit: Option<UnitOfWork>,
+}
+
+impl EventSourcedTaskRepository {
+    /// Opens the event log stored at `path` and replays it.
+    ///
+    /// A missing log is treated as a workspace holding only an empty
+    /// default board; it is created on the first mutation.
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - The repository holding the current state.
+    /// * `Err(KanbanError::Storage)` - If the log or snapshot cannot be read
+    ///   or a record is malformed or out of sequence.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::replay(path.as_ref(), None)
+    }
+
+    /// Opens the event log stored at `path` and replays only the records
+    /// appended up to `until`, showing the workspace as it was then.
+    ///
+    /// The repository is read only: every mutation fails with
+    /// `KanbanError::Storage`.
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - The repository holding the state at `until`.
+    /// * `Err(KanbanError::Storage)` - If the log or snapshot cannot be read
+    ///   or a record is malformed or out of sequence.
+    pub fn open_until(path: impl AsRef<Path>, until: DateTime<Utc>) -> Result<Self> {
+        Self::replay(path.as_ref(), Some(until))
+    }
+
+    /// Takes a snapshot every `interval` records instead of every
+    /// [`SNAPSHOT_INTERVAL`].
+    pub fn with_snapshot_interval(mut self, interval: u64) -> Self {
+        self.snapshot_interval = interval.max(1);
+        self
+    }
+
+    /// Path of the event log.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the snapshot file kept next to the log.
+    pub fn snapshot_path(&self) -> PathBuf {
+        snapshot_path(&self.path)
+    }
+
+    /// Sequence number of the last record replayed or appended.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Writes a snapshot of the current state, so the next open only
+    /// replays records appended after it.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the snapshot was written.
+    /// * `Err(KanbanError::Storage)` - If the repository is read only, a
+    ///   unit of work is in progress or the file cannot be written.
+    pub fn snapshot(&mut self) -> Result<()> {
+        self.check_writable()?;
+        if self.unit.is_some() {
+            return Err(KanbanError::Storage {
+                reason: String::from("cannot take a snapshot during a unit of work"),
+            });
+        }
+
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            seq: self.seq,
+            at: self.last_at,
+            offset: self.offset,
+            record_start: self.record_start,
+            state: self.state.clone(),
+        };
+
+        let path = self.snapshot_path();
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp_path = path.with_file_name(tmp_name);
+
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, &snapshot)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        drop(writer);
+
+        fs::rename(&tmp_path, &path)?;
+        self.snapshot_seq = self.seq;
+
+        Ok(())
+    }
+
+    /// Rebuilds the state from the snapshot, if usable, and the records
+    /// after it, stopping at the first record appended after `until`.
+    fn replay(path: &Path, until: Option<DateTime<Utc>>) -> Result<Self> {
+        let mut repo = EventSourcedTaskRepository {
+            path: path.to_path_buf(),
+            state: Workspace::default(),
+            seq: 0,
+            last_at: None,
+            offset: 0,
+            record_start: 0,
+            snapshot_seq: 0,
+            snapshot_interval: SNAPSHOT_INTERVAL,
+            until,
+            unit: None,
+        };
+        if !path.exists() {
+            return Ok(repo);
+        }
+
+        if let Some(snapshot) = load_snapshot(path)? {
+            let in_range = match (until, snapshot.at) {
+                (Some(until), Some(at)) => at <= until,
+                _ => true,
+            };
+            if in_range && snapshot_matches_log(path, &snapshot)? {
+                repo.state = snapshot.state;
+                repo.seq = snapshot.seq;
+                repo.last_at = snapshot.at;
+                repo.offset = snapshot.offset;
+                repo.record_start = snapshot.record_start;
+                repo.snapshot_seq = snapshot.seq;
+            }
+        }
+
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(repo.offset))?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        // records of a unit of work are only applied once its last one is read
+        let mut unit = Vec::new();
+        let mut position = repo.offset;
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)? as u64;
+            if read == 0 {
+                break;
+            }
+            // a last line without its newline is an append that was cut
+            // short; it is dropped and overwritten by the next append
+            if !line.ends_with('\n') {
+                break;
+            }
+            let start = position;
+            position += read;
+            if line.trim().is_empty() {
+                if unit.is_empty() {
+                    repo.offset = position;
+                }
+                continue;
+            }
+
+            let expected = repo.seq + unit.len() as u64 + 1;
+            let record: LogRecord =
+                serde_json::from_str(&line).map_err(|err| KanbanError::Storage {
+                    reason: format!("record {} is malformed: {}", expected, err),
+                })?;
+            if record.seq != expected {
+                return Err(KanbanError::Storage {
+                    reason: format!("expected record {} but found {}", expected, record.seq),
+                });
+            }
+            if until.is_some_and(|until| record.at > until) {
+                break;
+            }
+
+            let more = record.more;
+            unit.push(record);
+            if more {
+                continue;
+            }
+            for record in unit.drain(..) {
+                repo.state.apply(record.event);
+                repo.seq = record.seq;
+                repo.last_at = Some(record.at);
+            }
+            repo.record_start = start;
+            repo.offset = position;
+        }
+
+        Ok(repo)
+    }
+
+    /// Appends `event` to the log and applies it to the state. During a unit
+    /// of work the record is only applied and written on commit.
+    ///
+    /// The state is only changed once the record has been written, so a
+    /// failed append leaves the repository unchanged.
+    fn append(&mut self, event: DomainEvent) -> Result<()> {
+        self.check_writable()?;
+
+        let record = LogRecord {
+            seq: self.seq + 1,
+            at: Utc::now(),
+            more: false,
+            event,
+        };
+        if let Some(unit) = &mut self.unit {
+            self.state.apply(record.event.clone());
+            self.seq = record.seq;
+            self.last_at = Some(record.at);
+            unit.records.push(record);
+            return Ok(());
+        }
+
+        self.write(std::slice::from_ref(&record))?;
+        self.state.apply(record.event);
+        self.seq = record.seq;
+        self.last_at = Some(record.at);
+        self.snapshot_if_due();
+
+        Ok(())
+    }
+
+    /// Writes `records` to the log with a single write, marking all but the
+    /// last as followed by more of the same unit of work.
+    ///
+    /// Fails if another handle appended records since this one read the
+    /// log; only what is left of an append that was cut short is dropped.
+    fn write(&mut self, records: &[LogRecord]) -> Result<()> {
+        self.check_log_unchanged()?;
+
+        let mut lines = String::new();
+        let mut last_start = self.offset;
+        for (i, record) in records.iter().enumerate() {
+            let record = LogRecord {
+                more: i + 1 < records.len(),
+                ..record.clone()
+            };
+            last_start = self.offset + lines.len() as u64;
+            lines.push_str(&serde_json::to_string(&record)?);
+            lines.push('\n');
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if file.metadata()?.len() > self.offset {
+            file.set_len(self.offset)?;
+        }
+        file.write_all(lines.as_bytes())?;
+        file.sync_data()?;
+
+        self.record_start = last_start;
+        self.offset += lines.len() as u64;
+
+        Ok(())
+    }
+
+    /// Takes a snapshot once enough records were appended since the last.
+    fn snapshot_if_due(&mut self) {
+        if self.seq - self.snapshot_seq >= self.snapshot_interval {
+            // the records are already durable; a missing snapshot only makes
+            // the next open replay more of the log
+            let _ = self.snapshot();
+        }
+    }
+
+    /// Fails if the log holds complete records past the last one applied,
+    /// i.e. another handle appended to it. What is left of an aborted
+    /// append - a line without its newline, or records of a unit of work
+    /// whose last record is missing - is fine.
+    fn check_log_unchanged(&self) -> Result<()> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut rest = String::new();
+        file.read_to_string(&mut rest)?;
+
+        let complete = rest.rsplit_once('\n').map_or("", |(lines, _)| lines);
+        let aborted = complete.lines().all(|line| {
+            line.trim().is_empty()
+                || serde_json::from_str::<LogRecord>(line).is_ok_and(|record| record.more)
+        });
+        if !aborted {
+            return Err(KanbanError::Storage {
+                reason: format!(
+                    "{} was changed after record {} was read; reopen it",
+                    self.path.display(),
+                    self.seq
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        match self.until {
+            Some(until) => Err(KanbanError::Storage {
+                reason: format!(
+                    "workspace replayed until {} is read only",
+                    until.to_rfc3339()
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn snapshot_path(log: &Path) -> PathBuf {
+    let mut name = log.file_name().unwrap_or_default().to_os_string();
+    name.push(".snapshot");
+    log.with_file_name(name)
+}
+
+/// Reads the snapshot kept next to `log`, if there is one.
+fn load_snapshot(log: &Path) -> Result<Option<Snapshot>> {
+    let path = snapshot_path(log);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let reader = BufReader::new(File::open(&path)?);
+    // the snapshot only saves replaying the log, so one that cannot be read
+    // is ignored
+    let Ok(snapshot) = serde_json::from_reader::<_, Snapshot>(reader) else {
+        return Ok(None);
+    };
+    if snapshot.version > SNAPSHOT_VERSION {
+        return Err(KanbanError::Storage {
+            reason: format!(
+                "snapshot version {} is newer than supported version {}",
+                snapshot.version, SNAPSHOT_VERSION
+            ),
+        });
+    }
+
+    Ok(Some(snapshot))
+}
+
+/// Checks that `snapshot` was taken of the log at `log`: the record it
+/// ends with must be in the log at the position it remembers.
+fn snapshot_matches_log(log: &Path, snapshot: &Snapshot) -> Result<bool> {
+    if snapshot.seq == 0 {
+        return Ok(snapshot.offset == 0);
+    }
+    if snapshot.record_start >= snapshot.offset || snapshot.offset > fs::metadata(log)?.len() {
+        return Ok(false);
+    }
+
+    let mut file = File::open(log)?;
+    file.seek(SeekFrom::Start(snapshot.record_start))?;
+    let mut line = Vec::new();
+    BufReader::new(file).read_until(b'\n', &mut line)?;
+    if snapshot.record_start + line.len() as u64 != snapshot.offset || !line.ends_with(b"\n") {
+        return Ok(false);
+    }
+
+    Ok(serde_json::from_slice::<LogRecord>(&line)
+        .is_ok_and(|record| record.seq == snapshot.seq && Some(record.at) == snapshot.at))
+}
+
+/// Returns `true` if `task` differs from `previous` only in its column and
+/// the time it was updated.
+fn is_move(previous: &Task, task: &Task) -> bool {
+    if previous.status == task.status {
+        return false;
+    }
+
+    let mut moved = previous.clone();
+    moved.status = task.status.clone();
+    moved.updated_at = task.updated_at;
+    serde_json::to_value(&moved).ok() == serde_json::to_value(task).ok()
+}
+
+impl TaskRepository for EventSourcedTaskRepository {
+    fn get(&self, id: u32) -> Result<Option<Task>> {
+        Ok(self.state.tasks.iter().find(|t| t.id == Some(id)).cloned())
+    }
+
+    fn find_by_name(&self, board_id: u32, name: &str) -> Result<Option<Task>> {
+        Ok(self
+            .state
+            .tasks
+            .iter()
+            .find(|t| t.board_id == board_id && t.name.to_lowercase() == name.to_lowercase())
+            .cloned())
+    }
+
+    fn insert(&mut self, mut task: Task) -> Result<Task> {
+        // IDs are never reused, not even those of deleted tasks
+        task.id = Some(self.state.next_task_id);
+        self.append(DomainEvent::TaskCreated { task: task.clone() })?;
+
+        Ok(task)
+    }
+
+    fn update(&mut self, task: Task) -> Result<Task> {
+        let previous = self
+            .get(task.id.unwrap_or_default())?
+            .ok_or(KanbanError::NotFound {
+                id: task.id.unwrap_or_default(),
+            })?;
+
+        let event = if is_move(&previous, &task) {
+            DomainEvent::TaskMoved {
+                id: task.id.unwrap_or_default(),
+                status: task.status.clone(),
+                updated_at: task.updated_at,
+            }
+        } else {
+            DomainEvent::TaskUpdated { task: task.clone() }
+        };
+        self.append(event)?;
+
+        Ok(task)
+    }
+
+    fn delete(&mut self, id: u32) -> Result<Task> {
+        let task = self.get(id)?.ok_or(KanbanError::NotFound { id })?;
+        self.append(DomainEvent::TaskDeleted { id })?;
+
+        Ok(task)
+    }
+
+    fn list(&self, query: &TaskQuery) -> Result<Vec<Task>> {
+        Ok(query.apply(self.state.tasks.iter().cloned()))
+    }
+
+    fn append_event(&mut self, event: TaskEvent) -> Result<()> {
+        self.append(DomainEvent::HistoryRecorded { event })
+    }
+
+    fn history(&self, task_id: u32) -> Result<Vec<TaskEvent>> {
+        Ok(self
+            .state
+            .events
+            .iter()
+            .filter(|e| e.task_id == task_id)
+            .cloned()
+            .collect())
+    }
+
+    fn get_board(&self, id: u32) -> Result<Option<Board>> {
+        Ok(self.state.boards.iter().find(|b| b.id == Some(id)).cloned())
+    }
+
+    fn list_boards(&self) -> Result<Vec<Board>> {
+        Ok(self.state.boards.clone())
+    }
+
+    fn insert_board(&mut self, mut board: Board) -> Result<Board> {
+        // IDs are never reused, not even those of deleted boards
+        board.id = Some(self.state.next_board_id);
+        self.append(DomainEvent::BoardCreated {
+            board: board.clone(),
+        })?;
+
+        Ok(board)
+    }
+
+    fn update_board(&mut self, board: Board) -> Result<Board> {
+        if !self.state.boards.iter().any(|b| b.id == board.id) {
+            return Err(KanbanError::BoardNotFound {
+                id: board.id.unwrap_or_default(),
+            });
+        }
+        self.append(DomainEvent::BoardUpdated {
+            board: board.clone(),
+        })?;
+
+        Ok(board)
+    }
+
+    fn delete_board(&mut self, id: u32) -> Result<Board> {
+        let board = self
+            .get_board(id)?
+            .ok_or(KanbanError::BoardNotFound { id })?;
+        self.append(DomainEvent::BoardDeleted { id })?;
+
+        Ok(board)
+    }
+
+    fn get_user(&self, id: u32) -> Result<Option<User>> {
+        Ok(self.state.users.iter().find(|u| u.id == Some(id)).cloned())
+    }
+
+    fn list_users(&self) -> Result<Vec<User>> {
+        Ok(self.state.users.clone())
+    }
+
+    fn insert_user(&mut self, mut user: User) -> Result<User> {
+        user.id = Some(self.state.next_user_id);
+        self.append(DomainEvent::UserCreated { user: user.clone() })?;
+
+        Ok(user)
+    }
+
+    fn update_user(&mut self, user: User) -> Result<User> {
+        if !self.state.users.iter().any(|u| u.id == user.id) {
+            return Err(KanbanError::UserNotFound {
+                id: user.id.unwrap_or_default(),
+            });
+        }
+        self.append(DomainEvent::UserUpdated { user: user.clone() })?;
+
+        Ok(user)
+    }
+
+    fn get_comment(&self, id: u32) -> Result<Option<Comment>> {
+        Ok(self
+            .state
+            .comments
+            .iter()
+            .find(|c| c.id == Some(id))
+            .cloned())
+    }
+
+    fn list_comments(&self, task_id: u32) -> Result<Vec<Comment>> {
+        Ok(self
+            .state
+            .comments
+            .iter()
+            .filter(|c| c.task_id == task_id)
+            .cloned()
+            .collect())
+    }
+
+    fn insert_comment(&mut self, mut comment: Comment) -> Result<Comment> {
+        // IDs are never reused, not even those of deleted comments
+        comment.id = Some(self.state.next_comment_id);
+        self.append(DomainEvent::CommentAdded {
+            comment: comment.clone(),
+        })?;
+
+        Ok(comment)
+    }
+
+    fn update_comment(&mut self, comment: Comment) -> Result<Comment> {
+        if !self.state.comments.iter().any(|c| c.id == comment.id) {
+            return Err(KanbanError::CommentNotFound {
+                id: comment.id.unwrap_or_default(),
+            });
+        }
+        self.append(DomainEvent::CommentUpdated {
+            comment: comment.clone(),
+        })?;
+
+        Ok(comment)
+    }
+
+    fn delete_comment(&mut self, id: u32) -> Result<Comment> {
+        let comment = self
+            .get_comment(id)?
+            .ok_or(KanbanError::CommentNotFound { id })?;
+        self.append(DomainEvent::CommentDeleted { id })?;
+
+        Ok(comment)
+    }
+
+    fn begin(&mut self) -> Result<()> {
+        self.check_writable()?;
+        if self.unit.is_some() {
+            return Err(KanbanError::Storage {
+                reason: String::from("a unit of work is already in progress"),
+            });
+        }
+
+        self.unit = Some(UnitOfWork {
+            records: Vec::new(),
+            state: self.state.clone(),
+            seq: self.seq,
+            last_at: self.last_at,
+        });
+        Ok(())
+    }
+
+    /// Appends the records of the unit of work with a single write; if that
+    /// fails, the state from before the unit of work is put back.
+    fn commit(&mut self) -> Result<()> {
+        let Some(unit) = self.unit.take() else {
+            return Err(KanbanError::Storage {
+                reason: String::from("no unit of work in progress"),
+            });
+        };
+        if unit.records.is_empty() {
+            return Ok(());
+        }
+
+        if let Err(err) = self.write(&unit.records) {
+            self.state = unit.state;
+            self.seq = unit.seq;
+            self.last_at = unit.last_at;
+            return Err(err);
+        }
+        self.snapshot_if_due();
+
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        let Some(unit) = self.unit.take() else {
+            return Err(KanbanError::Storage {
+                reason: String::from("no unit of work in progress"),
+            });
+        };
+
+        self.state = unit.state;
+        self.seq = unit.seq;
+        self.last_at = unit.last_at;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod eventsourced_repo_tests {
+    use super::*;
+    use crate::history::EventKind;
+    use crate::repository::contract_tests;
+    use tempfile::TempDir;
+
+    struct Setup {
+        dir: TempDir,
+        repo: EventSourcedTaskRepository,
+    }
+
+    impl Setup {
+        fn new() -> Self {
+            let dir = TempDir::new().expect("temp dir not created");
+            let mut repo = EventSourcedTaskRepository::open(dir.path().join("board.jsonl"))
+                .expect("not opened");
+
+            repo.insert(Task::new("task1".to_string(), "description1".to_string()))
+                .expect("task not inserted");
+            repo.insert(Task::new("task2".to_string(), "description2".to_string()))
+                .expect("task not inserted");
+
+            Setup { dir, repo }
+        }
+
+        fn reopen(&self) -> EventSourcedTaskRepository {
+            EventSourcedTaskRepository::open(self.dir.path().join("board.jsonl"))
+                .expect("not reopened")
+        }
+
+        fn records(&self) -> Vec<serde_json::Value> {
+            fs::read_to_string(self.repo.path())
+                .expect("log not read")
+                .lines()
+                .map(|line| serde_json::from_str(line).expect("record not parsed"))
+                .collect()
+        }
+
+        fn move_task(&mut self, id: u32, status: Status) {
+            let mut task = self.repo.get(id).unwrap().expect("task not found");
+            task.status = status;
+            task.updated_at = Some(Utc::now());
+            self.repo.update(task).expect("task not moved");
+        }
+    }
+
+    #[test]
+    fn missing_log_opens_empty_board() {
+        let dir = TempDir::new().unwrap();
+        let repo = EventSourcedTaskRepository::open(dir.path().join("board.jsonl")).unwrap();
+
+        assert!(repo.list(&TaskQuery::new()).unwrap().is_empty());
+        assert_eq!(repo.list_boards().unwrap().len(), 1);
+        assert_eq!(repo.seq(), 0);
+        assert!(!repo.path().exists());
+    }
+
+    #[test]
+    fn state_is_rebuilt_by_replaying_the_log() {
+        let mut setup = Setup::new();
+        setup.move_task(1, Status::DOING);
+        let mut task = setup.repo.get(2).unwrap().unwrap();
+        task.name = "renamed".to_string();
+        setup.repo.update(task).unwrap();
+        setup
+            .repo
+            .append_event(TaskEvent::new(1, EventKind::Created, None, None, "alice"))
+            .unwrap();
+        setup
+            .repo
+            .insert_comment(Comment::new(1, "alice".into(), "looks good".into()))
+            .unwrap();
+
+        let reopened = setup.reopen();
+        assert_eq!(reopened.seq(), 6);
+        assert_eq!(reopened.get(1).unwrap().unwrap().status, Status::DOING);
+        assert_eq!(reopened.get(2).unwrap().unwrap().name, "renamed");
+        assert_eq!(reopened.history(1).unwrap().len(), 1);
+        assert_eq!(reopened.list_comments(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn log_records_domain_events() {
+        let mut setup = Setup::new();
+        setup.move_task(1, Status::DOING);
+        let mut task = setup.repo.get(1).unwrap().unwrap();
+        task.priority = crate::Priority::High;
+        setup.repo.update(task).unwrap();
+        setup.repo.delete(2).unwrap();
+
+        let records = setup.records();
+        let types: Vec<_> = records
+            .iter()
+            .map(|r| r["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                "task_created",
+                "task_created",
+                "task_moved",
+                "task_updated",
+                "task_deleted"
+            ]
+        );
+        let seqs: Vec<_> = records.iter().map(|r| r["seq"].as_u64().unwrap()).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+        assert_eq!(records[2]["status"], "Doing");
+    }
+
+    #[test]
+    fn ids_of_deleted_tasks_are_not_reused() {
+        let mut setup = Setup::new();
+        setup.repo.delete(2).unwrap();
+
+        let mut reopened = setup.reopen();
+        let task = reopened
+            .insert(Task::new("task3".into(), String::new()))
+            .unwrap();
+        assert_eq!(task.id, Some(3));
+    }
+
+    #[test]
+    fn ids_of_deleted_boards_and_comments_are_not_reused() {
+        let mut setup = Setup::new();
+        let board = setup
+            .repo
+            .insert_board(Board::new("ops".into(), Default::default()))
+            .unwrap();
+        setup.repo.delete_board(board.id.unwrap()).unwrap();
+        let comment = |body: &str| Comment::new(1, "alice".into(), body.into());
+        setup.repo.insert_comment(comment("first")).unwrap();
+        setup.repo.insert_comment(comment("second")).unwrap();
+        setup.repo.delete_comment(2).unwrap();
+        setup.repo.insert_user(User::new("alice".into())).unwrap();
+
+        let mut reopened = setup.reopen();
+        let board = reopened
+            .insert_board(Board::new("dev".into(), Default::default()))
+            .unwrap();
+        assert_eq!(board.id, Some(3));
+        assert_eq!(
+            reopened.insert_comment(comment("third")).unwrap().id,
+            Some(3)
+        );
+        assert_eq!(
+            reopened.insert_user(User::new("bob".into())).unwrap().id,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn snapshot_bounds_replay() {
+        let mut setup = Setup::new();
+        let dir = setup.dir.path().to_path_buf();
+        setup.repo = EventSourcedTaskRepository::open(dir.join("board.jsonl"))
+            .unwrap()
+            .with_snapshot_interval(3);
+        assert!(!setup.repo.snapshot_path().exists());
+
+        setup.move_task(1, Status::DOING);
+        assert!(setup.repo.snapshot_path().exists());
+        setup.move_task(2, Status::DOING);
+
+        let reopened = setup.reopen();
+        assert_eq!(reopened.seq(), 4);
+        assert_eq!(reopened.snapshot_seq, 3);
+        assert_eq!(reopened.get(1).unwrap().unwrap().status, Status::DOING);
+        assert_eq!(reopened.get(2).unwrap().unwrap().status, Status::DOING);
+
+        // records replayed from a snapshot must continue its sequence
+        let snapshot = load_snapshot(setup.repo.path()).unwrap().unwrap();
+        assert_eq!(snapshot.seq, 3);
+        assert_eq!(snapshot.state.tasks.len(), 2);
+    }
+
+    #[test]
+    fn snapshot_beyond_the_log_is_ignored() {
+        let mut setup = Setup::new();
+        setup.repo.snapshot().unwrap();
+        fs::write(setup.repo.path(), "").unwrap();
+
+        let reopened = setup.reopen();
+        assert_eq!(reopened.seq(), 0);
+        assert!(reopened.list(&TaskQuery::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn torn_last_line_is_dropped() {
+        let mut setup = Setup::new();
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(setup.repo.path())
+            .unwrap();
+        log.write_all(br#"{"seq":3,"at":"2025"#).unwrap();
+        drop(log);
+
+        let mut reopened = setup.reopen();
+        assert_eq!(reopened.seq(), 2);
+        reopened
+            .insert(Task::new("task3".into(), String::new()))
+            .unwrap();
+        setup.repo = setup.reopen();
+        assert_eq!(setup.repo.seq(), 3);
+        assert_eq!(setup.records().len(), 3);
+        assert_eq!(setup.repo.get(3).unwrap().unwrap().name, "task3");
+    }
+
+    #[test]
+    fn records_appended_by_another_handle_are_kept() {
+        let mut setup = Setup::new();
+        let mut other = setup.reopen();
+        other
+            .insert(Task::new("task3".into(), String::new()))
+            .unwrap();
+
+        assert!(matches!(
+            setup.repo.insert(Task::new("task4".into(), String::new())),
+            Err(KanbanError::Storage { .. })
+        ));
+        assert_eq!(setup.repo.seq(), 2);
+
+        setup.repo = setup.reopen();
+        assert_eq!(setup.records().len(), 3);
+        assert_eq!(setup.repo.get(3).unwrap().unwrap().name, "task3");
+        setup
+            .repo
+            .insert(Task::new("task4".into(), String::new()))
+            .unwrap();
+        assert_eq!(setup.records().len(), 4);
+    }
+
+    #[test]
+    fn malformed_record_inside_the_log_is_rejected() {
+        let setup = Setup::new();
+        let log = fs::read_to_string(setup.repo.path()).unwrap();
+        let last = log.lines().last().unwrap();
+        fs::write(setup.repo.path(), format!("{{\"seq\":1\n{}\n", last)).unwrap();
+
+        let err = EventSourcedTaskRepository::open(setup.repo.path()).unwrap_err();
+        assert!(matches!(err, KanbanError::Storage { .. }));
+    }
+
+    #[test]
+    fn snapshot_of_another_log_is_ignored() {
+        let mut setup = Setup::new();
+        setup.repo.snapshot().unwrap();
+        let snapshot = fs::read(setup.repo.snapshot_path()).unwrap();
+
+        // a longer log whose records do not line up with the snapshot
+        let other = setup.dir.path().join("other.jsonl");
+        let mut repo = EventSourcedTaskRepository::open(&other).unwrap();
+        for name in ["a much longer first task name", "b", "c"] {
+            repo.insert(Task::new(name.into(), String::new())).unwrap();
+        }
+        fs::write(repo.snapshot_path(), snapshot).unwrap();
+
+        let reopened = EventSourcedTaskRepository::open(&other).unwrap();
+        assert_eq!(reopened.seq(), 3);
+        assert_eq!(
+            reopened.get(1).unwrap().unwrap().name,
+            "a much longer first task name"
+        );
+
+        fs::write(repo.snapshot_path(), "not a snapshot").unwrap();
+        assert_eq!(EventSourcedTaskRepository::open(&other).unwrap().seq(), 3);
+    }
+
+    #[test]
+    fn out_of_sequence_record_is_rejected() {
+        let setup = Setup::new();
+        let log = fs::read_to_string(setup.repo.path()).unwrap();
+        let first = log.lines().next().unwrap();
+        fs::write(setup.repo.path(), format!("{}\n{}\n", first, first)).unwrap();
+
+        let err = EventSourcedTaskRepository::open(setup.repo.path()).unwrap_err();
+        assert!(matches!(err, KanbanError::Storage { .. }));
+    }
+
+    #[test]
+    fn replay_until_shows_past_state_read_only() {
+        let mut setup = Setup::new();
+        setup.repo.snapshot().unwrap();
+        let before_move = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        setup.move_task(1, Status::DOING);
+
+        let mut past = EventSourcedTaskRepository::open_until(setup.repo.path(), before_move)
+            .expect("not replayed");
+        assert_eq!(past.seq(), 2);
+        assert_eq!(past.get(1).unwrap().unwrap().status, Status::TODO);
+        assert!(matches!(
+            past.insert(Task::new("task3".into(), String::new())),
+            Err(KanbanError::Storage { .. })
+        ));
+
+        let earliest =
+            setup.repo.get(1).unwrap().unwrap().created_at - chrono::Duration::seconds(1);
+        let empty = EventSourcedTaskRepository::open_until(setup.repo.path(), earliest).unwrap();
+        assert!(empty.list(&TaskQuery::new()).unwrap().is_empty());
+
+        let now = EventSourcedTaskRepository::open_until(setup.repo.path(), Utc::now()).unwrap();
+        assert_eq!(now.get(1).unwrap().unwrap().status, Status::DOING);
+    }
+
+    #[test]
+    fn update_unknown_task_fails() {
+        let mut setup = Setup::new();
+        let mut task = Task::new("ghost".into(), String::new());
+        task.id = Some(42);
+
+        assert!(matches!(
+            setup.repo.update(task),
+            Err(KanbanError::NotFound { id: 42 })
+        ));
+        assert_eq!(setup.repo.seq(), 2);
+    }
+
+    #[test]
+    fn unit_of_work_is_replayed_whole_or_not_at_all() {
+        let mut setup = Setup::new();
+
+        setup.repo.begin().unwrap();
+        setup
+            .repo
+            .insert(Task::new("task3".to_string(), String::new()))
+            .unwrap();
+        setup
+            .repo
+            .append_event(TaskEvent::new(3, EventKind::Created, None, None, "alice"))
+            .unwrap();
+        assert_eq!(setup.records().len(), 2);
+        setup.repo.commit().unwrap();
+
+        let records = setup.records();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[2]["more"], true);
+        assert!(records[3].get("more").is_none());
+        let reopened = setup.reopen();
+        assert_eq!(reopened.seq(), 4);
+        assert_eq!(reopened.history(3).unwrap().len(), 1);
+
+        // a unit of work cut short before its last record is dropped
+        let log = fs::read_to_string(setup.repo.path()).unwrap();
+        let last = log.lines().last().unwrap();
+        fs::write(setup.repo.path(), &log[..log.len() - last.len() - 1]).unwrap();
+        let mut reopened = setup.reopen();
+        assert_eq!(reopened.seq(), 2);
+        assert!(reopened.get(3).unwrap().is_none());
+
+        reopened
+            .insert(Task::new("task3".to_string(), String::new()))
+            .unwrap();
+        assert_eq!(setup.records().len(), 3);
+        assert_eq!(setup.reopen().seq(), 3);
+    }
+
+    #[test]
+    fn units_of_work_commit_or_roll_back() {
+        let mut setup = Setup::new();
+        contract_tests::units_of_work_commit_or_roll_back(&mut setup.repo);
+    }
+
+    #[test]
+    fn names_match_ignoring_unicode_case() {
+        let mut setup = Setup::new();
+        contract_tests::names_match_ignoring_unicode_case(&mut setup.repo);
+    }
+}
@@ -0,0 +1,296 @@
+//! Delivery forecasts from historical throughput.
+//!
+//! The number of tasks finished on each day of a past window is used as a
+//! sample of daily throughput. Each simulation draws random days from that
+//! sample to play out the future; repeating this many times gives a
+//! distribution of outcomes, summarised at 50%, 85% and 95% confidence.
+
+use chrono::{DateTime, Duration, NaiveDate};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::analytics::TaskTimeline;
+use crate::error::{KanbanError, Result};
+
+/// Number of simulations run unless configured otherwise.
+pub const DEFAULT_TRIALS: usize = 10_000;
+
+/// Number of past days sampled unless configured otherwise.
+pub const DEFAULT_HISTORY_DAYS: u32 = 90;
+
+/// Longest future a single simulation plays out, in days.
+const MAX_SIMULATED_DAYS: u32 = 3650;
+
+/// How a forecast samples history and how many simulations it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForecastOptions {
+    /// number of past days used as the throughput sample
+    pub history_days: u32,
+    /// number of simulations
+    pub trials: usize,
+    /// RNG seed for reproducible results; random if unset
+    pub seed: Option<u64>,
+}
+
+impl Default for ForecastOptions {
+    fn default() -> Self {
+        ForecastOptions {
+            history_days: DEFAULT_HISTORY_DAYS,
+            trials: DEFAULT_TRIALS,
+            seed: None,
+        }
+    }
+}
+
+/// Tasks finished per day over a past window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Throughput {
+    /// one entry per day, oldest first
+    pub daily: Vec<u32>,
+}
+
+impl Throughput {
+    /// Counts the tasks finished on each day within `[from, until)`.
+    pub fn from_timelines(timelines: &[TaskTimeline], from: NaiveDate, until: NaiveDate) -> Self {
+        let daily = from
+            .iter_days()
+            .take_while(|date| *date < until)
+            .map(|date| {
+                timelines
+                    .iter()
+                    .filter_map(|t| t.done_at.as_ref().map(DateTime::date_naive))
+                    .filter(|done| *done == date)
+                    .count() as u32
+            })
+            .collect();
+
+        Throughput { daily }
+    }
+
+    /// Total number of tasks finished in the window.
+    pub fn total(&self) -> u32 {
+        self.daily.iter().sum()
+    }
+}
+
+/// Forecast of how many tasks will be finished within a number of days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemsForecast {
+    /// number of days forecast
+    pub days: u32,
+    /// at least this many tasks in half of the simulations
+    pub p50: u32,
+    /// at least this many tasks in 85% of the simulations
+    pub p85: u32,
+    /// at least this many tasks in 95% of the simulations
+    pub p95: u32,
+}
+
+/// Forecast of when a number of tasks will be finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompletionForecast {
+    /// number of tasks forecast
+    pub items: u32,
+    /// finished by this date in half of the simulations
+    pub p50: NaiveDate,
+    /// finished by this date in 85% of the simulations
+    pub p85: NaiveDate,
+    /// finished by this date in 95% of the simulations
+    pub p95: NaiveDate,
+}
+
+/// Monte Carlo simulator drawing from a throughput sample.
+#[derive(Debug, Clone)]
+pub struct MonteCarlo {
+    samples: Vec<u32>,
+    trials: usize,
+    rng: StdRng,
+}
+
+impl MonteCarlo {
+    /// Creates a simulator; a seed in `options` makes the results
+    /// reproducible.
+    ///
+    /// # Returns
+    /// * `Ok(MonteCarlo)` - The simulator.
+    /// * `Err(KanbanError::Validation)` - If no task was finished in the
+    ///   sampled window or `trials` is zero.
+    pub fn new(throughput: &Throughput, options: &ForecastOptions) -> Result<Self> {
+        if throughput.total() == 0 {
+            return Err(KanbanError::Validation {
+                field: "forecast",
+                reason: format!(
+                    "no tasks were finished in the last {} days",
+                    throughput.daily.len()
+                ),
+            });
+        }
+        if options.trials == 0 {
+            return Err(KanbanError::Validation {
+                field: "trials",
+                reason: String::from("must be at least 1"),
+            });
+        }
+
+        let rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+
+        Ok(MonteCarlo {
+            samples: throughput.daily.clone(),
+            trials: options.trials,
+            rng,
+        })
+    }
+
+    /// Forecasts how many tasks will be finished in the next `days` days.
+    pub fn items_within(&mut self, days: u32) -> ItemsForecast {
+        let mut outcomes: Vec<u32> = (0..self.trials)
+            .map(|_| (0..days).map(|_| self.draw()).sum())
+            .collect();
+        outcomes.sort_unstable();
+
+        // "at least N" with X% confidence is the (100 - X)th percentile
+        ItemsForecast {
+            days,
+            p50: percentile(&outcomes, 50),
+            p85: percentile(&outcomes, 15),
+            p95: percentile(&outcomes, 5),
+        }
+    }
+
+    /// Forecasts when `items` tasks will be finished, counting from the day
+    /// after `today`.
+    pub fn completion(&mut self, items: u32, today: NaiveDate) -> CompletionForecast {
+        let mut outcomes: Vec<u32> = (0..self.trials)
+            .map(|_| self.days_to_finish(items))
+            .collect();
+        outcomes.sort_unstable();
+
+        let date = |pct| today + Duration::days(i64::from(percentile(&outcomes, pct)));
+        CompletionForecast {
+            items,
+            p50: date(50),
+            p85: date(85),
+            p95: date(95),
+        }
+    }
+
+    fn days_to_finish(&mut self, items: u32) -> u32 {
+        let mut done = 0;
+        let mut days = 0;
+        while done < items && days < MAX_SIMULATED_DAYS {
+            done += self.draw();
+            days += 1;
+        }
+        days
+    }
+
+    fn draw(&mut self) -> u32 {
+        self.samples[self.rng.random_range(0..self.samples.len())]
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty outcomes.
+fn percentile(sorted: &[u32], pct: usize) -> u32 {
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn options(trials: usize, seed: Option<u64>) -> ForecastOptions {
+        ForecastOptions {
+            trials,
+            seed,
+            ..ForecastOptions::default()
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+    }
+
+    fn done_on(task_id: u32, day: u32) -> TaskTimeline {
+        let at = Utc.with_ymd_and_hms(2025, 6, day, 15, 0, 0).unwrap();
+        TaskTimeline {
+            task_id,
+            created_at: at,
+            started_at: Some(at),
+            done_at: Some(at),
+            completed_late: None,
+            reopened: 0,
+            blocked: vec![],
+        }
+    }
+
+    #[test]
+    fn throughput_counts_tasks_done_per_day() {
+        let timelines = vec![done_on(1, 2), done_on(2, 2), done_on(3, 4), done_on(4, 9)];
+
+        let throughput = Throughput::from_timelines(&timelines, date(1), date(5));
+        assert_eq!(throughput.daily, vec![0, 2, 0, 1]);
+        assert_eq!(throughput.total(), 3);
+    }
+
+    #[test]
+    fn constant_throughput_gives_exact_forecast() {
+        let throughput = Throughput { daily: vec![2] };
+        let mut sim = MonteCarlo::new(&throughput, &options(100, Some(7))).unwrap();
+
+        let items = sim.items_within(10);
+        assert_eq!((items.p50, items.p85, items.p95), (20, 20, 20));
+
+        let done = sim.completion(5, date(1));
+        assert_eq!(done.p50, date(4));
+        assert_eq!(done.p95, date(4));
+    }
+
+    #[test]
+    fn confidence_widens_with_variable_throughput() {
+        let throughput = Throughput {
+            daily: vec![0, 1, 3, 0, 2, 5, 1],
+        };
+        let mut sim = MonteCarlo::new(&throughput, &options(2000, Some(42))).unwrap();
+
+        let items = sim.items_within(14);
+        assert!(items.p95 <= items.p85 && items.p85 <= items.p50);
+        assert!(items.p95 < items.p50);
+
+        let done = sim.completion(20, date(1));
+        assert!(done.p50 <= done.p85 && done.p85 <= done.p95);
+        assert!(done.p50 < done.p95);
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let throughput = Throughput {
+            daily: vec![0, 1, 3, 0, 2],
+        };
+        let run = || {
+            MonteCarlo::new(&throughput, &options(500, Some(1)))
+                .unwrap()
+                .items_within(30)
+        };
+
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn empty_history_cannot_be_forecast() {
+        let throughput = Throughput { daily: vec![0; 30] };
+
+        assert!(matches!(
+            MonteCarlo::new(&throughput, &options(100, None)),
+            Err(KanbanError::Validation {
+                field: "forecast",
+                ..
+            })
+        ));
+        assert!(MonteCarlo::new(&Throughput { daily: vec![1] }, &options(0, None)).is_err());
+    }
+}
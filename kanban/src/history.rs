@@ -0,0 +1,192 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KanbanError, Result};
+
+/// Kind of change recorded in a task's history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// task was added to the board
+    Created,
+    /// task moved between workflow columns
+    Moved,
+    /// finished task sent back to an earlier column
+    Reopened,
+    /// started task sent back to the first column
+    Stopped,
+    /// task moved to another board
+    Transferred,
+    /// task name changed
+    Renamed,
+    /// task description changed
+    Described,
+    /// task priority changed
+    Prioritized,
+    /// task due date set, changed or cleared
+    Rescheduled,
+    /// label added to a task, or renamed when there is an old value
+    Labeled,
+    /// label removed from a task
+    Unlabeled,
+    /// user assigned to a task
+    Assigned,
+    /// user removed from a task
+    Unassigned,
+    /// checklist item added, checked, unchecked or removed
+    ChecklistChanged,
+    /// task made a subtask of another, or detached from its parent
+    ParentChanged,
+    /// blocker added to a task
+    DependencyAdded,
+    /// blocker removed from a task
+    DependencyRemoved,
+    /// comment added to a task
+    Commented,
+    /// comment text changed
+    CommentEdited,
+    /// comment removed from a task
+    CommentDeleted,
+    /// task hidden from the board
+    Archived,
+    /// archived task brought back onto the board
+    Restored,
+    /// task in progress flagged as blocked, with the reason as new value
+    Blocked,
+    /// blocked flag cleared, with the reason as old value
+    Unblocked,
+}
+
+impl EventKind {
+    /// Stable name used when the kind is stored as text.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Moved => "moved",
+            EventKind::Reopened => "reopened",
+            EventKind::Stopped => "stopped",
+            EventKind::Transferred => "transferred",
+            EventKind::Renamed => "renamed",
+            EventKind::Described => "described",
+            EventKind::Prioritized => "prioritized",
+            EventKind::Rescheduled => "rescheduled",
+            EventKind::Labeled => "labeled",
+            EventKind::Unlabeled => "unlabeled",
+            EventKind::Assigned => "assigned",
+            EventKind::Unassigned => "unassigned",
+            EventKind::ChecklistChanged => "checklist_changed",
+            EventKind::ParentChanged => "parent_changed",
+            EventKind::DependencyAdded => "dependency_added",
+            EventKind::DependencyRemoved => "dependency_removed",
+            EventKind::Commented => "commented",
+            EventKind::CommentEdited => "comment_edited",
+            EventKind::CommentDeleted => "comment_deleted",
+            EventKind::Archived => "archived",
+            EventKind::Restored => "restored",
+            EventKind::Blocked => "blocked",
+            EventKind::Unblocked => "unblocked",
+        }
+    }
+
+    /// Returns `true` for events that move a task between columns; their
+    /// values are the column names before and after the move.
+    pub fn is_move(&self) -> bool {
+        matches!(
+            self,
+            EventKind::Moved | EventKind::Reopened | EventKind::Stopped
+        )
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = KanbanError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "created" => Ok(EventKind::Created),
+            "moved" => Ok(EventKind::Moved),
+            "reopened" => Ok(EventKind::Reopened),
+            "stopped" => Ok(EventKind::Stopped),
+            "transferred" => Ok(EventKind::Transferred),
+            "renamed" => Ok(EventKind::Renamed),
+            "described" => Ok(EventKind::Described),
+            "prioritized" => Ok(EventKind::Prioritized),
+            "rescheduled" => Ok(EventKind::Rescheduled),
+            "labeled" => Ok(EventKind::Labeled),
+            "unlabeled" => Ok(EventKind::Unlabeled),
+            "assigned" => Ok(EventKind::Assigned),
+            "unassigned" => Ok(EventKind::Unassigned),
+            "checklist_changed" => Ok(EventKind::ChecklistChanged),
+            "parent_changed" => Ok(EventKind::ParentChanged),
+            "dependency_added" => Ok(EventKind::DependencyAdded),
+            "dependency_removed" => Ok(EventKind::DependencyRemoved),
+            "commented" => Ok(EventKind::Commented),
+            "comment_edited" => Ok(EventKind::CommentEdited),
+            "comment_deleted" => Ok(EventKind::CommentDeleted),
+            "archived" => Ok(EventKind::Archived),
+            "restored" => Ok(EventKind::Restored),
+            "blocked" => Ok(EventKind::Blocked),
+            "unblocked" => Ok(EventKind::Unblocked),
+            _ => Err(KanbanError::Validation {
+                field: "event",
+                reason: format!("unknown event kind '{}'", s),
+            }),
+        }
+    }
+}
+
+/// A single change made to a task through the service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskEvent {
+    /// task the change was made to
+    pub task_id: u32,
+    /// what changed
+    pub kind: EventKind,
+    /// value before the change, if there was one
+    pub old_value: Option<String>,
+    /// value after the change, if there is one
+    pub new_value: Option<String>,
+    /// when the change happened
+    pub at: DateTime<Utc>,
+    /// who made the change
+    pub actor: String,
+    /// why the change was made, for changes that require one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl TaskEvent {
+    /// Creates an event stamped with the current time.
+    pub fn new(
+        task_id: u32,
+        kind: EventKind,
+        old_value: Option<String>,
+        new_value: Option<String>,
+        actor: &str,
+    ) -> Self {
+        TaskEvent {
+            task_id,
+            kind,
+            old_value,
+            new_value,
+            at: Utc::now(),
+            actor: actor.to_string(),
+            reason: None,
+        }
+    }
+
+    /// Attaches the reason the change was made.
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+}
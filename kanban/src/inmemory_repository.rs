@@ -1,165 +1,349 @@
-use crate::{Status, Task};
-use chrono::Utc;
-
-/// Trait defining the behavior of a Task repository.
-pub trait InMemoryTaskRepo {
-    fn add_task(&mut self, name: &str, description: &str) -> Result<&Task, String>;
-    fn update(&mut self, task: Task);
-    fn move_to_doing(&mut self, id: u32) -> Result<(), String>;
-    fn move_to_done(&mut self, id: u32) -> Result<(), String>;
-    fn list_by_status(&self, status: Status) -> Vec<&Task>;
-    fn find_by_id(&mut self, id: u32) -> Option<&mut Task>;
-    fn find_by_name(&mut self, name: &str) -> Option<&mut Task>;
-}
+use crate::Task;
+use crate::board::{Board, DEFAULT_BOARD_ID};
+use crate::comment::Comment;
+use crate::error::{KanbanError, Result};
+use crate::history::TaskEvent;
+use crate::query::TaskQuery;
+use crate::repository::TaskRepository;
+use crate::user::User;
 
 /// In-memory implementation of a Task repository.
-/// Stores tasks in a vector.
+/// Stores boards, tasks, their history, users and comments in vectors.
+#[derive(Debug, Clone)]
 pub struct InMemoryTaskRepository {
+    boards: Vec<Board>,
     tasks: Vec<Task>,
+    events: Vec<TaskEvent>,
+    users: Vec<User>,
+    comments: Vec<Comment>,
+    /// ID handed to the next inserted board
+    next_board_id: u32,
+    /// ID handed to the next inserted task
+    next_task_id: u32,
+    /// ID handed to the next inserted comment
+    next_comment_id: u32,
+    /// state before the unit of work in progress, if any
+    backup: Option<Box<InMemoryTaskRepository>>,
 }
 
 impl InMemoryTaskRepository {
-    /// Creates a new empty task repository.
+    /// Creates a new task repository holding only the default board.
     pub fn new() -> Self {
-        Self { tasks: Vec::new() }
+        Self::default()
     }
 }
 
-impl InMemoryTaskRepo for InMemoryTaskRepository {
-    /// Adds a new task to the repository.
+impl Default for InMemoryTaskRepository {
+    fn default() -> Self {
+        InMemoryTaskRepository {
+            boards: vec![Board::default()],
+            tasks: Vec::new(),
+            events: Vec::new(),
+            users: Vec::new(),
+            comments: Vec::new(),
+            next_board_id: DEFAULT_BOARD_ID + 1,
+            next_task_id: 1,
+            next_comment_id: 1,
+            backup: None,
+        }
+    }
+}
+
+impl TaskRepository for InMemoryTaskRepository {
+    /// Finds a task by its unique ID.
     ///
     /// # Arguments
-    /// * `task` - The task to be added.
+    /// * `id` - The ID of the task to retrieve.
     ///
     /// # Returns
-    /// * `Ok(&Task)` - A reference to the newly added task.
-    /// * `Err(String)` - If a task with the same name already exists.
-    fn add_task(&mut self, name: &str, description: &str) -> Result<&Task, String> {
-        // ensure task uniqueness
-        let t = self.find_by_name(name);
-        if let Some(t) = t {
-            return Err(format!("Task with name '{}' already exists", t.name));
-        }
-
-        let mut task = Task::new(name.to_string(), description.to_string());
-        // validation
-        task.before_add()?;
+    /// * `Ok(Some(Task))` - A copy of the task, if found.
+    /// * `Ok(None)` - If no task with that ID exists.
+    fn get(&self, id: u32) -> Result<Option<Task>> {
+        Ok(self.tasks.iter().find(|t| t.id == Some(id)).cloned())
+    }
 
-        // Assign task ID and push to vector
-        task.id = Some((self.tasks.len() + 1) as u32);
-        self.tasks.push(task);
+    /// Searches a board for a task by name (case-insensitive).
+    ///
+    /// # Arguments
+    /// * `board_id` - The board to search.
+    /// * `name` - The task name to search for.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Task))` - A copy of the task, if found.
+    /// * `Ok(None)` - If no task with that name exists on the board.
+    fn find_by_name(&self, board_id: u32, name: &str) -> Result<Option<Task>> {
+        Ok(self
+            .tasks
+            .iter()
+            .find(|t| t.board_id == board_id && t.name.to_lowercase() == name.to_lowercase())
+            .cloned())
+    }
 
-        Ok(self.tasks.last().unwrap())
+    /// Adds a new task to the repository.
+    ///
+    /// # Arguments
+    /// * `task` - The task to be added. Any ID it carries is replaced.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The newly added task with its assigned ID.
+    fn insert(&mut self, mut task: Task) -> Result<Task> {
+        // IDs are never reused, not even those of deleted tasks
+        task.id = Some(self.next_task_id);
+        self.next_task_id += 1;
+        self.tasks.push(task.clone());
+
+        Ok(task)
     }
 
     /// Updates an existing task by replacing it in the vector.
     ///
     /// # Arguments
     /// * `task` - The updated task.
-    fn update(&mut self, task: Task) {
-        if let Some(pos) = self.tasks.iter().position(|t| t.id == task.id) {
-            self.tasks[pos] = task;
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The stored task.
+    /// * `Err(KanbanError::NotFound)` - If the task could not be found.
+    fn update(&mut self, task: Task) -> Result<Task> {
+        match self.tasks.iter().position(|t| t.id == task.id) {
+            Some(pos) => {
+                self.tasks[pos] = task.clone();
+                Ok(task)
+            }
+            None => Err(KanbanError::NotFound {
+                id: task.id.unwrap_or_default(),
+            }),
         }
     }
 
-    /// Transitions a task to `Doing`.
+    /// Removes a task, its history and its comments from the repository.
     ///
     /// # Arguments
     /// * `id` - The unique identifier of the task.
     ///
     /// # Returns
-    /// * `Ok(())` - If the task was found and updated successfully.
-    /// * `Err(String)` - If the task could not be found.
-    fn move_to_doing(&mut self, id: u32) -> Result<(), String> {
-        let task = self.find_by_id(id);
-        if task.is_none() {
-            return Err("Task not found".to_string());
+    /// * `Ok(Task)` - The removed task.
+    /// * `Err(KanbanError::NotFound)` - If the task could not be found.
+    fn delete(&mut self, id: u32) -> Result<Task> {
+        match self.tasks.iter().position(|t| t.id == Some(id)) {
+            Some(pos) => {
+                self.events.retain(|e| e.task_id != id);
+                self.comments.retain(|c| c.task_id != id);
+                Ok(self.tasks.remove(pos))
+            }
+            None => Err(KanbanError::NotFound { id }),
         }
+    }
 
-        let task = task.unwrap();
+    /// Lists tasks matching the given query.
+    ///
+    /// # Arguments
+    /// * `query` - The criteria, ordering and paging to apply.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Task>)` - A list of tasks matching the query.
+    fn list(&self, query: &TaskQuery) -> Result<Vec<Task>> {
+        Ok(query.apply(self.tasks.iter().cloned()))
+    }
 
-        task.before_move_to_doing()?;
+    /// Records an event in the task's history.
+    fn append_event(&mut self, event: TaskEvent) -> Result<()> {
+        self.events.push(event);
+        Ok(())
+    }
 
-        task.status = Status::Doing;
-        task.updated_at = Some(Utc::now());
+    /// Returns the events recorded for a task, oldest first.
+    fn history(&self, task_id: u32) -> Result<Vec<TaskEvent>> {
+        Ok(self
+            .events
+            .iter()
+            .filter(|e| e.task_id == task_id)
+            .cloned()
+            .collect())
+    }
 
-        Ok(())
+    /// Finds a board by its unique ID.
+    fn get_board(&self, id: u32) -> Result<Option<Board>> {
+        Ok(self.boards.iter().find(|b| b.id == Some(id)).cloned())
     }
 
-    /// Transitions a task to `Done`.
+    /// Lists every board in order of creation.
+    fn list_boards(&self) -> Result<Vec<Board>> {
+        Ok(self.boards.clone())
+    }
+
+    /// Adds a new board, assigning it the next free ID.
+    fn insert_board(&mut self, mut board: Board) -> Result<Board> {
+        // IDs are never reused, not even those of deleted boards
+        board.id = Some(self.next_board_id);
+        self.next_board_id += 1;
+        self.boards.push(board.clone());
+
+        Ok(board)
+    }
+
+    /// Replaces an existing board.
     ///
-    /// # Arguments
-    /// * `id` - The unique identifier of the task.
+    /// # Returns
+    /// * `Ok(Board)` - The stored board.
+    /// * `Err(KanbanError::BoardNotFound)` - If the board could not be found.
+    fn update_board(&mut self, board: Board) -> Result<Board> {
+        match self.boards.iter().position(|b| b.id == board.id) {
+            Some(pos) => {
+                self.boards[pos] = board.clone();
+                Ok(board)
+            }
+            None => Err(KanbanError::BoardNotFound {
+                id: board.id.unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Removes a board.
     ///
     /// # Returns
-    /// * `Ok(())` - If the task was found and marked as done.
-    /// * `Err(String)` - If the task could not be found.
-    fn move_to_done(&mut self, id: u32) -> Result<(), String> {
-        let task = self.find_by_id(id);
-        if task.is_none() {
-            return Err("Task not found".to_string());
+    /// * `Ok(Board)` - The removed board.
+    /// * `Err(KanbanError::BoardNotFound)` - If the board could not be found.
+    fn delete_board(&mut self, id: u32) -> Result<Board> {
+        match self.boards.iter().position(|b| b.id == Some(id)) {
+            Some(pos) => Ok(self.boards.remove(pos)),
+            None => Err(KanbanError::BoardNotFound { id }),
         }
+    }
 
-        let task = task.unwrap();
+    /// Finds a user by their unique ID.
+    fn get_user(&self, id: u32) -> Result<Option<User>> {
+        Ok(self.users.iter().find(|u| u.id == Some(id)).cloned())
+    }
 
-        task.before_move_to_done()?;
+    /// Lists every user in order of creation.
+    fn list_users(&self) -> Result<Vec<User>> {
+        Ok(self.users.clone())
+    }
 
-        task.status = Status::Done;
-        task.updated_at = Some(Utc::now());
+    /// Adds a new user, assigning them the next free ID.
+    fn insert_user(&mut self, mut user: User) -> Result<User> {
+        let next_id = self.users.iter().filter_map(|u| u.id).max().unwrap_or(0) + 1;
+        user.id = Some(next_id);
+        self.users.push(user.clone());
 
-        Ok(())
+        Ok(user)
     }
 
-    /// Lists tasks by their current status.
-    ///
-    /// # Arguments
-    /// * `status` - The status to filter by.
+    /// Replaces an existing user.
     ///
     /// # Returns
-    /// * `Vec<&Task>` - A list of tasks matching the given status.
-    ///   If status is `Status::None`, all tasks are returned.
-    fn list_by_status(&self, status: Status) -> Vec<&Task> {
-        // if status is None list all tasks
-        if status == Status::None {
-            return self.tasks.iter().collect();
+    /// * `Ok(User)` - The stored user.
+    /// * `Err(KanbanError::UserNotFound)` - If the user could not be found.
+    fn update_user(&mut self, user: User) -> Result<User> {
+        match self.users.iter().position(|u| u.id == user.id) {
+            Some(pos) => {
+                self.users[pos] = user.clone();
+                Ok(user)
+            }
+            None => Err(KanbanError::UserNotFound {
+                id: user.id.unwrap_or_default(),
+            }),
         }
+    }
 
-        // otherwise list by status
-        self.tasks.iter().filter(|t| t.status == status).collect()
+    /// Finds a comment by its unique ID.
+    fn get_comment(&self, id: u32) -> Result<Option<Comment>> {
+        Ok(self.comments.iter().find(|c| c.id == Some(id)).cloned())
     }
 
-    /// Finds a task by its unique ID.
-    ///
-    /// # Arguments
-    /// * `id` - The ID of the task to retrieve.
+    /// Returns the comments on a task in order of creation.
+    fn list_comments(&self, task_id: u32) -> Result<Vec<Comment>> {
+        Ok(self
+            .comments
+            .iter()
+            .filter(|c| c.task_id == task_id)
+            .cloned()
+            .collect())
+    }
+
+    /// Adds a new comment, assigning it the next free ID.
+    fn insert_comment(&mut self, mut comment: Comment) -> Result<Comment> {
+        // IDs are never reused, not even those of deleted comments
+        comment.id = Some(self.next_comment_id);
+        self.next_comment_id += 1;
+        self.comments.push(comment.clone());
+
+        Ok(comment)
+    }
+
+    /// Replaces an existing comment.
     ///
     /// # Returns
-    /// * `Some(&mut Task)` - A mutable reference to the task, if found.
-    /// * `None` - If no task with that ID exists.
-    fn find_by_id(&mut self, id: u32) -> Option<&mut Task> {
-        // return the position of the task in storage vector
-        // NOTE: positions start from 1 not 0 like index
-        self.tasks.iter_mut().find(|t| t.id == Option::from(id))
+    /// * `Ok(Comment)` - The stored comment.
+    /// * `Err(KanbanError::CommentNotFound)` - If the comment could not be found.
+    fn update_comment(&mut self, comment: Comment) -> Result<Comment> {
+        match self.comments.iter().position(|c| c.id == comment.id) {
+            Some(pos) => {
+                self.comments[pos] = comment.clone();
+                Ok(comment)
+            }
+            None => Err(KanbanError::CommentNotFound {
+                id: comment.id.unwrap_or_default(),
+            }),
+        }
     }
 
-    /// Searches for a task by name (case-insensitive).
-    ///
-    /// # Arguments
-    /// * `name` - The task name to search for.
+    /// Removes a comment.
     ///
     /// # Returns
-    /// * `Some(&mut Task)` - A mutable reference to the task, if found.
-    /// * `None` - If no task with that name exists.
-    fn find_by_name(&mut self, name: &str) -> Option<&mut Task> {
-        self.tasks
-            .iter_mut()
-            .find(|t| t.name.to_lowercase() == name.to_lowercase())
+    /// * `Ok(Comment)` - The removed comment.
+    /// * `Err(KanbanError::CommentNotFound)` - If the comment could not be found.
+    fn delete_comment(&mut self, id: u32) -> Result<Comment> {
+        match self.comments.iter().position(|c| c.id == Some(id)) {
+            Some(pos) => Ok(self.comments.remove(pos)),
+            None => Err(KanbanError::CommentNotFound { id }),
+        }
+    }
+
+    /// Starts a unit of work by keeping a copy of the current state.
+    fn begin(&mut self) -> Result<()> {
+        if self.backup.is_some() {
+            return Err(KanbanError::Storage {
+                reason: String::from("a unit of work is already in progress"),
+            });
+        }
+
+        self.backup = Some(Box::new(self.clone()));
+        Ok(())
+    }
+
+    /// Keeps the writes of the unit of work; they are already in place.
+    fn commit(&mut self) -> Result<()> {
+        match self.backup.take() {
+            Some(_) => Ok(()),
+            None => Err(KanbanError::Storage {
+                reason: String::from("no unit of work in progress"),
+            }),
+        }
+    }
+
+    /// Puts back the state from before the unit of work.
+    fn rollback(&mut self) -> Result<()> {
+        match self.backup.take() {
+            Some(backup) => {
+                *self = *backup;
+                Ok(())
+            }
+            None => Err(KanbanError::Storage {
+                reason: String::from("no unit of work in progress"),
+            }),
+        }
     }
 }
 
 #[cfg(test)]
 mod in_memory_repo_tests {
     use super::*;
+    use crate::Status;
+    use crate::board::DEFAULT_BOARD_ID;
+    use crate::history::EventKind;
+    use crate::repository::contract_tests;
+    use crate::workflow::Workflow;
 
     const TASK_NAME: &str = "task1";
     const TASK_DESCRIPTION: &str = "description1";
@@ -172,130 +356,258 @@ mod in_memory_repo_tests {
             let mut repo = InMemoryTaskRepository::new();
 
             // add test data
-            repo.add_task(TASK_NAME, TASK_DESCRIPTION);
-            repo.add_task("task 2", "description task 2");
-            repo.add_task("task 3", "description task 3");
+            for (name, description) in [
+                (TASK_NAME, TASK_DESCRIPTION),
+                ("task 2", "description task 2"),
+                ("task 3", "description task 3"),
+            ] {
+                repo.insert(Task::new(name.to_string(), description.to_string()))
+                    .expect("task not inserted");
+            }
 
             let task2 = &mut repo.tasks[1];
-            task2.status = Status::Doing;
+            task2.status = Status::DOING;
 
             let task3 = &mut repo.tasks[2];
-            task3.status = Status::Done;
+            task3.status = Status::DONE;
 
             Setup { repo }
         }
     }
 
     #[test]
-    fn add_task_succeeds() {
+    fn insert_assigns_id() {
         let mut setup = Setup::new();
 
-        // insert new task
-        let no_of_tasks_before = setup.repo.tasks.iter().clone().len();
+        let no_of_tasks_before = setup.repo.tasks.len();
 
         let task_name = "new task";
         let task_descr = "new task description";
 
-        setup
+        let task = setup
             .repo
-            .add_task(task_name, task_descr)
-            .expect("TODO: panic message");
-
-        let no_of_tasks_after = setup.repo.tasks.len();
+            .insert(Task::new(task_name.to_string(), task_descr.to_string()))
+            .expect("task not inserted");
 
         // Assertions
+        assert_eq!(task.id, Some(4));
         assert_eq!(setup.repo.tasks.last().unwrap().name, task_name.to_string());
-        assert!(no_of_tasks_after > no_of_tasks_before);
+        assert_eq!(setup.repo.tasks.len(), no_of_tasks_before + 1);
     }
 
     #[test]
-    fn add_task_rejects_duplicate_name() {
+    fn get_returns_owned_task() {
+        let setup = Setup::new();
+
+        let task = setup.repo.get(2).unwrap().unwrap();
+        assert_eq!(task.name, "task 2");
+        assert!(setup.repo.get(42).unwrap().is_none());
+    }
+
+    #[test]
+    fn find_task_by_name_succeeds() {
+        let setup = Setup::new();
+
+        let task1 = setup
+            .repo
+            .find_by_name(DEFAULT_BOARD_ID, "TASK1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(task1.name, TASK_NAME.to_string());
+        assert!(setup.repo.find_by_name(2, "task1").unwrap().is_none());
+    }
+
+    #[test]
+    fn update_replaces_task() {
         let mut setup = Setup::new();
-        let task = Task::new(TASK_NAME.to_string(), TASK_DESCRIPTION.to_string());
-        let res = setup.repo.add_task(TASK_NAME, TASK_DESCRIPTION);
 
-        assert_eq!(res.is_err(), true);
-        let err = res.expect_err("Should have returned an error");
+        let mut task = setup.repo.get(1).unwrap().unwrap();
+        task.description = "changed".to_string();
+        setup.repo.update(task).expect("task not updated");
+
+        assert_eq!(setup.repo.get(1).unwrap().unwrap().description, "changed");
+    }
+
+    #[test]
+    fn update_fails_for_unknown_task() {
+        let mut setup = Setup::new();
+
+        let mut task = Task::new("ghost".to_string(), "".to_string());
+        task.id = Some(42);
         assert_eq!(
-            err.as_str(),
-            format!("Task with name '{}' already exists", TASK_NAME)
+            setup.repo.update(task).unwrap_err(),
+            KanbanError::NotFound { id: 42 }
         );
     }
 
     #[test]
-    fn find_task_by_name_succeeds() {
+    fn delete_removes_task() {
         let mut setup = Setup::new();
 
-        let task1 = setup.repo.find_by_name(TASK_NAME).unwrap();
-        assert_eq!(task1.name, TASK_NAME.to_string());
+        let event = TaskEvent::new(2, EventKind::Created, None, None, "tester");
+        setup.repo.append_event(event).unwrap();
+
+        let removed = setup.repo.delete(2).expect("task not deleted");
+        assert!(setup.repo.history(2).unwrap().is_empty());
+        assert_eq!(removed.name, "task 2");
+        assert!(setup.repo.get(2).unwrap().is_none());
+        assert_eq!(
+            setup.repo.delete(2).unwrap_err(),
+            KanbanError::NotFound { id: 2 }
+        );
     }
 
     #[test]
-    fn move_task_to_doing_state_succeeds() {
-        // select task to be moved to Doing state
+    fn ids_of_deleted_tasks_are_not_reused() {
         let mut setup = Setup::new();
 
-        // move to doing state
-        let res = setup.repo.move_to_doing(1);
-        assert!(res.is_ok());
+        setup.repo.delete(3).unwrap();
+        let task = setup
+            .repo
+            .insert(Task::new("task 4".to_string(), String::new()))
+            .unwrap();
+        assert_eq!(task.id, Some(4));
+    }
 
-        let task1 = setup.repo.find_by_id(1).unwrap();
-        assert_eq!(task1.status, Status::Doing)
+    #[test]
+    fn list_tasks() {
+        let setup = Setup::new();
+        let by_status = |status| TaskQuery::new().status(status);
+
+        assert_eq!(setup.repo.list(&by_status(Status::TODO)).unwrap().len(), 1);
+        assert_eq!(setup.repo.list(&by_status(Status::DOING)).unwrap().len(), 1);
+        assert_eq!(setup.repo.list(&by_status(Status::DONE)).unwrap().len(), 1);
+        assert_eq!(setup.repo.list(&TaskQuery::new()).unwrap().len(), 3);
+
+        let by_name = TaskQuery::new().name_contains("TASK ");
+        assert_eq!(setup.repo.list(&by_name).unwrap().len(), 2);
     }
 
     #[test]
-    fn move_task_to_doing_state_fails() {
-        // select task to be moved to Doing state
+    fn boards_crud() {
         let mut setup = Setup::new();
+        let boards = setup.repo.list_boards().unwrap();
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0].id, Some(DEFAULT_BOARD_ID));
 
-        let res = setup.repo.move_to_doing(3);
-        assert!(res.is_err());
+        let board = setup
+            .repo
+            .insert_board(Board::new("ops".to_string(), Workflow::default()))
+            .unwrap();
+        assert_eq!(board.id, Some(2));
+
+        let mut renamed = board.clone();
+        renamed.name = "operations".to_string();
+        setup.repo.update_board(renamed).unwrap();
+        assert_eq!(setup.repo.get_board(2).unwrap().unwrap().name, "operations");
 
-        // fetch task of ID: 1 and confirm its still in the todo state
-        let task = setup.repo.find_by_id(3).unwrap();
-        assert_eq!(task.status, Status::Done);
+        setup.repo.delete_board(2).unwrap();
+        assert!(setup.repo.get_board(2).unwrap().is_none());
+        assert_eq!(
+            setup.repo.update_board(board).unwrap_err(),
+            KanbanError::BoardNotFound { id: 2 }
+        );
+    }
+
+    #[test]
+    fn users_crud() {
+        let mut setup = Setup::new();
+        assert!(setup.repo.list_users().unwrap().is_empty());
+
+        let user = setup
+            .repo
+            .insert_user(User::new("alice".to_string()))
+            .unwrap();
+        assert_eq!(user.id, Some(1));
+
+        let mut limited = user.clone();
+        limited.wip_limit = Some(2);
+        setup.repo.update_user(limited).unwrap();
+        assert_eq!(setup.repo.get_user(1).unwrap().unwrap().wip_limit, Some(2));
+        assert_eq!(setup.repo.list_users().unwrap().len(), 1);
+
+        let mut ghost = user;
+        ghost.id = Some(9);
+        assert_eq!(
+            setup.repo.update_user(ghost).unwrap_err(),
+            KanbanError::UserNotFound { id: 9 }
+        );
     }
 
     #[test]
-    fn move_task_to_done_state_succeeds() {
-        // select task to be moved to Doing state
+    fn comments_crud() {
         let mut setup = Setup::new();
+        let comment = |task_id, body: &str| Comment::new(task_id, "alice".into(), body.into());
 
-        // move to doing state
-        let res = setup.repo.move_to_done(2);
-        assert!(res.is_ok());
+        let first = setup.repo.insert_comment(comment(1, "first")).unwrap();
+        setup.repo.insert_comment(comment(2, "other")).unwrap();
+        setup.repo.insert_comment(comment(1, "second")).unwrap();
+        assert_eq!(first.id, Some(1));
 
-        let task2 = setup.repo.find_by_id(2).unwrap();
-        assert_eq!(task2.status, Status::Done)
+        let bodies: Vec<_> = setup
+            .repo
+            .list_comments(1)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.body)
+            .collect();
+        assert_eq!(bodies, vec!["first", "second"]);
+
+        let mut edited = first.clone();
+        edited.edit("changed".into(), "bob");
+        setup.repo.update_comment(edited).unwrap();
+        assert_eq!(
+            setup.repo.get_comment(1).unwrap().unwrap().revisions.len(),
+            1
+        );
+
+        setup.repo.delete_comment(1).unwrap();
+        assert!(setup.repo.get_comment(1).unwrap().is_none());
+        assert_eq!(
+            setup.repo.update_comment(first).unwrap_err(),
+            KanbanError::CommentNotFound { id: 1 }
+        );
+
+        // deleting a task takes its comments with it
+        setup.repo.delete(1).unwrap();
+        assert!(setup.repo.list_comments(1).unwrap().is_empty());
+        assert_eq!(setup.repo.list_comments(2).unwrap().len(), 1);
     }
 
     #[test]
-    fn move_task_to_done_state_fails() {
-        // select task to be moved to Doing state
+    fn ids_of_deleted_comments_are_not_reused() {
         let mut setup = Setup::new();
+        let comment = |body: &str| Comment::new(1, "alice".into(), body.into());
 
-        // task of id 1 is in the todo state
-        let res = setup.repo.move_to_done(1);
-        assert!(res.is_err());
+        setup.repo.insert_comment(comment("first")).unwrap();
+        let second = setup.repo.insert_comment(comment("second")).unwrap();
+        setup.repo.delete_comment(second.id.unwrap()).unwrap();
 
-        // fetch task of ID: 1 and confirm its still in the todo state
-        let task = setup.repo.find_by_id(1).unwrap();
-        assert_eq!(task.status, Status::Todo);
+        let third = setup.repo.insert_comment(comment("third")).unwrap();
+        assert_eq!(third.id, Some(3));
     }
 
     #[test]
-    fn list_tasks() {
-        let setup = Setup::new();
-        let todo_tasks = setup.repo.list_by_status(Status::Todo);
-        assert_eq!(todo_tasks.len(), 1);
+    fn ids_of_deleted_boards_are_not_reused() {
+        let mut setup = Setup::new();
+        let board = |name: &str| Board::new(name.to_string(), Workflow::default());
+
+        setup.repo.insert_board(board("ops")).unwrap();
+        setup.repo.delete_board(2).unwrap();
 
-        let doing_tasks = setup.repo.list_by_status(Status::Doing);
-        assert_eq!(doing_tasks.len(), 1);
+        let next = setup.repo.insert_board(board("support")).unwrap();
+        assert_eq!(next.id, Some(3));
+    }
 
-        let done_tasks = setup.repo.list_by_status(Status::Done);
-        assert_eq!(done_tasks.len(), 1);
+    #[test]
+    fn units_of_work_commit_or_roll_back() {
+        let mut setup = Setup::new();
+        contract_tests::units_of_work_commit_or_roll_back(&mut setup.repo);
+    }
 
-        let all_tasks = setup.repo.list_by_status(Status::None);
-        assert_eq!(all_tasks.len(), 3);
+    #[test]
+    fn names_match_ignoring_unicode_case() {
+        let mut setup = Setup::new();
+        contract_tests::names_match_ignoring_unicode_case(&mut setup.repo);
     }
 }
@@ -0,0 +1,905 @@
+//! JSON file backed task repository.
+//!
+//! The whole workspace - every board, its tasks, the users they are
+//! assigned to and the comments left on them - is kept in a single JSON
+//! document:
+//!
+//! ```json
+//! {
+//!   "version": 4,
+//!   "boards": [
+//!     {
+//!       "id": 1,
+//!       "name": "Default",
+//!       "workflow": {
+//!         "columns": ["Todo", "Doing", "Done"],
+//!         "transitions": [["Todo", "Doing"], ["Doing", "Done"]],
+//!         "wip_limits": [],
+//!         "start_column": null,
+//!         "done_column": null,
+//!         "require_assignee": false,
+//!         "allow_incomplete_subtasks": false
+//!       },
+//!       "created_at": "2025-06-01T09:00:00Z"
+//!     }
+//!   ],
+//!   "tasks": [
+//!     {
+//!       "id": 1,
+//!       "board_id": 1,
+//!       "name": "write docs",
+//!       "description": "describe the file format",
+//!       "status": "Doing",
+//!       "created_at": "2025-06-01T09:30:00Z",
+//!       "updated_at": "2025-06-02T14:00:00Z",
+//!       "expedited": false,
+//!       "priority": "high",
+//!       "due_at": "2025-06-06T17:00:00Z",
+//!       "completed_late": false,
+//!       "labels": ["docs", "frontend"],
+//!       "assignees": [1],
+//!       "checklist": [{"text": "outline", "done": true}],
+//!       "parent_id": null,
+//!       "blocked_by": [],
+//!       "archived_at": null,
+//!       "blocked": {"reason": "waiting on review", "since": "2025-06-02T16:00:00Z"}
+//!     }
+//!   ],
+//!   "next_board_id": 2,
+//!   "next_task_id": 2,
+//!   "next_comment_id": 2,
+//!   "events": [
+//!     {
+//!       "task_id": 1,
+//!       "kind": "moved",
+//!       "old_value": "Todo",
+//!       "new_value": "Doing",
+//!       "at": "2025-06-02T14:00:00Z",
+//!       "actor": "alice"
+//!     }
+//!   ],
+//!   "users": [
+//!     {
+//!       "id": 1,
+//!       "name": "alice",
+//!       "wip_limit": 2,
+//!       "created_at": "2025-06-01T08:00:00Z"
+//!     }
+//!   ],
+//!   "comments": [
+//!     {
+//!       "id": 1,
+//!       "task_id": 1,
+//!       "author": "alice",
+//!       "body": "Draft is **ready** for review",
+//!       "created_at": "2025-06-02T15:00:00Z",
+//!       "updated_at": "2025-06-02T15:10:00Z",
+//!       "edited_by": "alice",
+//!       "revisions": [
+//!         {
+//!           "body": "Draft is ready",
+//!           "author": "alice",
+//!           "written_at": "2025-06-02T15:00:00Z"
+//!         }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `status` is the name of the task's column in its board's workflow,
+//! `priority` is one of `critical`, `high`, `medium` (the default) or `low`
+//! and timestamps are RFC 3339 strings in UTC. `updated_at` is `null` until
+//! the task changes and `due_at` is `null` for tasks without a due date;
+//! `completed_late` records whether the task reached the done column after
+//! it was due. `labels` is a sorted list of lowercase labels and
+//! `assignees` lists the IDs of the users working on the task. `checklist`
+//! holds the task's steps in order and `parent_id` the task it is a subtask
+//! of, if any; `blocked_by` lists the IDs of the tasks that must be done
+//! before work on it starts; `archived_at` is set while the task is
+//! archived and `blocked` while work on it is blocked, otherwise they are
+//! `null`. `next_board_id`, `next_task_id` and `next_comment_id` are the IDs
+//! the next board, task and comment receive, so IDs of deleted boards,
+//! tasks and comments are never reused; files without them continue from
+//! the highest ID in use.
+//!
+//! `events` holds the history of every task, oldest first; events that need
+//! a reason, such as reopening a task, carry it in a `reason` field.
+//! `comments` holds markdown comments on tasks; `revisions` keeps the
+//! earlier versions of an edited comment, oldest first. Version 1 files
+//! have no history, and files written before version 3 have no boards:
+//! their tasks are placed on the default board. Version 4 added
+//! `next_board_id` and `next_comment_id`.
+//!
+//! The file is rewritten after every mutation by writing a sibling temp file
+//! and renaming it over the original, so readers never observe a partially
+//! written workspace. During a unit of work (see [`TaskRepository::begin`])
+//! changes are only kept in memory and the file is written once on commit.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Task;
+use crate::board::{Board, DEFAULT_BOARD_ID};
+use crate::comment::Comment;
+use crate::error::{KanbanError, Result};
+use crate::history::TaskEvent;
+use crate::query::TaskQuery;
+use crate::repository::TaskRepository;
+use crate::user::User;
+
+/// Version of the on-disk format written by this repository.
+pub const FORMAT_VERSION: u32 = 4;
+
+/// The document stored in the workspace file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WorkspaceFile {
+    version: u32,
+    #[serde(default)]
+    boards: Vec<Board>,
+    tasks: Vec<Task>,
+    #[serde(default)]
+    events: Vec<TaskEvent>,
+    #[serde(default)]
+    users: Vec<User>,
+    #[serde(default)]
+    comments: Vec<Comment>,
+    #[serde(default)]
+    next_board_id: u32,
+    #[serde(default)]
+    next_task_id: u32,
+    #[serde(default)]
+    next_comment_id: u32,
+}
+
+impl Default for WorkspaceFile {
+    fn default() -> Self {
+        WorkspaceFile {
+            version: FORMAT_VERSION,
+            boards: vec![Board::default()],
+            tasks: Vec::new(),
+            events: Vec::new(),
+            users: Vec::new(),
+            comments: Vec::new(),
+            next_board_id: DEFAULT_BOARD_ID + 1,
+            next_task_id: 1,
+            next_comment_id: 1,
+        }
+    }
+}
+
+/// Task repository persisted to a JSON file.
+/// The workspace is held in memory and written back on every change, or
+/// once per unit of work.
+#[derive(Debug)]
+pub struct JsonFileTaskRepository {
+    path: PathBuf,
+    workspace: WorkspaceFile,
+    /// workspace before the unit of work in progress, if any; changes are
+    /// only kept in memory until it is committed
+    backup: Option<WorkspaceFile>,
+}
+
+impl JsonFileTaskRepository {
+    /// Opens the workspace stored at `path`.
+    ///
+    /// A missing file is treated as a workspace holding only an empty
+    /// default board; it is created on the first mutation.
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - The repository holding the loaded tasks.
+    /// * `Err(KanbanError::Storage)` - If the file cannot be read, is not
+    ///   valid JSON or was written by a newer format version.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Ok(Self {
+                path,
+                workspace: WorkspaceFile::default(),
+                backup: None,
+            });
+        }
+
+        let reader = BufReader::new(File::open(&path)?);
+        let mut workspace: WorkspaceFile = serde_json::from_reader(reader)?;
+        if workspace.version > FORMAT_VERSION {
+            return Err(KanbanError::Storage {
+                reason: format!(
+                    "board file version {} is newer than supported version {}",
+                    workspace.version, FORMAT_VERSION
+                ),
+            });
+        }
+
+        // files from before boards existed only hold tasks of the default board
+        if workspace.boards.is_empty() {
+            workspace.boards.push(Board::default());
+        }
+        // files from before the ID counters existed continue from the
+        // highest ID in use
+        let highest = workspace.boards.iter().filter_map(|b| b.id).max();
+        workspace.next_board_id = workspace.next_board_id.max(highest.unwrap_or(0) + 1);
+        let highest = workspace.tasks.iter().filter_map(|t| t.id).max();
+        workspace.next_task_id = workspace.next_task_id.max(highest.unwrap_or(0) + 1);
+        let highest = workspace.comments.iter().filter_map(|c| c.id).max();
+        workspace.next_comment_id = workspace.next_comment_id.max(highest.unwrap_or(0) + 1);
+
+        Ok(Self {
+            path,
+            workspace,
+            backup: None,
+        })
+    }
+
+    /// Path of the workspace file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Atomically writes `workspace` to the file and keeps it in memory.
+    /// During a unit of work it is only kept in memory.
+    ///
+    /// The in-memory state is only replaced once the file has been written,
+    /// so a failed save leaves the repository unchanged.
+    fn save(&mut self, mut workspace: WorkspaceFile) -> Result<()> {
+        workspace.version = FORMAT_VERSION;
+        if self.backup.is_none() {
+            self.write(&workspace)?;
+        }
+        self.workspace = workspace;
+
+        Ok(())
+    }
+
+    /// Atomically replaces the file with `workspace`.
+    fn write(&self, workspace: &WorkspaceFile) -> Result<()> {
+        let mut tmp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp_path = self.path.with_file_name(tmp_name);
+
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer_pretty(&mut writer, workspace)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        drop(writer);
+
+        fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+impl TaskRepository for JsonFileTaskRepository {
+    fn get(&self, id: u32) -> Result<Option<Task>> {
+        Ok(self
+            .workspace
+            .tasks
+            .iter()
+            .find(|t| t.id == Some(id))
+            .cloned())
+    }
+
+    fn find_by_name(&self, board_id: u32, name: &str) -> Result<Option<Task>> {
+        Ok(self
+            .workspace
+            .tasks
+            .iter()
+            .find(|t| t.board_id == board_id && t.name.to_lowercase() == name.to_lowercase())
+            .cloned())
+    }
+
+    fn insert(&mut self, mut task: Task) -> Result<Task> {
+        // IDs are never reused, not even those of deleted tasks
+        let next_id = self.workspace.next_task_id;
+        task.id = Some(next_id);
+
+        let mut workspace = self.workspace.clone();
+        workspace.tasks.push(task.clone());
+        workspace.next_task_id = next_id + 1;
+        self.save(workspace)?;
+
+        Ok(task)
+    }
+
+    fn update(&mut self, task: Task) -> Result<Task> {
+        let pos = self
+            .workspace
+            .tasks
+            .iter()
+            .position(|t| t.id == task.id)
+            .ok_or(KanbanError::NotFound {
+                id: task.id.unwrap_or_default(),
+            })?;
+
+        let mut workspace = self.workspace.clone();
+        workspace.tasks[pos] = task.clone();
+        self.save(workspace)?;
+
+        Ok(task)
+    }
+
+    fn delete(&mut self, id: u32) -> Result<Task> {
+        let pos = self
+            .workspace
+            .tasks
+            .iter()
+            .position(|t| t.id == Some(id))
+            .ok_or(KanbanError::NotFound { id })?;
+
+        let mut workspace = self.workspace.clone();
+        let removed = workspace.tasks.remove(pos);
+        workspace.events.retain(|e| e.task_id != id);
+        workspace.comments.retain(|c| c.task_id != id);
+        self.save(workspace)?;
+
+        Ok(removed)
+    }
+
+    fn list(&self, query: &TaskQuery) -> Result<Vec<Task>> {
+        Ok(query.apply(self.workspace.tasks.iter().cloned()))
+    }
+
+    fn append_event(&mut self, event: TaskEvent) -> Result<()> {
+        let mut workspace = self.workspace.clone();
+        workspace.events.push(event);
+        self.save(workspace)
+    }
+
+    fn history(&self, task_id: u32) -> Result<Vec<TaskEvent>> {
+        Ok(self
+            .workspace
+            .events
+            .iter()
+            .filter(|e| e.task_id == task_id)
+            .cloned()
+            .collect())
+    }
+
+    fn get_board(&self, id: u32) -> Result<Option<Board>> {
+        Ok(self
+            .workspace
+            .boards
+            .iter()
+            .find(|b| b.id == Some(id))
+            .cloned())
+    }
+
+    fn list_boards(&self) -> Result<Vec<Board>> {
+        Ok(self.workspace.boards.clone())
+    }
+
+    fn insert_board(&mut self, mut board: Board) -> Result<Board> {
+        let next_id = self.workspace.next_board_id;
+        board.id = Some(next_id);
+
+        let mut workspace = self.workspace.clone();
+        workspace.boards.push(board.clone());
+        workspace.next_board_id = next_id + 1;
+        self.save(workspace)?;
+
+        Ok(board)
+    }
+
+    fn update_board(&mut self, board: Board) -> Result<Board> {
+        let pos = self
+            .workspace
+            .boards
+            .iter()
+            .position(|b| b.id == board.id)
+            .ok_or(KanbanError::BoardNotFound {
+                id: board.id.unwrap_or_default(),
+            })?;
+
+        let mut workspace = self.workspace.clone();
+        workspace.boards[pos] = board.clone();
+        self.save(workspace)?;
+
+        Ok(board)
+    }
+
+    fn delete_board(&mut self, id: u32) -> Result<Board> {
+        let pos = self
+            .workspace
+            .boards
+            .iter()
+            .position(|b| b.id == Some(id))
+            .ok_or(KanbanError::BoardNotFound { id })?;
+
+        let mut workspace = self.workspace.clone();
+        let removed = workspace.boards.remove(pos);
+        self.save(workspace)?;
+
+        Ok(removed)
+    }
+
+    fn get_user(&self, id: u32) -> Result<Option<User>> {
+        Ok(self
+            .workspace
+            .users
+            .iter()
+            .find(|u| u.id == Some(id))
+            .cloned())
+    }
+
+    fn list_users(&self) -> Result<Vec<User>> {
+        Ok(self.workspace.users.clone())
+    }
+
+    fn insert_user(&mut self, mut user: User) -> Result<User> {
+        let next_id = self
+            .workspace
+            .users
+            .iter()
+            .filter_map(|u| u.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        user.id = Some(next_id);
+
+        let mut workspace = self.workspace.clone();
+        workspace.users.push(user.clone());
+        self.save(workspace)?;
+
+        Ok(user)
+    }
+
+    fn update_user(&mut self, user: User) -> Result<User> {
+        let pos = self
+            .workspace
+            .users
+            .iter()
+            .position(|u| u.id == user.id)
+            .ok_or(KanbanError::UserNotFound {
+                id: user.id.unwrap_or_default(),
+            })?;
+
+        let mut workspace = self.workspace.clone();
+        workspace.users[pos] = user.clone();
+        self.save(workspace)?;
+
+        Ok(user)
+    }
+
+    fn get_comment(&self, id: u32) -> Result<Option<Comment>> {
+        Ok(self
+            .workspace
+            .comments
+            .iter()
+            .find(|c| c.id == Some(id))
+            .cloned())
+    }
+
+    fn list_comments(&self, task_id: u32) -> Result<Vec<Comment>> {
+        Ok(self
+            .workspace
+            .comments
+            .iter()
+            .filter(|c| c.task_id == task_id)
+            .cloned()
+            .collect())
+    }
+
+    fn insert_comment(&mut self, mut comment: Comment) -> Result<Comment> {
+        // IDs are never reused, not even those of deleted comments
+        let next_id = self.workspace.next_comment_id;
+        comment.id = Some(next_id);
+
+        let mut workspace = self.workspace.clone();
+        workspace.comments.push(comment.clone());
+        workspace.next_comment_id = next_id + 1;
+        self.save(workspace)?;
+
+        Ok(comment)
+    }
+
+    fn update_comment(&mut self, comment: Comment) -> Result<Comment> {
+        let pos = self
+            .workspace
+            .comments
+            .iter()
+            .position(|c| c.id == comment.id)
+            .ok_or(KanbanError::CommentNotFound {
+                id: comment.id.unwrap_or_default(),
+            })?;
+
+        let mut workspace = self.workspace.clone();
+        workspace.comments[pos] = comment.clone();
+        self.save(workspace)?;
+
+        Ok(comment)
+    }
+
+    fn delete_comment(&mut self, id: u32) -> Result<Comment> {
+        let pos = self
+            .workspace
+            .comments
+            .iter()
+            .position(|c| c.id == Some(id))
+            .ok_or(KanbanError::CommentNotFound { id })?;
+
+        let mut workspace = self.workspace.clone();
+        let removed = workspace.comments.remove(pos);
+        self.save(workspace)?;
+
+        Ok(removed)
+    }
+
+    fn begin(&mut self) -> Result<()> {
+        if self.backup.is_some() {
+            return Err(KanbanError::Storage {
+                reason: String::from("a unit of work is already in progress"),
+            });
+        }
+
+        self.backup = Some(self.workspace.clone());
+        Ok(())
+    }
+
+    /// Writes the workspace with every change of the unit of work in a
+    /// single save; if that fails, the changes are dropped.
+    fn commit(&mut self) -> Result<()> {
+        let Some(backup) = self.backup.take() else {
+            return Err(KanbanError::Storage {
+                reason: String::from("no unit of work in progress"),
+            });
+        };
+
+        if let Err(err) = self.write(&self.workspace) {
+            self.workspace = backup;
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        match self.backup.take() {
+            Some(backup) => {
+                self.workspace = backup;
+                Ok(())
+            }
+            None => Err(KanbanError::Storage {
+                reason: String::from("no unit of work in progress"),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod json_repo_tests {
+    use super::*;
+    use crate::Status;
+    use crate::board::DEFAULT_BOARD_ID;
+    use crate::history::EventKind;
+    use crate::repository::contract_tests;
+    use crate::workflow::Workflow;
+    use tempfile::TempDir;
+
+    struct Setup {
+        dir: TempDir,
+        repo: JsonFileTaskRepository,
+    }
+
+    impl Setup {
+        fn new() -> Self {
+            let dir = TempDir::new().expect("temp dir not created");
+            let mut repo =
+                JsonFileTaskRepository::open(dir.path().join("board.json")).expect("not opened");
+
+            repo.insert(Task::new("task1".to_string(), "description1".to_string()))
+                .expect("task not inserted");
+            repo.insert(Task::new("task2".to_string(), "description2".to_string()))
+                .expect("task not inserted");
+
+            Setup { dir, repo }
+        }
+
+        fn reopen(&self) -> JsonFileTaskRepository {
+            JsonFileTaskRepository::open(self.repo.path()).expect("not reopened")
+        }
+    }
+
+    #[test]
+    fn missing_file_opens_empty_board() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("board.json");
+        let repo = JsonFileTaskRepository::open(&path).unwrap();
+
+        assert!(repo.list(&TaskQuery::new()).unwrap().is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn mutations_are_persisted() {
+        let mut setup = Setup::new();
+
+        let mut task = setup.repo.get(2).unwrap().unwrap();
+        task.status = Status::DOING;
+        setup.repo.update(task).unwrap();
+        setup.repo.delete(1).unwrap();
+
+        let reopened = setup.reopen();
+        let tasks = reopened.list(&TaskQuery::new()).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name, "task2");
+        assert_eq!(tasks[0].status, Status::DOING);
+        assert_eq!(
+            tasks[0].created_at,
+            setup.repo.get(2).unwrap().unwrap().created_at
+        );
+    }
+
+    #[test]
+    fn save_leaves_no_temp_file() {
+        let setup = Setup::new();
+
+        let entries: Vec<_> = fs::read_dir(setup.dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec!["board.json"]);
+    }
+
+    #[test]
+    fn file_uses_documented_format() {
+        let setup = Setup::new();
+
+        let raw = fs::read_to_string(setup.repo.path()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&raw).unwrap();
+        assert_eq!(value["version"], FORMAT_VERSION);
+        assert_eq!(value["tasks"][0]["id"], 1);
+        assert_eq!(value["tasks"][0]["name"], "task1");
+        assert_eq!(value["tasks"][0]["status"], "Todo");
+        assert_eq!(value["tasks"][0]["priority"], "medium");
+        assert!(value["tasks"][0]["updated_at"].is_null());
+    }
+
+    #[test]
+    fn history_is_persisted() {
+        let mut setup = Setup::new();
+
+        let event = TaskEvent::new(
+            2,
+            EventKind::Moved,
+            Some("Todo".to_string()),
+            Some("Doing".to_string()),
+            "alice",
+        );
+        setup.repo.append_event(event.clone()).unwrap();
+
+        let reopened = setup.reopen();
+        assert_eq!(reopened.history(2).unwrap(), vec![event]);
+        assert!(reopened.history(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn version_1_file_loads_without_history() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("board.json");
+        fs::write(
+            &path,
+            r#"{"version": 1, "tasks": [{"id": 1, "name": "old", "description": "",
+                "status": "Todo", "created_at": "2025-01-01T00:00:00Z", "updated_at": null}]}"#,
+        )
+        .unwrap();
+
+        let repo = JsonFileTaskRepository::open(&path).unwrap();
+        assert_eq!(repo.get(1).unwrap().unwrap().name, "old");
+        assert_eq!(repo.get(1).unwrap().unwrap().board_id, DEFAULT_BOARD_ID);
+        assert_eq!(
+            repo.list_boards().unwrap(),
+            vec![repo.get_board(DEFAULT_BOARD_ID).unwrap().unwrap()]
+        );
+        assert!(repo.history(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn boards_are_persisted() {
+        let mut setup = Setup::new();
+
+        let workflow = Workflow::linear(vec![Status::new("Open"), Status::new("Closed")]).unwrap();
+        let board = setup
+            .repo
+            .insert_board(Board::new("ops".to_string(), workflow))
+            .unwrap();
+        let mut task = setup.repo.get(2).unwrap().unwrap();
+        task.board_id = board.id.unwrap();
+        setup.repo.update(task).unwrap();
+
+        let reopened = setup.reopen();
+        assert_eq!(reopened.list_boards().unwrap().len(), 2);
+        assert_eq!(reopened.get_board(2).unwrap(), Some(board));
+        assert!(
+            reopened
+                .find_by_name(DEFAULT_BOARD_ID, "task2")
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            reopened.find_by_name(2, "TASK2").unwrap().unwrap().id,
+            Some(2)
+        );
+
+        setup.repo.delete_board(2).unwrap();
+        assert!(setup.reopen().get_board(2).unwrap().is_none());
+    }
+
+    #[test]
+    fn newer_format_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("board.json");
+        fs::write(&path, r#"{"version": 99, "tasks": []}"#).unwrap();
+
+        let err = JsonFileTaskRepository::open(&path).unwrap_err();
+        assert!(matches!(err, KanbanError::Storage { .. }));
+    }
+
+    #[test]
+    fn invalid_workflow_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("board.json");
+        let board = r#"{"id": 1, "name": "Default", "created_at": "2025-06-01T09:00:00Z",
+            "workflow": {"columns": [], "transitions": []}}"#;
+        fs::write(
+            &path,
+            format!(r#"{{"version": 3, "boards": [{}], "tasks": []}}"#, board),
+        )
+        .unwrap();
+
+        let err = JsonFileTaskRepository::open(&path).unwrap_err();
+        assert!(matches!(err, KanbanError::Storage { .. }));
+    }
+
+    #[test]
+    fn update_unknown_task_fails() {
+        let mut setup = Setup::new();
+
+        let mut task = Task::new("ghost".to_string(), "".to_string());
+        task.id = Some(42);
+        assert_eq!(
+            setup.repo.update(task).unwrap_err(),
+            KanbanError::NotFound { id: 42 }
+        );
+    }
+
+    #[test]
+    fn ids_of_deleted_tasks_are_not_reused() {
+        let mut setup = Setup::new();
+
+        setup.repo.delete(2).unwrap();
+        assert_eq!(setup.reopen().workspace.next_task_id, 3);
+
+        let mut reopened = setup.reopen();
+        let task = reopened
+            .insert(Task::new("task3".to_string(), String::new()))
+            .unwrap();
+        assert_eq!(task.id, Some(3));
+    }
+
+    #[test]
+    fn files_without_id_counter_continue_from_highest_id() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("board.json");
+        fs::write(
+            &path,
+            r#"{"version": 3, "tasks": [{"id": 7, "name": "old", "description": "",
+                "status": "Todo", "created_at": "2025-01-01T00:00:00Z", "updated_at": null}]}"#,
+        )
+        .unwrap();
+
+        let mut repo = JsonFileTaskRepository::open(&path).unwrap();
+        repo.delete(7).unwrap();
+        let task = repo
+            .insert(Task::new("new".to_string(), String::new()))
+            .unwrap();
+        assert_eq!(task.id, Some(8));
+    }
+
+    #[test]
+    fn users_and_assignees_are_persisted() {
+        let mut setup = Setup::new();
+
+        let mut user = User::new("alice".to_string());
+        user.wip_limit = Some(3);
+        let user = setup.repo.insert_user(user).unwrap();
+        let mut task = setup.repo.get(1).unwrap().unwrap();
+        task.assignees.insert(user.id.unwrap());
+        setup.repo.update(task).unwrap();
+
+        let reopened = setup.reopen();
+        assert_eq!(reopened.list_users().unwrap(), vec![user.clone()]);
+        assert!(reopened.get(1).unwrap().unwrap().assignees.contains(&1));
+
+        let mut renamed = user;
+        renamed.name = "alicia".to_string();
+        setup.repo.update_user(renamed).unwrap();
+        assert_eq!(setup.reopen().get_user(1).unwrap().unwrap().name, "alicia");
+    }
+
+    #[test]
+    fn comments_are_persisted() {
+        let mut setup = Setup::new();
+
+        let comment = Comment::new(1, "alice".to_string(), "looks *good*".to_string());
+        let mut comment = setup.repo.insert_comment(comment).unwrap();
+        setup
+            .repo
+            .insert_comment(Comment::new(2, "bob".to_string(), "me too".to_string()))
+            .unwrap();
+        comment.edit("looks **great**".to_string(), "alice");
+        setup.repo.update_comment(comment.clone()).unwrap();
+
+        let reopened = setup.reopen();
+        assert_eq!(reopened.list_comments(1).unwrap(), vec![comment]);
+        assert_eq!(reopened.get_comment(2).unwrap().unwrap().author, "bob");
+
+        setup.repo.delete_comment(2).unwrap();
+        setup.repo.delete(1).unwrap();
+        let reopened = setup.reopen();
+        assert!(reopened.get_comment(1).unwrap().is_none());
+        assert!(reopened.get_comment(2).unwrap().is_none());
+    }
+
+    #[test]
+    fn ids_of_deleted_comments_are_not_reused() {
+        let mut setup = Setup::new();
+        let comment = |body: &str| Comment::new(1, "alice".to_string(), body.to_string());
+
+        setup.repo.insert_comment(comment("first")).unwrap();
+        setup.repo.insert_comment(comment("second")).unwrap();
+        setup.repo.delete_comment(2).unwrap();
+
+        let mut reopened = setup.reopen();
+        let third = reopened.insert_comment(comment("third")).unwrap();
+        assert_eq!(third.id, Some(3));
+    }
+
+    #[test]
+    fn ids_of_deleted_boards_are_not_reused() {
+        let mut setup = Setup::new();
+        let board = |name: &str| Board::new(name.to_string(), Workflow::default());
+
+        setup.repo.insert_board(board("ops")).unwrap();
+        setup.repo.delete_board(2).unwrap();
+
+        let mut reopened = setup.reopen();
+        let next = reopened.insert_board(board("support")).unwrap();
+        assert_eq!(next.id, Some(3));
+    }
+
+    #[test]
+    fn unit_of_work_is_saved_once_on_commit() {
+        let mut setup = Setup::new();
+
+        setup.repo.begin().unwrap();
+        setup
+            .repo
+            .insert(Task::new("task3".to_string(), String::new()))
+            .unwrap();
+        setup
+            .repo
+            .append_event(TaskEvent::new(3, EventKind::Created, None, None, "alice"))
+            .unwrap();
+        assert!(setup.reopen().get(3).unwrap().is_none());
+
+        setup.repo.commit().unwrap();
+        let reopened = setup.reopen();
+        assert_eq!(reopened.get(3).unwrap().unwrap().name, "task3");
+        assert_eq!(reopened.history(3).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn units_of_work_commit_or_roll_back() {
+        let mut setup = Setup::new();
+        contract_tests::units_of_work_commit_or_roll_back(&mut setup.repo);
+    }
+
+    #[test]
+    fn names_match_ignoring_unicode_case() {
+        let mut setup = Setup::new();
+        contract_tests::names_match_ignoring_unicode_case(&mut setup.repo);
+    }
+}
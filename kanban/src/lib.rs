@@ -1,10 +1,35 @@
+pub mod analytics;
+pub mod board;
+pub mod cfd;
+pub mod comment;
+pub mod dependency;
 pub mod domain;
+pub mod error;
+pub mod eventsourced_repository;
+pub mod forecast;
+pub mod history;
 pub mod inmemory_repository;
+pub mod json_repository;
+pub mod patch;
+pub mod query;
 pub mod repository;
 pub mod service;
+pub mod sqlite_repository;
+pub mod user;
+pub mod workflow;
 
-use std::cmp::PartialEq;
-
-pub use domain::{Status, Task};
-pub use inmemory_repository::{InMemoryTaskRepo, InMemoryTaskRepository};
+pub use board::Board;
+pub use comment::Comment;
+pub use domain::{BlockedFlag, ChecklistItem, Priority, Progress, Status, Task};
+pub use error::KanbanError;
+pub use eventsourced_repository::EventSourcedTaskRepository;
+pub use history::{EventKind, TaskEvent};
+pub use inmemory_repository::InMemoryTaskRepository;
+pub use json_repository::JsonFileTaskRepository;
+pub use patch::TaskPatch;
+pub use query::TaskQuery;
+pub use repository::TaskRepository;
 pub use service::TaskService;
+pub use sqlite_repository::SqliteTaskRepository;
+pub use user::User;
+pub use workflow::Workflow;
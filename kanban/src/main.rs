@@ -1,3 +1,1904 @@
-fn main() {
-    println!("Hello, world!");
+//! Command-line front end for the kanban board.
+//!
+//! Exit codes:
+//! * `0` - success
+//! * `1` - storage failure
+//! * `2` - invalid command-line usage
+//! * `3` - task, board, user or comment not found
+//! * `4` - invalid input (validation failure or duplicate name)
+//! * `5` - transition not allowed from the task's current status, or the
+//!   task is blocked by unfinished tasks
+//! * `6` - target column is at its WIP limit
+
+use std::io::{self, IsTerminal};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use kanban::analytics::FlowStats;
+use kanban::domain::normalize_label;
+use kanban::error::Result;
+use kanban::forecast::{self, CompletionForecast, ForecastOptions, ItemsForecast};
+use kanban::query::SortKey;
+use kanban::user::Workload;
+use kanban::{
+    ChecklistItem, Comment, EventSourcedTaskRepository, JsonFileTaskRepository, KanbanError,
+    Priority, SqliteTaskRepository, Status, Task, TaskEvent, TaskPatch, TaskQuery, TaskRepository,
+    TaskService, User, Workflow,
+};
+
+/// Rows of the ASCII cumulative flow diagram.
+const CFD_HEIGHT: usize = 15;
+/// Size of the SVG cumulative flow diagram in pixels.
+const SVG_WIDTH: u32 = 800;
+const SVG_HEIGHT: u32 = 400;
+
+/// ANSI escape codes used to highlight due dates on a terminal.
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Parser)]
+#[command(name = "kanban", version, about = "A simple kanban board")]
+struct Cli {
+    /// Board file to use. `.db`, `.sqlite` and `.sqlite3` files are opened
+    /// as SQLite databases, `.jsonl` files as event logs, anything else as
+    /// JSON.
+    #[arg(long, global = true, default_value = "kanban.json")]
+    board: PathBuf,
+
+    /// Board within the file to work on, by name or ID (defaults to the
+    /// default board)
+    #[arg(long, global = true)]
+    on: Option<String>,
+
+    /// Name recorded as the author of changes (defaults to $USER)
+    #[arg(long, global = true, env = "USER")]
+    actor: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum Command {
+    /// Add a new task to the board
+    Add {
+        /// task name
+        name: String,
+        /// detailed description of the task
+        #[arg(short, long, default_value = "")]
+        description: String,
+        /// critical, high, medium or low
+        #[arg(short, long, default_value = "medium")]
+        priority: Priority,
+        /// when the task is due (YYYY-MM-DD for the end of that day, or RFC 3339)
+        #[arg(long, value_parser = parse_due)]
+        due: Option<DateTime<Utc>>,
+        /// label to attach (repeatable or comma separated)
+        #[arg(short, long = "label", value_delimiter = ',')]
+        labels: Vec<String>,
+    },
+    /// Add a subtask to a task, on the same board
+    Subtask {
+        /// parent task ID
+        parent: u32,
+        /// subtask name
+        name: String,
+        /// detailed description of the subtask
+        #[arg(short, long, default_value = "")]
+        description: String,
+    },
+    /// Make a task a subtask of another, or detach it with `--clear`
+    Parent {
+        id: u32,
+        /// parent task ID
+        #[arg(required_unless_present = "clear")]
+        parent: Option<u32>,
+        /// detach the task from its parent
+        #[arg(long, conflicts_with = "parent")]
+        clear: bool,
+    },
+    /// Edit the checklist of a task
+    Checklist {
+        id: u32,
+        #[command(subcommand)]
+        action: ChecklistCommand,
+    },
+    /// Record that one task blocks another
+    Link {
+        /// task that must be done first
+        blocker: u32,
+        /// task that waits for it
+        blocked: u32,
+    },
+    /// Remove a link between a blocker and the task it blocks
+    Unlink {
+        /// task that must be done first
+        blocker: u32,
+        /// task that waits for it
+        blocked: u32,
+    },
+    /// Show which tasks on the board block which
+    Graph {
+        /// output format
+        #[arg(long, value_enum, default_value_t = GraphFormat::Text)]
+        format: GraphFormat,
+    },
+    /// Change the name, description, priority or due date of a task
+    Edit {
+        id: u32,
+        /// new task name
+        #[arg(short, long)]
+        name: Option<String>,
+        /// new description
+        #[arg(short, long)]
+        description: Option<String>,
+        /// critical, high, medium or low
+        #[arg(short, long)]
+        priority: Option<Priority>,
+        /// when the task is due (YYYY-MM-DD for the end of that day, or RFC 3339)
+        #[arg(long, value_parser = parse_due)]
+        due: Option<DateTime<Utc>>,
+        /// remove the due date
+        #[arg(long, conflicts_with = "due")]
+        clear_due: bool,
+    },
+    /// Change the priority of a task
+    Prioritize {
+        id: u32,
+        /// critical, high, medium or low
+        priority: Priority,
+    },
+    /// Set or clear the date a task is due
+    Schedule {
+        id: u32,
+        /// when the task is due (YYYY-MM-DD for the end of that day, or RFC 3339)
+        #[arg(value_parser = parse_due, required_unless_present = "clear")]
+        due: Option<DateTime<Utc>>,
+        /// remove the due date
+        #[arg(long, conflicts_with = "due")]
+        clear: bool,
+    },
+    /// Attach labels to a task, or remove them with `--remove`
+    Label {
+        id: u32,
+        /// labels to attach or remove
+        #[arg(required = true, value_delimiter = ',')]
+        labels: Vec<String>,
+        /// remove the labels instead
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Manage the labels used on the board
+    #[command(subcommand)]
+    Labels(LabelCommand),
+    /// Assign a user to a task
+    Assign {
+        id: u32,
+        /// user name or ID
+        #[arg(value_name = "USER")]
+        user: String,
+    },
+    /// Remove a user from a task
+    Unassign {
+        id: u32,
+        /// user name or ID
+        #[arg(value_name = "USER")]
+        user: String,
+    },
+    /// Manage users and show their workload
+    #[command(subcommand)]
+    Users(UserCommand),
+    /// Discuss a task in markdown comments
+    #[command(subcommand)]
+    Comment(CommentCommand),
+    /// Show overdue tasks and tasks due soon
+    Due {
+        /// number of days ahead counted as due soon
+        #[arg(long, default_value_t = 3)]
+        within: u32,
+    },
+    /// Show the most urgent task waiting in the first column
+    Next,
+    /// Start working on a task (moves it to the workflow's start column)
+    Start {
+        id: u32,
+        /// ignore the WIP limit and flag the task as expedited
+        #[arg(long)]
+        expedite: bool,
+    },
+    /// Complete a task (moves it to the workflow's done column)
+    Done {
+        id: u32,
+        /// ignore the WIP limit and flag the task as expedited
+        #[arg(long)]
+        expedite: bool,
+    },
+    /// Send a finished task back to an earlier column
+    Reopen {
+        id: u32,
+        /// why the task is reopened
+        #[arg(short, long)]
+        reason: String,
+        /// column to reopen the task in (defaults to the start column)
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Send a task in progress back to the first column
+    Stop {
+        id: u32,
+        /// why work on the task stops
+        #[arg(short, long)]
+        reason: String,
+    },
+    /// Flag a task in progress as blocked
+    Block {
+        id: u32,
+        /// what the task is waiting on
+        #[arg(short, long)]
+        reason: String,
+    },
+    /// Clear a task's blocked flag
+    Unblock { id: u32 },
+    /// List blocked tasks with how long they have been blocked
+    Blocked,
+    /// Move a task to any column allowed by the workflow
+    Move {
+        id: u32,
+        /// target column name
+        column: String,
+        /// ignore the WIP limit and flag the task as expedited
+        #[arg(long)]
+        expedite: bool,
+    },
+    /// Move a task to another board, keeping its history
+    Transfer {
+        id: u32,
+        /// target board name or ID
+        #[arg(value_name = "BOARD")]
+        target: String,
+    },
+    /// Manage the boards in the file
+    #[command(subcommand)]
+    Boards(BoardCommand),
+    /// List tasks, optionally filtered, sorted and paged
+    List {
+        /// only list tasks in this status (repeatable)
+        #[arg(short, long)]
+        status: Vec<Status>,
+        /// only list tasks whose name contains this text
+        #[arg(short, long)]
+        name: Option<String>,
+        /// only list tasks carrying this label (repeatable, all must match)
+        #[arg(short, long = "label")]
+        labels: Vec<String>,
+        /// only list tasks carrying any of these labels (repeatable)
+        #[arg(long = "any-label")]
+        any_labels: Vec<String>,
+        /// only list tasks assigned to this user (name or ID)
+        #[arg(short, long)]
+        assignee: Option<String>,
+        /// sort by priority (then age), id, name, status, created, updated or due
+        #[arg(long, default_value = "priority")]
+        sort: SortKey,
+        /// reverse the sort order
+        #[arg(long)]
+        desc: bool,
+        /// number of tasks to skip
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+        /// maximum number of tasks to show
+        #[arg(long)]
+        limit: Option<usize>,
+        /// list archived tasks instead
+        #[arg(long)]
+        archived: bool,
+    },
+    /// Hide a task from the board, keeping its history and comments
+    Archive { id: u32 },
+    /// Bring an archived task back onto its board
+    Restore { id: u32 },
+    /// Delete a task for good, with its history and comments
+    Delete {
+        id: u32,
+        /// delete the task even if it is not archived
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Show the details of a task
+    Show { id: u32 },
+    /// Show every change made to a task
+    History { id: u32 },
+    /// Show lead and cycle time percentiles of tasks finished in a period
+    Stats {
+        /// first day of the period (YYYY-MM-DD), defaults to 30 days ago
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// last day of the period (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+    /// Show the board as it was at a point in time (event log board files only)
+    Replay {
+        /// point in time (YYYY-MM-DD for the end of that day, or RFC 3339)
+        #[arg(long, value_parser = parse_due)]
+        until: DateTime<Utc>,
+    },
+    /// Show daily task counts per column as a cumulative flow diagram
+    Cfd {
+        /// first day of the period (YYYY-MM-DD), defaults to 30 days ago
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// last day of the period (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// output format
+        #[arg(long, value_enum, default_value_t = ChartFormat::Ascii)]
+        format: ChartFormat,
+    },
+    /// Forecast delivery from past throughput with Monte Carlo simulation.
+    /// Without `--by`, forecasts when the tasks in the first column finish.
+    Forecast {
+        /// forecast how many tasks finish by this day (YYYY-MM-DD)
+        #[arg(long, conflicts_with = "items")]
+        by: Option<NaiveDate>,
+        /// forecast when this many tasks finish
+        #[arg(long)]
+        items: Option<u32>,
+        /// number of past days used as the throughput sample
+        #[arg(long, default_value_t = forecast::DEFAULT_HISTORY_DAYS)]
+        history: u32,
+        /// number of simulations
+        #[arg(long, default_value_t = forecast::DEFAULT_TRIALS)]
+        trials: usize,
+        /// seed for reproducible results
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum BoardCommand {
+    /// List every board
+    List,
+    /// Create a board
+    Add {
+        /// board name
+        name: String,
+        /// comma separated columns tasks move through in order
+        /// (defaults to Todo,Doing,Done)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<Status>,
+        /// require tasks to be assigned before work on them starts
+        #[arg(long)]
+        require_assignee: bool,
+        /// let tasks be done while checklist items or subtasks are open
+        #[arg(long)]
+        allow_incomplete_subtasks: bool,
+    },
+    /// Rename a board
+    Rename {
+        /// board name or ID
+        #[arg(value_name = "BOARD")]
+        target: String,
+        /// new name
+        name: String,
+    },
+    /// Delete an empty board
+    Delete {
+        /// board name or ID
+        #[arg(value_name = "BOARD")]
+        target: String,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum ChecklistCommand {
+    /// Add an item to the end of the checklist
+    Add {
+        /// what needs doing
+        text: String,
+    },
+    /// Mark an item as done
+    Check {
+        /// item number, starting at 1
+        item: NonZeroUsize,
+    },
+    /// Mark an item as not done
+    Uncheck {
+        /// item number, starting at 1
+        item: NonZeroUsize,
+    },
+    /// Remove an item
+    Remove {
+        /// item number, starting at 1
+        item: NonZeroUsize,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum LabelCommand {
+    /// List every label on the board with the number of tasks carrying it
+    List,
+    /// Rename a label on every task of the board
+    Rename {
+        /// current label
+        from: String,
+        /// new label
+        to: String,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum UserCommand {
+    /// List every user with their tasks in progress against their WIP limit
+    List,
+    /// Add a user
+    Add {
+        /// user name
+        name: String,
+        /// maximum number of tasks the user should have in progress
+        #[arg(long)]
+        wip: Option<usize>,
+    },
+    /// Set or clear a user's WIP limit
+    Limit {
+        /// user name or ID
+        #[arg(value_name = "USER")]
+        user: String,
+        /// maximum number of tasks in progress
+        #[arg(required_unless_present = "clear")]
+        wip: Option<usize>,
+        /// remove the limit
+        #[arg(long, conflicts_with = "wip")]
+        clear: bool,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum CommentCommand {
+    /// List the comments on a task, oldest first
+    List {
+        /// task ID
+        task: u32,
+        /// also show the earlier versions of edited comments
+        #[arg(long)]
+        revisions: bool,
+    },
+    /// Comment on a task
+    Add {
+        /// task ID
+        task: u32,
+        /// comment text in markdown, or `-` to read it from stdin
+        body: String,
+    },
+    /// Replace the text of a comment; the old text is kept as a revision
+    Edit {
+        /// comment ID
+        id: u32,
+        /// new text in markdown, or `-` to read it from stdin
+        body: String,
+    },
+    /// Delete a comment
+    Delete {
+        /// comment ID
+        id: u32,
+    },
+}
+
+/// Output formats of the cumulative flow diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ChartFormat {
+    /// one row per day, one column per workflow column
+    Csv,
+    /// stacked-area chart for the terminal
+    Ascii,
+    /// stacked-area chart as an SVG document
+    Svg,
+}
+
+/// Output formats of the dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GraphFormat {
+    /// one `blocker -> blocked` line per link
+    Text,
+    /// Graphviz DOT document
+    Dot,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = if let Command::Replay { until } = cli.command {
+        if is_event_log(&cli.board) {
+            EventSourcedTaskRepository::open_until(&cli.board, until)
+                .and_then(|repo| run(repo, &cli))
+        } else {
+            Err(KanbanError::Validation {
+                field: "board",
+                reason: String::from("replay needs an event log (.jsonl) board file"),
+            })
+        }
+    } else if is_sqlite(&cli.board) {
+        SqliteTaskRepository::open(&cli.board).and_then(|repo| run(repo, &cli))
+    } else if is_event_log(&cli.board) {
+        EventSourcedTaskRepository::open(&cli.board).and_then(|repo| run(repo, &cli))
+    } else {
+        JsonFileTaskRepository::open(&cli.board).and_then(|repo| run(repo, &cli))
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+fn is_sqlite(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("db" | "sqlite" | "sqlite3")
+    )
+}
+
+fn is_event_log(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("jsonl")
+}
+
+/// Maps a service error to the process exit code documented above.
+fn exit_code(err: &KanbanError) -> u8 {
+    match err {
+        KanbanError::Storage { .. } => 1,
+        KanbanError::NotFound { .. }
+        | KanbanError::BoardNotFound { .. }
+        | KanbanError::UserNotFound { .. }
+        | KanbanError::CommentNotFound { .. } => 3,
+        KanbanError::Validation { .. }
+        | KanbanError::DuplicateName { .. }
+        | KanbanError::DuplicateBoardName { .. }
+        | KanbanError::DuplicateUserName { .. } => 4,
+        KanbanError::InvalidTransition { .. } | KanbanError::Blocked { .. } => 5,
+        KanbanError::WipLimitExceeded { .. } => 6,
+    }
+}
+
+fn run<R: TaskRepository>(repo: R, cli: &Cli) -> Result<()> {
+    let mut svc = TaskService::new(repo)?;
+    if let Some(actor) = &cli.actor {
+        svc.set_actor(actor);
+    }
+    if let Some(board) = &cli.on {
+        let id = find_board(&svc, board)?;
+        svc.use_board(id)?;
+    }
+
+    execute(&mut svc, cli.command.clone())
+}
+
+fn execute<R: TaskRepository>(svc: &mut TaskService<R>, command: Command) -> Result<()> {
+    match command {
+        Command::Add {
+            name,
+            description,
+            priority,
+            due,
+            labels,
+        } => {
+            if due.is_some_and(|due| due < Utc::now()) {
+                return Err(KanbanError::Validation {
+                    field: "due_at",
+                    reason: String::from("must not be in the past"),
+                });
+            }
+            // check every label before the task is stored, so a bad one
+            // leaves nothing behind
+            let labels = labels
+                .iter()
+                .map(|label| normalize_label(label))
+                .collect::<Result<Vec<_>>>()?;
+            let mut task = svc.add_task_with_priority(&name, &description, priority)?;
+            if due.is_some() {
+                task = svc.set_due_date(task.id.unwrap_or_default(), due)?;
+            }
+            for label in &labels {
+                task = svc.add_label(task.id.unwrap_or_default(), label)?;
+            }
+            println!("Added task {}: {}", task.id.unwrap_or_default(), task.name);
+        }
+        Command::Edit {
+            id,
+            name,
+            description,
+            priority,
+            due,
+            clear_due,
+        } => {
+            let patch = TaskPatch {
+                name,
+                description,
+                priority,
+                due_at: if clear_due { Some(None) } else { due.map(Some) },
+            };
+            if patch.is_empty() {
+                return Err(KanbanError::Validation {
+                    field: "edit",
+                    reason: String::from("nothing to change"),
+                });
+            }
+            let task = svc.update_task(id, patch)?;
+            print_row(&task);
+        }
+        Command::Schedule { id, due, .. } => {
+            let task = svc.set_due_date(id, due)?;
+            match task.due_at {
+                Some(due) => println!("Task {} is due {}", id, format_time(due)),
+                None => println!("Task {} has no due date", id),
+            }
+        }
+        Command::Label { id, labels, remove } => {
+            let mut task = svc.find_by_id(id)?.ok_or(KanbanError::NotFound { id })?;
+            for label in &labels {
+                task = if remove {
+                    svc.remove_label(id, label)?
+                } else {
+                    svc.add_label(id, label)?
+                };
+            }
+            println!("Task {} labels: {}", id, format_labels(&task));
+        }
+        Command::Labels(command) => execute_label(svc, command)?,
+        Command::Assign { id, user } => {
+            let user = find_user(svc, &user)?;
+            svc.assign(id, user.id.unwrap_or_default())?;
+            println!("Task {} assigned to {}", id, user.name);
+        }
+        Command::Unassign { id, user } => {
+            let user = find_user(svc, &user)?;
+            svc.unassign(id, user.id.unwrap_or_default())?;
+            println!("Task {} unassigned from {}", id, user.name);
+        }
+        Command::Users(command) => execute_user(svc, command)?,
+        Command::Comment(command) => execute_comment(svc, command)?,
+        Command::Due { within } => {
+            let overdue = svc.overdue_tasks()?;
+            let soon = svc.due_soon(Duration::days(i64::from(within)))?;
+            if overdue.is_empty() && soon.is_empty() {
+                println!("Nothing due in the next {} days", within);
+            }
+            let color = std::io::stdout().is_terminal();
+            for task in &overdue {
+                print_due(task, "overdue", color.then_some(RED));
+            }
+            for task in &soon {
+                print_due(task, "due", color.then_some(YELLOW));
+            }
+        }
+        Command::Subtask {
+            parent,
+            name,
+            description,
+        } => {
+            let task = svc.add_subtask(parent, &name, &description)?;
+            println!(
+                "Added subtask {} to task {}: {}",
+                task.id.unwrap_or_default(),
+                parent,
+                task.name
+            );
+        }
+        Command::Parent { id, parent, .. } => {
+            svc.set_parent(id, parent)?;
+            match parent {
+                Some(parent) => println!("Task {} is now a subtask of {}", id, parent),
+                None => println!("Task {} has no parent", id),
+            }
+        }
+        Command::Checklist { id, action } => {
+            let task = match action {
+                ChecklistCommand::Add { text } => svc.add_checklist_item(id, &text)?,
+                ChecklistCommand::Check { item } => {
+                    svc.set_checklist_item_done(id, item.get() - 1, true)?
+                }
+                ChecklistCommand::Uncheck { item } => {
+                    svc.set_checklist_item_done(id, item.get() - 1, false)?
+                }
+                ChecklistCommand::Remove { item } => {
+                    svc.remove_checklist_item(id, item.get() - 1)?
+                }
+            };
+            print_checklist(&task.checklist);
+        }
+        Command::Link { blocker, blocked } => {
+            svc.add_dependency(blocker, blocked)?;
+            println!("Task {} now blocks task {}", blocker, blocked);
+        }
+        Command::Unlink { blocker, blocked } => {
+            svc.remove_dependency(blocker, blocked)?;
+            println!("Task {} no longer blocks task {}", blocker, blocked);
+        }
+        Command::Graph { format } => {
+            let graph = svc.dependency_graph()?;
+            match format {
+                GraphFormat::Dot => print!("{}", graph.to_dot()),
+                GraphFormat::Text => {
+                    if graph.edges.is_empty() {
+                        println!("No dependencies");
+                    }
+                    for (blocker, blocked) in &graph.edges {
+                        let marker = if graph.open_blockers(*blocked).contains(blocker) {
+                            ""
+                        } else {
+                            "  (done)"
+                        };
+                        println!("{:>4} -> {}{}", blocker, blocked, marker);
+                    }
+                }
+            }
+        }
+        Command::Prioritize { id, priority } => {
+            let task = svc.set_priority(id, priority)?;
+            println!("Task {} is now {} priority", id, task.priority);
+        }
+        Command::Next => match svc.next_task()? {
+            Some(task) => print_row(&task),
+            None => println!("No tasks waiting"),
+        },
+        Command::Start { id, expedite } => {
+            let column = task_workflow(svc, id)?.start_column().clone();
+            move_task(svc, id, column.as_str(), expedite)?
+        }
+        Command::Done { id, expedite } => {
+            let column = task_workflow(svc, id)?.done_column().clone();
+            move_task(svc, id, column.as_str(), expedite)?
+        }
+        Command::Reopen { id, reason, to } => {
+            let task = svc.reopen_task(id, to.as_deref(), &reason)?;
+            println!("Task {} reopened in {}", id, task.status);
+        }
+        Command::Stop { id, reason } => {
+            let task = svc.stop_task(id, &reason)?;
+            println!("Task {} moved back to {}", id, task.status);
+        }
+        Command::Block { id, reason } => {
+            svc.block_task(id, &reason)?;
+            println!("Task {} blocked", id);
+        }
+        Command::Unblock { id } => {
+            let blocked_for = svc.blocked_time(id)?;
+            svc.unblock_task(id)?;
+            println!(
+                "Task {} unblocked ({} blocked in total)",
+                id,
+                format_duration(blocked_for)
+            );
+        }
+        Command::Blocked => {
+            let tasks = svc.blocked_tasks()?;
+            if tasks.is_empty() {
+                println!("No blocked tasks");
+            }
+            let now = Utc::now();
+            for task in &tasks {
+                let Some(flag) = &task.blocked else { continue };
+                println!(
+                    "{:>4}  {:<10} {}  ({})",
+                    task.id.unwrap_or_default(),
+                    format_duration(now - flag.since),
+                    task.name,
+                    flag.reason
+                );
+            }
+        }
+        Command::Move {
+            id,
+            column,
+            expedite,
+        } => move_task(svc, id, &column, expedite)?,
+        Command::Transfer { id, target } => {
+            let board_id = find_board(svc, &target)?;
+            let task = svc.transfer_task(id, board_id)?;
+            println!("Task {} moved to board {} ({})", id, target, task.status);
+        }
+        Command::Boards(command) => execute_board(svc, command)?,
+        Command::List {
+            status,
+            name,
+            labels,
+            any_labels,
+            assignee,
+            sort,
+            desc,
+            offset,
+            limit,
+            archived,
+        } => {
+            let mut query = TaskQuery {
+                statuses: status,
+                name,
+                sort,
+                descending: desc,
+                offset,
+                limit,
+                ..TaskQuery::default()
+            };
+            for label in &labels {
+                query = query.label(label);
+            }
+            for label in &any_labels {
+                query = query.any_label(label);
+            }
+            if let Some(assignee) = assignee {
+                query = query.assigned_to(find_user(svc, &assignee)?.id.unwrap_or_default());
+            }
+            if archived {
+                query = query.archived();
+            }
+            let tasks = svc.list(&query)?;
+            if tasks.is_empty() {
+                println!("No tasks");
+            }
+            for task in &tasks {
+                print_row(task);
+            }
+        }
+        Command::Archive { id } => {
+            svc.archive_task(id)?;
+            println!("Archived task {}", id);
+        }
+        Command::Restore { id } => {
+            let task = svc.restore_task(id)?;
+            println!("Restored task {} to {}", id, task.status);
+        }
+        Command::Delete { id, force } => {
+            let task = svc.find_by_id(id)?.ok_or(KanbanError::NotFound { id })?;
+            if !task.is_archived() && !force {
+                return Err(KanbanError::Validation {
+                    field: "task",
+                    reason: format!("archive task {} first or pass --force to delete it", id),
+                });
+            }
+            svc.delete_task(id)?;
+            println!("Deleted task {}: {}", id, task.name);
+        }
+        Command::Show { id } => {
+            let task = svc.find_by_id(id)?.ok_or(KanbanError::NotFound { id })?;
+            print_details(&task, &svc.users()?);
+            print_breakdown(&task, &svc.subtasks(id)?, svc.progress(id)?.percent());
+        }
+        Command::History { id } => {
+            for event in svc.history(id)? {
+                print_event(&event);
+            }
+        }
+        Command::Stats { from, to } => {
+            let to = to.unwrap_or_else(|| Local::now().date_naive());
+            let from = from.unwrap_or(to - Duration::days(30));
+            let stats = svc.stats(
+                start_of_day(from, &Local),
+                start_of_day(to + Duration::days(1), &Local),
+            )?;
+            print_stats(&stats, from, to);
+            println!(
+                "Blocked:    {} in total on the board",
+                format_duration(svc.board_blocked_time()?)
+            );
+        }
+        Command::Replay { until } => {
+            println!("{} as of {}", svc.board().name, format_time(until));
+            for column in svc.workflow().columns().to_vec() {
+                let tasks = svc.list_by_status(column.clone())?;
+                println!("{} ({})", column, tasks.len());
+                for task in &tasks {
+                    print_row(task);
+                }
+            }
+        }
+        Command::Cfd { from, to, format } => {
+            let to = to.unwrap_or_else(|| Utc::now().date_naive());
+            let from = from.unwrap_or(to - Duration::days(30));
+            let cfd = svc.cumulative_flow(from, to)?;
+            match format {
+                ChartFormat::Csv => print!("{}", cfd.to_csv()),
+                ChartFormat::Ascii => print!("{}", cfd.render_ascii(CFD_HEIGHT)),
+                ChartFormat::Svg => print!("{}", cfd.render_svg(SVG_WIDTH, SVG_HEIGHT)),
+            }
+        }
+        Command::Forecast {
+            by,
+            items,
+            history,
+            trials,
+            seed,
+        } => {
+            let options = ForecastOptions {
+                history_days: history,
+                trials,
+                seed,
+            };
+            match by {
+                Some(by) => print_items_forecast(&svc.forecast_items(by, &options)?, by),
+                None => {
+                    let items = match items {
+                        Some(items) => items,
+                        None => {
+                            let initial = svc.workflow().initial().clone();
+                            svc.list_by_status(initial)?.len() as u32
+                        }
+                    };
+                    print_completion_forecast(&svc.forecast_completion(items, &options)?);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_board<R: TaskRepository>(svc: &mut TaskService<R>, command: BoardCommand) -> Result<()> {
+    match command {
+        BoardCommand::List => {
+            let current = svc.board().id;
+            for board in svc.boards()? {
+                let marker = if board.id == current { '*' } else { ' ' };
+                let columns: Vec<&str> = board
+                    .workflow
+                    .columns()
+                    .iter()
+                    .map(Status::as_str)
+                    .collect();
+                println!(
+                    "{} {:>3}  {:<16} {}",
+                    marker,
+                    board.id.unwrap_or_default(),
+                    board.name,
+                    columns.join(" -> ")
+                );
+            }
+        }
+        BoardCommand::Add {
+            name,
+            columns,
+            require_assignee,
+            allow_incomplete_subtasks,
+        } => {
+            let mut workflow = if columns.is_empty() {
+                Workflow::default()
+            } else {
+                Workflow::linear(columns)?
+            };
+            if require_assignee {
+                workflow = workflow.requiring_assignee();
+            }
+            if allow_incomplete_subtasks {
+                workflow = workflow.allowing_incomplete_subtasks();
+            }
+            let board = svc.create_board(&name, workflow)?;
+            println!(
+                "Added board {}: {}",
+                board.id.unwrap_or_default(),
+                board.name
+            );
+        }
+        BoardCommand::Rename { target, name } => {
+            let id = find_board(svc, &target)?;
+            let board = svc.rename_board(id, &name)?;
+            println!("Board {} renamed to {}", id, board.name);
+        }
+        BoardCommand::Delete { target } => {
+            let id = find_board(svc, &target)?;
+            let board = svc.delete_board(id)?;
+            println!("Deleted board {}: {}", id, board.name);
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_label<R: TaskRepository>(svc: &mut TaskService<R>, command: LabelCommand) -> Result<()> {
+    match command {
+        LabelCommand::List => {
+            let labels = svc.labels()?;
+            if labels.is_empty() {
+                println!("No labels");
+            }
+            for (label, count) in labels {
+                println!("{:>4}  {}", count, label);
+            }
+        }
+        LabelCommand::Rename { from, to } => {
+            let changed = svc.rename_label(&from, &to)?;
+            println!("Renamed {} to {} on {} tasks", from, to, changed);
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_user<R: TaskRepository>(svc: &mut TaskService<R>, command: UserCommand) -> Result<()> {
+    match command {
+        UserCommand::List => {
+            let workload = svc.workload()?;
+            if workload.is_empty() {
+                println!("No users");
+            }
+            for load in &workload {
+                print_workload(load);
+            }
+        }
+        UserCommand::Add { name, wip } => {
+            let user = svc.add_user(&name, wip)?;
+            println!("Added user {}: {}", user.id.unwrap_or_default(), user.name);
+        }
+        UserCommand::Limit { user, wip, .. } => {
+            let user = find_user(svc, &user)?;
+            let user = svc.set_user_wip_limit(user.id.unwrap_or_default(), wip)?;
+            match user.wip_limit {
+                Some(limit) => println!("{} may have {} tasks in progress", user.name, limit),
+                None => println!("{} has no WIP limit", user.name),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_comment<R: TaskRepository>(
+    svc: &mut TaskService<R>,
+    command: CommentCommand,
+) -> Result<()> {
+    match command {
+        CommentCommand::List { task, revisions } => {
+            let comments = svc.comments(task)?;
+            if comments.is_empty() {
+                println!("No comments");
+            }
+            for comment in &comments {
+                print_comment(comment, revisions);
+            }
+        }
+        CommentCommand::Add { task, body } => {
+            let comment = svc.add_comment(task, &read_body(body)?)?;
+            println!(
+                "Added comment {} to task {}",
+                comment.id.unwrap_or_default(),
+                task
+            );
+        }
+        CommentCommand::Edit { id, body } => {
+            let comment = svc.edit_comment(id, &read_body(body)?)?;
+            println!(
+                "Edited comment {} ({} earlier versions)",
+                id,
+                comment.revisions.len()
+            );
+        }
+        CommentCommand::Delete { id } => {
+            let comment = svc.delete_comment(id)?;
+            println!("Deleted comment {} from task {}", id, comment.task_id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the comment text given on the command line, reading it from
+/// stdin when it is `-`.
+fn read_body(body: String) -> Result<String> {
+    if body == "-" {
+        return Ok(io::read_to_string(io::stdin())?);
+    }
+
+    Ok(body)
+}
+
+/// Resolves a user given by name, or failing that by ID.
+fn find_user<R: TaskRepository>(svc: &TaskService<R>, user: &str) -> Result<User> {
+    if let Some(found) = svc.find_user(user)? {
+        return Ok(found);
+    }
+
+    match user.parse::<u32>() {
+        Ok(id) => svc
+            .users()?
+            .into_iter()
+            .find(|u| u.id == Some(id))
+            .ok_or(KanbanError::UserNotFound { id }),
+        Err(_) => Err(KanbanError::Validation {
+            field: "user",
+            reason: format!("unknown user '{}'", user),
+        }),
+    }
+}
+
+/// Resolves a board given by name, or failing that by ID.
+fn find_board<R: TaskRepository>(svc: &TaskService<R>, board: &str) -> Result<u32> {
+    if let Some(found) = svc.find_board(board)? {
+        return Ok(found.id.unwrap_or_default());
+    }
+
+    match board.parse::<u32>() {
+        Ok(id) if svc.boards()?.iter().any(|b| b.id == Some(id)) => Ok(id),
+        Ok(id) => Err(KanbanError::BoardNotFound { id }),
+        Err(_) => Err(KanbanError::Validation {
+            field: "board",
+            reason: format!("unknown board '{}'", board),
+        }),
+    }
+}
+
+/// Workflow of the board task `id` is on.
+fn task_workflow<R: TaskRepository>(svc: &TaskService<R>, id: u32) -> Result<Workflow> {
+    let task = svc.find_by_id(id)?.ok_or(KanbanError::NotFound { id })?;
+    svc.boards()?
+        .into_iter()
+        .find(|b| b.id == Some(task.board_id))
+        .map(|b| b.workflow)
+        .ok_or(KanbanError::BoardNotFound { id: task.board_id })
+}
+
+fn move_task<R: TaskRepository>(
+    svc: &mut TaskService<R>,
+    id: u32,
+    column: &str,
+    expedite: bool,
+) -> Result<()> {
+    let task = if expedite {
+        svc.expedite_task(id, column)?
+    } else {
+        svc.move_task(id, column)?
+    };
+    println!("Task {} moved to {}", id, task.status);
+    Ok(())
+}
+
+fn print_row(task: &Task) {
+    let labels = if task.labels.is_empty() {
+        String::new()
+    } else {
+        format!("  [{}]", format_labels(task))
+    };
+    let blocked = if task.blocked.is_some() {
+        "  (blocked)"
+    } else {
+        ""
+    };
+    println!(
+        "{:>4}  {:<6} {:<8} {}{}{}",
+        task.id.unwrap_or_default(),
+        task.status,
+        task.priority,
+        task.name,
+        labels,
+        blocked
+    );
+}
+
+/// Comma separated labels of a task, in order.
+fn format_labels(task: &Task) -> String {
+    let labels: Vec<&str> = task.labels.iter().map(String::as_str).collect();
+    labels.join(", ")
+}
+
+/// Prints the checklist, subtasks and overall progress of a task.
+fn print_breakdown(task: &Task, subtasks: &[Task], percent: Option<u8>) {
+    if let Some(parent) = task.parent_id {
+        println!("Parent:      {}", parent);
+    }
+    if !task.blocked_by.is_empty() {
+        let blockers: Vec<String> = task.blocked_by.iter().map(u32::to_string).collect();
+        println!("Blocked by:  {}", blockers.join(", "));
+    }
+    if let Some(percent) = percent {
+        println!("Progress:    {}%", percent);
+    }
+    if !task.checklist.is_empty() {
+        println!("Checklist:");
+        print_checklist(&task.checklist);
+    }
+    if !subtasks.is_empty() {
+        println!("Subtasks:");
+        for subtask in subtasks {
+            print_row(subtask);
+        }
+    }
+}
+
+fn print_checklist(checklist: &[ChecklistItem]) {
+    if checklist.is_empty() {
+        println!("Checklist is empty");
+    }
+    for (pos, item) in checklist.iter().enumerate() {
+        println!("{:>4}  {}", pos + 1, item);
+    }
+}
+
+fn print_workload(load: &Workload) {
+    let limit = load
+        .user
+        .wip_limit
+        .map_or_else(|| String::from("-"), |limit| limit.to_string());
+    let marker = if load.is_over_limit() {
+        "  over limit"
+    } else {
+        ""
+    };
+    println!(
+        "{:>4}  {:<16} {:>3} / {}{}",
+        load.user.id.unwrap_or_default(),
+        load.user.name,
+        load.in_progress,
+        limit,
+        marker
+    );
+}
+
+fn print_details(task: &Task, users: &[User]) {
+    println!("ID:          {}", task.id.unwrap_or_default());
+    println!("Name:        {}", task.name);
+    println!("Status:      {}", task.status);
+    println!("Priority:    {}", task.priority);
+    println!("Description: {}", task.description);
+    println!("Created:     {}", task.created_at.to_rfc3339());
+    if let Some(updated_at) = task.updated_at {
+        println!("Updated:     {}", updated_at.to_rfc3339());
+    }
+    if !task.labels.is_empty() {
+        println!("Labels:      {}", format_labels(task));
+    }
+    if !task.assignees.is_empty() {
+        let names: Vec<String> = task
+            .assignees
+            .iter()
+            .map(|id| {
+                users
+                    .iter()
+                    .find(|u| u.id == Some(*id))
+                    .map_or_else(|| format!("#{}", id), |u| u.name.clone())
+            })
+            .collect();
+        println!("Assignees:   {}", names.join(", "));
+    }
+    if let Some(due_at) = task.due_at {
+        println!("Due:         {}", format_time(due_at));
+    }
+    if task.completed_late {
+        println!("Late:        yes");
+    }
+    if task.expedited {
+        println!("Expedited:   yes");
+    }
+    if let Some(archived_at) = task.archived_at {
+        println!("Archived:    {}", format_time(archived_at));
+    }
+    if let Some(flag) = &task.blocked {
+        println!(
+            "Blocked:     {} (since {})",
+            flag.reason,
+            format_time(flag.since)
+        );
+    }
+}
+
+/// Prints a task with its due date, wrapped in `color` when given.
+fn print_due(task: &Task, label: &str, color: Option<&str>) {
+    let line = format!(
+        "{:>4}  {:<7} {}  {}",
+        task.id.unwrap_or_default(),
+        label,
+        task.due_at.map(format_time).unwrap_or_default(),
+        task.name
+    );
+    match color {
+        Some(color) => println!("{}{}{}", color, line, RESET),
+        None => println!("{}", line),
+    }
+}
+
+/// Formats a timestamp in local time, e.g. `2025-06-01 17:00`.
+fn format_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Parses a due date given as RFC 3339, or as `YYYY-MM-DD` meaning the end
+/// of that day in local time.
+fn parse_due(value: &str) -> std::result::Result<DateTime<Utc>, String> {
+    if let Ok(due) = DateTime::parse_from_rfc3339(value) {
+        return Ok(due.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("'{}' is not a YYYY-MM-DD or RFC 3339 date", value))?;
+    let end_of_day = date.and_hms_opt(23, 59, 59).unwrap_or_default();
+    Local
+        .from_local_datetime(&end_of_day)
+        .latest()
+        .map(|due| due.with_timezone(&Utc))
+        .ok_or_else(|| format!("'{}' does not exist in the local time zone", value))
+}
+
+/// Returns the first instant of `date` in the time zone `tz`. Days are
+/// local like the due dates parsed by [`parse_due`], so a task due on a day
+/// falls within that day's window.
+fn start_of_day<Tz: TimeZone>(date: NaiveDate, tz: &Tz) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    // a DST change can skip midnight, the day then starts an hour later
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(midnight + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+fn print_items_forecast(forecast: &ItemsForecast, by: NaiveDate) {
+    println!("Tasks finished by {} ({} days):", by, forecast.days);
+    println!("  50%: {} or more", forecast.p50);
+    println!("  85%: {} or more", forecast.p85);
+    println!("  95%: {} or more", forecast.p95);
+}
+
+fn print_completion_forecast(forecast: &CompletionForecast) {
+    println!("{} tasks finished by:", forecast.items);
+    println!("  50%: {}", forecast.p50);
+    println!("  85%: {}", forecast.p85);
+    println!("  95%: {}", forecast.p95);
+}
+
+fn print_stats(stats: &FlowStats, from: NaiveDate, to: NaiveDate) {
+    println!("Period:     {} to {}", from, to);
+    println!("Completed:  {}", stats.completed);
+    match stats.on_time_rate() {
+        Some(rate) => println!(
+            "On time:    {:.0}% ({} of {} with a due date)",
+            rate * 100.0,
+            stats.on_time,
+            stats.due_dated
+        ),
+        None => println!("On time:    n/a"),
+    }
+    match stats.rework_rate() {
+        Some(rate) => println!(
+            "Rework:     {:.0}% ({} of {} reopened before finishing)",
+            rate * 100.0,
+            stats.reworked,
+            stats.completed
+        ),
+        None => println!("Rework:     n/a"),
+    }
+    match stats.flow_efficiency() {
+        Some(efficiency) => println!(
+            "Efficiency: {:.0}% ({} active, {} blocked)",
+            efficiency * 100.0,
+            format_duration(stats.active_time),
+            format_duration(stats.blocked_time)
+        ),
+        None => println!("Efficiency: n/a"),
+    }
+    for (label, percentiles) in [
+        ("Lead time", stats.lead_time),
+        ("Cycle time", stats.cycle_time),
+    ] {
+        match percentiles {
+            Some(p) => println!(
+                "{:<11} p50 {}  p85 {}  p95 {}",
+                format!("{}:", label),
+                format_duration(p.p50),
+                format_duration(p.p85),
+                format_duration(p.p95)
+            ),
+            None => println!("{:<11} n/a", format!("{}:", label)),
+        }
+    }
+}
+
+/// Formats a duration as days, hours and minutes, e.g. `2d 3h 15m`.
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
+
+fn print_comment(comment: &Comment, revisions: bool) {
+    let edited = match (&comment.edited_by, comment.updated_at) {
+        (Some(editor), Some(at)) => format!(" (edited by {} {})", editor, format_time(at)),
+        _ => String::new(),
+    };
+    println!(
+        "#{} {} {}{}",
+        comment.id.unwrap_or_default(),
+        comment.author,
+        format_time(comment.created_at),
+        edited
+    );
+    print_indented(&comment.body, 4);
+
+    if revisions {
+        for (n, revision) in comment.revisions.iter().enumerate() {
+            println!(
+                "    revision {} by {} {}",
+                n + 1,
+                revision.author,
+                format_time(revision.written_at)
+            );
+            print_indented(&revision.body, 8);
+        }
+    }
+    println!();
+}
+
+fn print_indented(text: &str, indent: usize) {
+    for line in text.lines() {
+        println!("{:indent$}{}", "", line, indent = indent);
+    }
+}
+
+fn print_event(event: &TaskEvent) {
+    // multi-line values such as comment bodies are shown on one line
+    let one_line = |value: &String| value.lines().collect::<Vec<_>>().join(" ");
+    let change = match (&event.old_value, &event.new_value) {
+        (Some(old), Some(new)) => format!("{} -> {}", one_line(old), one_line(new)),
+        (None, Some(new)) => one_line(new),
+        (Some(old), None) => one_line(old),
+        (None, None) => String::new(),
+    };
+
+    let reason = event
+        .reason
+        .as_ref()
+        .map(|reason| format!("  ({})", one_line(reason)))
+        .unwrap_or_default();
+
+    println!(
+        "{}  {:<11} {:<12} {}{}",
+        event.at.to_rfc3339(),
+        event.kind,
+        event.actor,
+        change,
+        reason
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_list_with_status() {
+        let cli = Cli::try_parse_from([
+            "kanban", "--board", "b.db", "list", "-s", "todo", "-s", "doing", "--sort", "name",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.board, PathBuf::from("b.db"));
+        assert!(matches!(
+            cli.command,
+            Command::List { status, sort: SortKey::Name, .. }
+                if status == vec![Status::TODO, Status::DOING]
+        ));
+    }
+
+    #[test]
+    fn parses_priorities() {
+        let cli = Cli::try_parse_from(["kanban", "add", "fix", "-p", "HIGH"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Add {
+                priority: Priority::High,
+                ..
+            }
+        ));
+
+        let cli = Cli::try_parse_from(["kanban", "list"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::List {
+                sort: SortKey::Priority,
+                ..
+            }
+        ));
+        assert!(Cli::try_parse_from(["kanban", "prioritize", "1", "urgent"]).is_err());
+    }
+
+    #[test]
+    fn stats_days_start_at_local_midnight() {
+        let day = NaiveDate::from_ymd_opt(2030, 1, 2).unwrap();
+        let east = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(
+            start_of_day(day, &east),
+            Utc.with_ymd_and_hms(2030, 1, 1, 22, 0, 0).unwrap()
+        );
+        let west = FixedOffset::west_opt(5 * 3600).unwrap();
+        assert_eq!(
+            start_of_day(day, &west),
+            Utc.with_ymd_and_hms(2030, 1, 2, 5, 0, 0).unwrap()
+        );
+
+        // a task due on the 1st falls in that day's window, not the next
+        let due = parse_due("2030-01-01").unwrap();
+        assert!(start_of_day(day - Duration::days(1), &Local) <= due);
+        assert!(due < start_of_day(day, &Local));
+    }
+
+    #[test]
+    fn parses_due_dates() {
+        let cli = Cli::try_parse_from([
+            "kanban",
+            "add",
+            "ship",
+            "--due",
+            "2030-01-01T12:00:00+02:00",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Add { due: Some(due), .. } if due == Utc.with_ymd_and_hms(2030, 1, 1, 10, 0, 0).unwrap()
+        ));
+
+        let end_of_day = parse_due("2030-01-01").unwrap().with_timezone(&Local);
+        assert_eq!(
+            end_of_day.date_naive(),
+            NaiveDate::from_ymd_opt(2030, 1, 1).unwrap()
+        );
+        assert_eq!(
+            end_of_day.time(),
+            NaiveTime::from_hms_opt(23, 59, 59).unwrap()
+        );
+        assert!(parse_due("next week").is_err());
+
+        let cli = Cli::try_parse_from(["kanban", "schedule", "2", "--clear"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Schedule {
+                id: 2,
+                due: None,
+                clear: true
+            }
+        ));
+        assert!(Cli::try_parse_from(["kanban", "schedule", "2"]).is_err());
+        assert!(matches!(
+            Cli::try_parse_from(["kanban", "due"]).unwrap().command,
+            Command::Due { within: 3 }
+        ));
+    }
+
+    #[test]
+    fn parses_edit() {
+        let cli = Cli::try_parse_from([
+            "kanban",
+            "edit",
+            "3",
+            "-n",
+            "renamed",
+            "-p",
+            "high",
+            "--clear-due",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Edit {
+                id: 3,
+                name: Some(name),
+                description: None,
+                priority: Some(Priority::High),
+                due: None,
+                clear_due: true,
+            } if name == "renamed"
+        ));
+        assert!(
+            Cli::try_parse_from(["kanban", "edit", "3", "--due", "2030-01-01", "--clear-due"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_archive_commands() {
+        let cli = Cli::try_parse_from(["kanban", "list", "--archived"]).unwrap();
+        assert!(matches!(cli.command, Command::List { archived: true, .. }));
+        let cli = Cli::try_parse_from(["kanban", "delete", "4", "--force"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Delete { id: 4, force: true }
+        ));
+        let cli = Cli::try_parse_from(["kanban", "restore", "4"]).unwrap();
+        assert!(matches!(cli.command, Command::Restore { id: 4 }));
+    }
+
+    #[test]
+    fn parses_reopen_and_stop() {
+        let cli = Cli::try_parse_from(["kanban", "reopen", "3", "-r", "bug found", "--to", "Todo"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Reopen { id: 3, reason, to: Some(to) } if reason == "bug found" && to == "Todo"
+        ));
+        let cli = Cli::try_parse_from(["kanban", "stop", "3", "--reason", "oops"]).unwrap();
+        assert!(matches!(cli.command, Command::Stop { id: 3, .. }));
+        assert!(Cli::try_parse_from(["kanban", "reopen", "3"]).is_err());
+    }
+
+    #[test]
+    fn parses_block_commands() {
+        let cli = Cli::try_parse_from(["kanban", "block", "2", "-r", "waiting on ops"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Block { id: 2, reason } if reason == "waiting on ops"
+        ));
+        assert!(Cli::try_parse_from(["kanban", "block", "2"]).is_err());
+        let cli = Cli::try_parse_from(["kanban", "unblock", "2"]).unwrap();
+        assert!(matches!(cli.command, Command::Unblock { id: 2 }));
+        let cli = Cli::try_parse_from(["kanban", "blocked"]).unwrap();
+        assert!(matches!(cli.command, Command::Blocked));
+    }
+
+    #[test]
+    fn parses_replay() {
+        let cli =
+            Cli::try_parse_from(["kanban", "replay", "--until", "2025-06-01T12:00:00Z"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Replay { until } if until == Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()
+        ));
+        assert!(Cli::try_parse_from(["kanban", "replay"]).is_err());
+        assert!(is_event_log(Path::new("board.jsonl")));
+        assert!(!is_event_log(Path::new("board.json")));
+    }
+
+    #[test]
+    fn parses_labels() {
+        let cli =
+            Cli::try_parse_from(["kanban", "add", "fix", "-l", "bug,ui", "-l", "p1"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Add { labels, .. } if labels == ["bug", "ui", "p1"]
+        ));
+
+        let cli = Cli::try_parse_from([
+            "kanban",
+            "list",
+            "--label",
+            "bug",
+            "--any-label",
+            "ui",
+            "--any-label",
+            "api",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::List { labels, any_labels, .. } if labels == ["bug"] && any_labels.len() == 2
+        ));
+
+        assert!(Cli::try_parse_from(["kanban", "label", "1"]).is_err());
+        let cli = Cli::try_parse_from(["kanban", "labels", "rename", "fe", "frontend"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Labels(LabelCommand::Rename { from, .. }) if from == "fe"
+        ));
+    }
+
+    #[test]
+    fn parses_user_commands() {
+        let cli = Cli::try_parse_from(["kanban", "users", "add", "alice", "--wip", "2"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Users(UserCommand::Add { name, wip: Some(2) }) if name == "alice"
+        ));
+
+        let cli = Cli::try_parse_from(["kanban", "users", "limit", "alice", "--clear"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Users(UserCommand::Limit {
+                wip: None,
+                clear: true,
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["kanban", "users", "limit", "alice"]).is_err());
+
+        let cli = Cli::try_parse_from(["kanban", "assign", "3", "bob"]).unwrap();
+        assert!(matches!(cli.command, Command::Assign { id: 3, user } if user == "bob"));
+        let cli = Cli::try_parse_from(["kanban", "list", "-a", "bob"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::List { assignee: Some(user), .. } if user == "bob"
+        ));
+        let cli =
+            Cli::try_parse_from(["kanban", "boards", "add", "ops", "--require-assignee"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Boards(BoardCommand::Add {
+                require_assignee: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_checklists_and_subtasks() {
+        let cli = Cli::try_parse_from(["kanban", "checklist", "4", "add", "write tests"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Checklist { id: 4, action: ChecklistCommand::Add { text } } if text == "write tests"
+        ));
+        let cli = Cli::try_parse_from(["kanban", "checklist", "4", "check", "2"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Checklist { action: ChecklistCommand::Check { item }, .. } if item.get() == 2
+        ));
+        assert!(Cli::try_parse_from(["kanban", "checklist", "4", "check", "0"]).is_err());
+
+        let cli = Cli::try_parse_from(["kanban", "subtask", "4", "review", "-d", "x"]).unwrap();
+        assert!(matches!(cli.command, Command::Subtask { parent: 4, .. }));
+        let cli = Cli::try_parse_from(["kanban", "parent", "5", "--clear"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Parent {
+                id: 5,
+                parent: None,
+                clear: true
+            }
+        ));
+        assert!(Cli::try_parse_from(["kanban", "parent", "5"]).is_err());
+    }
+
+    #[test]
+    fn parses_dependency_commands() {
+        let cli = Cli::try_parse_from(["kanban", "link", "1", "2"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Link {
+                blocker: 1,
+                blocked: 2
+            }
+        ));
+        let cli = Cli::try_parse_from(["kanban", "graph", "--format", "dot"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Graph {
+                format: GraphFormat::Dot
+            }
+        ));
+        assert!(Cli::try_parse_from(["kanban", "unlink", "1"]).is_err());
+    }
+
+    #[test]
+    fn parses_comment_commands() {
+        let cli = Cli::try_parse_from(["kanban", "comment", "add", "3", "**done**"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Comment(CommentCommand::Add { task: 3, body }) if body == "**done**"
+        ));
+        let cli = Cli::try_parse_from(["kanban", "comment", "list", "3", "--revisions"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Comment(CommentCommand::List {
+                task: 3,
+                revisions: true
+            })
+        ));
+        let cli = Cli::try_parse_from(["kanban", "comment", "edit", "1", "-"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Comment(CommentCommand::Edit { id: 1, body }) if body == "-"
+        ));
+        assert!(Cli::try_parse_from(["kanban", "comment", "delete"]).is_err());
+    }
+
+    #[test]
+    fn parses_cfd_format() {
+        let cli = Cli::try_parse_from(["kanban", "cfd", "--format", "svg"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Cfd {
+                format: ChartFormat::Svg,
+                from: None,
+                ..
+            }
+        ));
+        assert!(Cli::try_parse_from(["kanban", "cfd", "--format", "png"]).is_err());
+    }
+
+    #[test]
+    fn parses_forecast_options() {
+        let cli =
+            Cli::try_parse_from(["kanban", "forecast", "--items", "5", "--seed", "9"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Forecast {
+                items: Some(5),
+                seed: Some(9),
+                trials: forecast::DEFAULT_TRIALS,
+                ..
+            }
+        ));
+        assert!(
+            Cli::try_parse_from(["kanban", "forecast", "--items", "5", "--by", "2030-01-01"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_board_commands() {
+        let cli = Cli::try_parse_from([
+            "kanban",
+            "boards",
+            "add",
+            "ops",
+            "--columns",
+            "Backlog,Todo,Done",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Boards(BoardCommand::Add { name, columns, require_assignee: false, .. })
+                if name == "ops" && columns.len() == 3 && columns[1] == Status::TODO
+        ));
+
+        let cli =
+            Cli::try_parse_from(["kanban", "--on", "ops", "transfer", "3", "Default"]).unwrap();
+        assert_eq!(cli.on.as_deref(), Some("ops"));
+        assert!(matches!(cli.command, Command::Transfer { id: 3, .. }));
+    }
+
+    #[test]
+    fn board_extension_selects_backend() {
+        assert!(is_sqlite(Path::new("team.sqlite")));
+        assert!(is_sqlite(Path::new("team.db")));
+        assert!(!is_sqlite(Path::new("kanban.json")));
+    }
+
+    #[test]
+    fn durations_are_human_readable() {
+        assert_eq!(format_duration(Duration::minutes(45)), "45m");
+        assert_eq!(format_duration(Duration::minutes(135)), "2h 15m");
+        assert_eq!(
+            format_duration(Duration::days(3) + Duration::hours(1)),
+            "3d 1h 0m"
+        );
+    }
+
+    #[test]
+    fn errors_map_to_exit_codes() {
+        assert_eq!(exit_code(&KanbanError::NotFound { id: 1 }), 3);
+        assert_eq!(exit_code(&KanbanError::BoardNotFound { id: 1 }), 3);
+        assert_eq!(exit_code(&KanbanError::UserNotFound { id: 1 }), 3);
+        assert_eq!(exit_code(&KanbanError::CommentNotFound { id: 1 }), 3);
+        assert_eq!(
+            exit_code(&KanbanError::InvalidTransition {
+                from: Status::TODO,
+                to: Status::DONE
+            }),
+            5
+        );
+        assert_eq!(
+            exit_code(&KanbanError::Blocked {
+                id: 2,
+                blockers: vec![1]
+            }),
+            5
+        );
+    }
 }
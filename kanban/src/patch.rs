@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+
+use crate::Priority;
+
+/// A partial update of a task's editable fields.
+///
+/// Fields left as `None` keep their current value. Built with chained calls
+/// and applied with [`TaskService::update_task`](crate::TaskService::update_task):
+///
+/// ```
+/// use kanban::{Priority, TaskPatch};
+///
+/// let patch = TaskPatch::new()
+///     .name("write release notes")
+///     .priority(Priority::High)
+///     .clear_due();
+/// assert!(!patch.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskPatch {
+    /// new task name
+    pub name: Option<String>,
+    /// new description
+    pub description: Option<String>,
+    /// new priority
+    pub priority: Option<Priority>,
+    /// new due date; `Some(None)` removes the due date
+    pub due_at: Option<Option<DateTime<Utc>>>,
+}
+
+impl TaskPatch {
+    /// Creates a patch that changes nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renames the task.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Replaces the task's description.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Changes the task's priority.
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Sets the task's due date.
+    pub fn due(mut self, due_at: DateTime<Utc>) -> Self {
+        self.due_at = Some(Some(due_at));
+        self
+    }
+
+    /// Removes the task's due date.
+    pub fn clear_due(mut self) -> Self {
+        self.due_at = Some(None);
+        self
+    }
+
+    /// Returns `true` if the patch leaves every field as it is.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_sets_fields() {
+        assert!(TaskPatch::new().is_empty());
+
+        let patch = TaskPatch::new()
+            .description("")
+            .priority(Priority::Low)
+            .clear_due();
+        assert_eq!(patch.name, None);
+        assert_eq!(patch.description.as_deref(), Some(""));
+        assert_eq!(patch.priority, Some(Priority::Low));
+        assert_eq!(patch.due_at, Some(None));
+        assert!(!patch.is_empty());
+    }
+}
@@ -0,0 +1,543 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{KanbanError, Result};
+use crate::{Status, Task};
+
+/// Field used to order query results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// order of creation in the repository
+    #[default]
+    Id,
+    /// task name (case-insensitive)
+    Name,
+    /// column, in the order of [`TaskQuery::columns`]
+    Status,
+    /// creation timestamp
+    CreatedAt,
+    /// last update timestamp; never-updated tasks sort first
+    UpdatedAt,
+    /// priority, most urgent first, then oldest first
+    Priority,
+    /// due date, soonest first; tasks without one sort last
+    DueAt,
+}
+
+impl FromStr for SortKey {
+    type Err = KanbanError;
+
+    /// Parses `id`, `name`, `status`, `created`, `updated`, `priority` or `due`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "id" => Ok(SortKey::Id),
+            "name" => Ok(SortKey::Name),
+            "status" => Ok(SortKey::Status),
+            "created" | "created_at" => Ok(SortKey::CreatedAt),
+            "updated" | "updated_at" => Ok(SortKey::UpdatedAt),
+            "priority" => Ok(SortKey::Priority),
+            "due" | "due_at" => Ok(SortKey::DueAt),
+            _ => Err(KanbanError::Validation {
+                field: "sort",
+                reason: format!("unknown sort key '{}'", s),
+            }),
+        }
+    }
+}
+
+/// Which tasks a query matches with respect to the archive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArchiveFilter {
+    /// only tasks that are not archived
+    #[default]
+    Active,
+    /// only archived tasks
+    Archived,
+    /// archived or not
+    All,
+}
+
+/// Criteria, ordering and paging applied to a task listing.
+///
+/// Built with chained calls; an empty query returns every task that is not
+/// archived, ordered by ID.
+///
+/// ```
+/// use kanban::{Status, TaskQuery};
+/// use kanban::query::SortKey;
+///
+/// let query = TaskQuery::new()
+///     .status(Status::TODO)
+///     .status(Status::DOING)
+///     .name_contains("docs")
+///     .label("bug")
+///     .sort_by(SortKey::CreatedAt)
+///     .descending()
+///     .limit(10);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskQuery {
+    /// only return tasks on this board; `None` means any board
+    pub board: Option<u32>,
+    /// only return tasks in one of these statuses; empty means any status
+    pub statuses: Vec<Status>,
+    /// only return tasks whose name contains this text (case-insensitive)
+    pub name: Option<String>,
+    /// only return tasks created at or after this time
+    pub created_from: Option<DateTime<Utc>>,
+    /// only return tasks created before this time
+    pub created_until: Option<DateTime<Utc>>,
+    /// only return tasks updated at or after this time
+    pub updated_from: Option<DateTime<Utc>>,
+    /// only return tasks updated before this time
+    pub updated_until: Option<DateTime<Utc>>,
+    /// only return tasks due at or after this time
+    pub due_from: Option<DateTime<Utc>>,
+    /// only return tasks due before this time
+    pub due_until: Option<DateTime<Utc>>,
+    /// only return tasks carrying every one of these labels
+    pub all_labels: BTreeSet<String>,
+    /// only return tasks carrying at least one of these labels; empty means
+    /// any labels
+    pub any_labels: BTreeSet<String>,
+    /// only return tasks assigned to this user
+    pub assignee: Option<u32>,
+    /// only return subtasks of this task
+    pub parent: Option<u32>,
+    /// whether archived tasks are returned
+    pub archive: ArchiveFilter,
+    /// column order used by [`SortKey::Status`]; statuses not listed sort
+    /// after the listed ones, by name
+    pub columns: Vec<Status>,
+    /// field the results are ordered by
+    pub sort: SortKey,
+    /// reverse the sort order
+    pub descending: bool,
+    /// number of matching tasks to skip
+    pub offset: usize,
+    /// maximum number of tasks to return
+    pub limit: Option<usize>,
+}
+
+impl TaskQuery {
+    /// Creates a query matching every task that is not archived.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches tasks on the board with the given ID.
+    pub fn board(mut self, board_id: u32) -> Self {
+        self.board = Some(board_id);
+        self
+    }
+
+    /// Adds a status to the set of statuses to match.
+    pub fn status(mut self, status: Status) -> Self {
+        self.statuses.push(status);
+        self
+    }
+
+    /// Matches tasks whose name contains `text` (case-insensitive).
+    pub fn name_contains(mut self, text: impl Into<String>) -> Self {
+        self.name = Some(text.into());
+        self
+    }
+
+    /// Matches tasks created within `[from, until)`.
+    pub fn created_between(mut self, from: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.created_from = Some(from);
+        self.created_until = Some(until);
+        self
+    }
+
+    /// Matches tasks last updated within `[from, until)`.
+    /// Tasks that were never updated do not match.
+    pub fn updated_between(mut self, from: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.updated_from = Some(from);
+        self.updated_until = Some(until);
+        self
+    }
+
+    /// Matches tasks due within `[from, until)`.
+    /// Tasks without a due date do not match.
+    pub fn due_between(mut self, from: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.due_from = Some(from);
+        self.due_until = Some(until);
+        self
+    }
+
+    /// Matches tasks due before `until`.
+    /// Tasks without a due date do not match.
+    pub fn due_before(mut self, until: DateTime<Utc>) -> Self {
+        self.due_until = Some(until);
+        self
+    }
+
+    /// Matches tasks carrying `label`; repeated calls require every label.
+    pub fn label(mut self, label: &str) -> Self {
+        self.all_labels.insert(label.trim().to_lowercase());
+        self
+    }
+
+    /// Matches tasks carrying at least one of the labels added this way.
+    pub fn any_label(mut self, label: &str) -> Self {
+        self.any_labels.insert(label.trim().to_lowercase());
+        self
+    }
+
+    /// Matches tasks assigned to the user with the given ID.
+    pub fn assigned_to(mut self, user_id: u32) -> Self {
+        self.assignee = Some(user_id);
+        self
+    }
+
+    /// Matches subtasks of the task with the given ID.
+    pub fn children_of(mut self, parent_id: u32) -> Self {
+        self.parent = Some(parent_id);
+        self
+    }
+
+    /// Matches archived tasks only.
+    pub fn archived(mut self) -> Self {
+        self.archive = ArchiveFilter::Archived;
+        self
+    }
+
+    /// Matches tasks whether they are archived or not.
+    pub fn including_archived(mut self) -> Self {
+        self.archive = ArchiveFilter::All;
+        self
+    }
+
+    /// Orders results by `key`, ascending unless [`TaskQuery::descending`] is set.
+    pub fn sort_by(mut self, key: SortKey) -> Self {
+        self.sort = key;
+        self
+    }
+
+    /// Sets the column order [`SortKey::Status`] follows, usually the
+    /// columns of the board's workflow.
+    pub fn column_order(mut self, columns: &[Status]) -> Self {
+        self.columns = columns.to_vec();
+        self
+    }
+
+    /// Reverses the sort order.
+    pub fn descending(mut self) -> Self {
+        self.descending = true;
+        self
+    }
+
+    /// Skips the first `offset` matching tasks.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns at most `limit` tasks.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns `true` if the task satisfies every criteria of the query.
+    /// Ordering and paging are not considered.
+    pub fn matches(&self, task: &Task) -> bool {
+        let board_matches = self.board.is_none_or(|board| task.board_id == board);
+        let status_matches = self.statuses.is_empty() || self.statuses.contains(&task.status);
+        let name_matches = self
+            .name
+            .as_ref()
+            .is_none_or(|name| task.name.to_lowercase().contains(&name.to_lowercase()));
+
+        let assignee_matches = self
+            .assignee
+            .is_none_or(|user| task.assignees.contains(&user));
+        let parent_matches = self.parent.is_none_or(|id| task.parent_id == Some(id));
+        let archive_matches = match self.archive {
+            ArchiveFilter::Active => task.archived_at.is_none(),
+            ArchiveFilter::Archived => task.archived_at.is_some(),
+            ArchiveFilter::All => true,
+        };
+        let labels_match = self.all_labels.is_subset(&task.labels)
+            && (self.any_labels.is_empty() || !self.any_labels.is_disjoint(&task.labels));
+
+        board_matches
+            && status_matches
+            && name_matches
+            && labels_match
+            && assignee_matches
+            && parent_matches
+            && archive_matches
+            && in_range(Some(task.created_at), self.created_from, self.created_until)
+            && in_range(task.updated_at, self.updated_from, self.updated_until)
+            && in_range(task.due_at, self.due_from, self.due_until)
+    }
+
+    /// Filters, orders and pages `tasks` according to the query.
+    pub fn apply(&self, tasks: impl IntoIterator<Item = Task>) -> Vec<Task> {
+        let mut matching: Vec<Task> = tasks.into_iter().filter(|t| self.matches(t)).collect();
+
+        matching.sort_by(|a, b| {
+            let ordering = self.compare(a, b);
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        matching
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        let ordering = match self.sort {
+            SortKey::Id => Ordering::Equal,
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::Status => self
+                .column_position(&a.status)
+                .cmp(&self.column_position(&b.status))
+                .then_with(|| a.status.as_str().cmp(b.status.as_str())),
+            SortKey::CreatedAt => a.created_at.cmp(&b.created_at),
+            SortKey::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            SortKey::Priority => a
+                .priority
+                .cmp(&b.priority)
+                .then(a.created_at.cmp(&b.created_at)),
+            SortKey::DueAt => match (a.due_at, b.due_at) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        };
+
+        // ties fall back to ID so results are stable
+        ordering.then(a.id.cmp(&b.id))
+    }
+
+    fn column_position(&self, status: &Status) -> usize {
+        self.columns
+            .iter()
+            .position(|column| column == status)
+            .unwrap_or(self.columns.len())
+    }
+}
+
+fn in_range(
+    ts: Option<DateTime<Utc>>,
+    from: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> bool {
+    if from.is_none() && until.is_none() {
+        return true;
+    }
+
+    ts.is_some_and(|ts| from.is_none_or(|from| ts >= from) && until.is_none_or(|until| ts < until))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Priority;
+    use chrono::{Duration, TimeZone};
+
+    fn tasks() -> Vec<Task> {
+        let base = Utc.with_ymd_and_hms(2025, 6, 1, 9, 0, 0).unwrap();
+
+        ["write docs", "fix login", "Review docs", "deploy"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut task = Task::new(name.to_string(), String::new());
+                task.id = Some(i as u32 + 1);
+                task.created_at = base + Duration::days(i as i64);
+                task
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_query_returns_everything_by_id() {
+        let ids: Vec<_> = TaskQuery::new()
+            .apply(tasks().into_iter().rev())
+            .iter()
+            .map(|t| t.id.unwrap())
+            .collect();
+
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn filters_by_board_status_set_and_name() {
+        let mut tasks = tasks();
+        tasks[1].status = Status::DOING;
+        tasks[2].status = Status::DONE;
+
+        let query = TaskQuery::new().status(Status::TODO).status(Status::DOING);
+        assert_eq!(query.apply(tasks.clone()).len(), 3);
+
+        let query = TaskQuery::new().status(Status::DONE).name_contains("DOCS");
+        let found = query.apply(tasks.clone());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "Review docs");
+
+        tasks[3].board_id = 2;
+        let found = TaskQuery::new().board(2).apply(tasks);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "deploy");
+    }
+
+    #[test]
+    fn filters_by_date_ranges() {
+        let mut tasks = tasks();
+        let day = |d| Utc.with_ymd_and_hms(2025, 6, d, 0, 0, 0).unwrap();
+        tasks[0].updated_at = Some(day(10));
+
+        let created = TaskQuery::new().created_between(day(2), day(4));
+        let names: Vec<_> = created
+            .apply(tasks.clone())
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["fix login", "Review docs"]);
+
+        let updated = TaskQuery::new().updated_between(day(9), day(11));
+        assert_eq!(updated.apply(tasks).len(), 1);
+    }
+
+    #[test]
+    fn sorts_and_pages() {
+        let query = TaskQuery::new()
+            .sort_by(SortKey::Name)
+            .descending()
+            .offset(1)
+            .limit(2);
+
+        let names: Vec<_> = query.apply(tasks()).into_iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["Review docs", "fix login"]);
+    }
+
+    #[test]
+    fn sorts_by_column_order() {
+        let mut tasks = tasks();
+        tasks[0].status = Status::DONE;
+        tasks[1].status = Status::DOING;
+        tasks[2].status = Status::new("Blocked");
+
+        let query = TaskQuery::new().sort_by(SortKey::Status).column_order(&[
+            Status::TODO,
+            Status::DOING,
+            Status::DONE,
+        ]);
+        let names: Vec<_> = query.apply(tasks).into_iter().map(|t| t.name).collect();
+        assert_eq!(
+            names,
+            vec!["deploy", "fix login", "write docs", "Review docs"]
+        );
+    }
+
+    #[test]
+    fn sorts_by_priority_then_age() {
+        let mut tasks = tasks();
+        tasks[1].priority = Priority::Low;
+        tasks[2].priority = Priority::Critical;
+        tasks[3].priority = Priority::Critical;
+
+        let names: Vec<_> = TaskQuery::new()
+            .sort_by(SortKey::Priority)
+            .apply(tasks)
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec!["Review docs", "deploy", "write docs", "fix login"]
+        );
+    }
+
+    #[test]
+    fn filters_and_sorts_by_due_date() {
+        let mut tasks = tasks();
+        let day = |d| Utc.with_ymd_and_hms(2025, 6, d, 0, 0, 0).unwrap();
+        tasks[0].due_at = Some(day(20));
+        tasks[2].due_at = Some(day(10));
+        tasks[3].due_at = Some(day(15));
+
+        let names: Vec<_> = TaskQuery::new()
+            .due_before(day(16))
+            .sort_by(SortKey::DueAt)
+            .apply(tasks.clone())
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Review docs", "deploy"]);
+
+        assert_eq!(
+            TaskQuery::new()
+                .due_between(day(12), day(21))
+                .apply(tasks.clone())
+                .len(),
+            2
+        );
+
+        let last = TaskQuery::new().sort_by(SortKey::DueAt).apply(tasks);
+        assert_eq!(last[3].name, "fix login");
+    }
+
+    #[test]
+    fn filters_by_all_or_any_labels() {
+        let mut tasks = tasks();
+        let label = |task: &mut Task, labels: &[&str]| {
+            task.labels = labels.iter().map(|l| l.to_string()).collect();
+        };
+        label(&mut tasks[0], &["docs"]);
+        label(&mut tasks[1], &["bug", "frontend"]);
+        label(&mut tasks[2], &["docs", "frontend"]);
+
+        let names = |query: TaskQuery| -> Vec<String> {
+            query
+                .apply(tasks.clone())
+                .into_iter()
+                .map(|t| t.name)
+                .collect()
+        };
+        assert_eq!(
+            names(TaskQuery::new().label("Frontend")),
+            vec!["fix login", "Review docs"]
+        );
+        assert_eq!(
+            names(TaskQuery::new().label("frontend").label("docs")),
+            vec!["Review docs"]
+        );
+        assert_eq!(
+            names(TaskQuery::new().any_label("bug").any_label("docs")),
+            vec!["write docs", "fix login", "Review docs"]
+        );
+        assert!(names(TaskQuery::new().label("ops")).is_empty());
+    }
+
+    #[test]
+    fn archived_tasks_are_hidden_unless_asked_for() {
+        let mut tasks = tasks();
+        tasks[1].archived_at = Some(Utc::now());
+
+        let ids = |query: TaskQuery| -> Vec<u32> {
+            query
+                .apply(tasks.clone())
+                .iter()
+                .map(|t| t.id.unwrap())
+                .collect()
+        };
+        assert_eq!(ids(TaskQuery::new()), vec![1, 3, 4]);
+        assert_eq!(ids(TaskQuery::new().archived()), vec![2]);
+        assert_eq!(ids(TaskQuery::new().including_archived()), vec![1, 2, 3, 4]);
+    }
+}
@@ -1 +1,228 @@
+use crate::Task;
+use crate::board::Board;
+use crate::comment::Comment;
+use crate::error::Result;
+use crate::history::TaskEvent;
+use crate::query::TaskQuery;
+use crate::user::User;
 
+/// Storage abstraction for boards and their tasks.
+///
+/// Implementations only persist and retrieve tasks; business rules such as
+/// validation, name uniqueness and status transitions live in the service.
+/// All methods hand out owned values so that backends are free to keep
+/// tasks wherever they like (memory, files, databases...). Reads are
+/// fallible too: a backend that cannot read or decode what it stored
+/// reports `KanbanError::Storage` instead of pretending it is missing.
+/// Writes that belong together, such as a change and the history event
+/// recording it, are grouped with [`begin`](TaskRepository::begin) and
+/// [`commit`](TaskRepository::commit).
+///
+/// Every repository holds the default board (see
+/// [`DEFAULT_BOARD_ID`](crate::board::DEFAULT_BOARD_ID)) from the moment it
+/// is created.
+pub trait TaskRepository {
+    /// Fetches a task by its unique ID.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Task))` - The task, if found.
+    /// * `Ok(None)` - If no task with that ID exists.
+    /// * `Err(KanbanError::Storage)` - If the task could not be read.
+    fn get(&self, id: u32) -> Result<Option<Task>>;
+
+    /// Fetches a task on the given board by its name (case-insensitive).
+    fn find_by_name(&self, board_id: u32, name: &str) -> Result<Option<Task>>;
+
+    /// Stores a new task, assigning it a fresh ID.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The stored task including its ID.
+    /// * `Err(KanbanError)` - If the task could not be stored.
+    fn insert(&mut self, task: Task) -> Result<Task>;
+
+    /// Replaces an existing task with the given one.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The stored task.
+    /// * `Err(KanbanError::NotFound)` - If no task with the same ID exists.
+    fn update(&mut self, task: Task) -> Result<Task>;
+
+    /// Removes a task, along with its history and comments, from storage.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The removed task.
+    /// * `Err(KanbanError::NotFound)` - If no task with the given ID exists.
+    fn delete(&mut self, id: u32) -> Result<Task>;
+
+    /// Lists the tasks matching the query, in the order and page it asks for.
+    fn list(&self, query: &TaskQuery) -> Result<Vec<Task>>;
+
+    /// Appends an event to the history of the task it refers to.
+    fn append_event(&mut self, event: TaskEvent) -> Result<()>;
+
+    /// Returns the history of a task, oldest event first.
+    fn history(&self, task_id: u32) -> Result<Vec<TaskEvent>>;
+
+    /// Fetches a board by its unique ID.
+    fn get_board(&self, id: u32) -> Result<Option<Board>>;
+
+    /// Lists every board, ordered by ID.
+    fn list_boards(&self) -> Result<Vec<Board>>;
+
+    /// Stores a new board, assigning it a fresh ID.
+    ///
+    /// # Returns
+    /// * `Ok(Board)` - The stored board including its ID.
+    /// * `Err(KanbanError)` - If the board could not be stored.
+    fn insert_board(&mut self, board: Board) -> Result<Board>;
+
+    /// Replaces an existing board with the given one.
+    ///
+    /// # Returns
+    /// * `Ok(Board)` - The stored board.
+    /// * `Err(KanbanError::BoardNotFound)` - If no board with the same ID exists.
+    fn update_board(&mut self, board: Board) -> Result<Board>;
+
+    /// Removes a board from storage. Its tasks are left untouched.
+    ///
+    /// # Returns
+    /// * `Ok(Board)` - The removed board.
+    /// * `Err(KanbanError::BoardNotFound)` - If no board with the given ID exists.
+    fn delete_board(&mut self, id: u32) -> Result<Board>;
+
+    /// Fetches a user by their unique ID.
+    fn get_user(&self, id: u32) -> Result<Option<User>>;
+
+    /// Lists every user, ordered by ID.
+    fn list_users(&self) -> Result<Vec<User>>;
+
+    /// Stores a new user, assigning them a fresh ID.
+    ///
+    /// # Returns
+    /// * `Ok(User)` - The stored user including their ID.
+    /// * `Err(KanbanError)` - If the user could not be stored.
+    fn insert_user(&mut self, user: User) -> Result<User>;
+
+    /// Replaces an existing user with the given one.
+    ///
+    /// # Returns
+    /// * `Ok(User)` - The stored user.
+    /// * `Err(KanbanError::UserNotFound)` - If no user with the same ID exists.
+    fn update_user(&mut self, user: User) -> Result<User>;
+
+    /// Fetches a comment by its unique ID.
+    fn get_comment(&self, id: u32) -> Result<Option<Comment>>;
+
+    /// Lists the comments on a task, oldest first.
+    fn list_comments(&self, task_id: u32) -> Result<Vec<Comment>>;
+
+    /// Stores a new comment, assigning it a fresh ID.
+    ///
+    /// # Returns
+    /// * `Ok(Comment)` - The stored comment including its ID.
+    /// * `Err(KanbanError)` - If the comment could not be stored.
+    fn insert_comment(&mut self, comment: Comment) -> Result<Comment>;
+
+    /// Replaces an existing comment with the given one.
+    ///
+    /// # Returns
+    /// * `Ok(Comment)` - The stored comment.
+    /// * `Err(KanbanError::CommentNotFound)` - If no comment with the same ID exists.
+    fn update_comment(&mut self, comment: Comment) -> Result<Comment>;
+
+    /// Removes a comment from storage.
+    ///
+    /// # Returns
+    /// * `Ok(Comment)` - The removed comment.
+    /// * `Err(KanbanError::CommentNotFound)` - If no comment with the given ID exists.
+    fn delete_comment(&mut self, id: u32) -> Result<Comment>;
+
+    /// Starts a unit of work. The writes made until [`commit`](Self::commit)
+    /// or [`rollback`](Self::rollback) are stored together or not at all.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the unit of work started.
+    /// * `Err(KanbanError::Storage)` - If one is already in progress or it
+    ///   could not be started.
+    fn begin(&mut self) -> Result<()>;
+
+    /// Stores every write made since [`begin`](Self::begin) at once.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the writes were stored.
+    /// * `Err(KanbanError::Storage)` - If no unit of work is in progress or
+    ///   the writes could not be stored; none of them are kept then.
+    fn commit(&mut self) -> Result<()>;
+
+    /// Discards every write made since [`begin`](Self::begin).
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the writes were discarded.
+    /// * `Err(KanbanError::Storage)` - If no unit of work is in progress or
+    ///   it could not be undone.
+    fn rollback(&mut self) -> Result<()>;
+}
+
+/// Checks every backend is expected to pass, run from each backend's tests.
+#[cfg(test)]
+pub(crate) mod contract_tests {
+    use super::*;
+    use crate::board::DEFAULT_BOARD_ID;
+    use crate::error::KanbanError;
+    use crate::history::EventKind;
+
+    /// Task names match ignoring case for any letter, not only ASCII ones.
+    pub(crate) fn names_match_ignoring_unicode_case(repo: &mut impl TaskRepository) {
+        let task = repo
+            .insert(Task::new("Ärger im Büro".to_string(), String::new()))
+            .unwrap();
+
+        for name in ["ärger im büro", "ÄRGER IM BÜRO"] {
+            let found = repo.find_by_name(DEFAULT_BOARD_ID, name).unwrap();
+            assert_eq!(found.map(|t| t.id), Some(task.id));
+        }
+
+        let matching: Vec<_> = repo
+            .list(&TaskQuery::new().name_contains("ÜRO"))
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(matching, vec![task.id]);
+    }
+
+    /// Writes of a unit of work are visible right away, kept by a commit
+    /// and discarded by a rollback.
+    pub(crate) fn units_of_work_commit_or_roll_back(repo: &mut impl TaskRepository) {
+        let before = repo.list(&TaskQuery::new()).unwrap().len();
+
+        repo.begin().unwrap();
+        let task = repo
+            .insert(Task::new("unit".to_string(), String::new()))
+            .unwrap();
+        let id = task.id.unwrap();
+        repo.append_event(TaskEvent::new(id, EventKind::Created, None, None, "alice"))
+            .unwrap();
+        assert!(repo.get(id).unwrap().is_some());
+        assert!(matches!(repo.begin(), Err(KanbanError::Storage { .. })));
+        repo.rollback().unwrap();
+
+        assert!(repo.get(id).unwrap().is_none());
+        assert!(repo.history(id).unwrap().is_empty());
+        assert_eq!(repo.list(&TaskQuery::new()).unwrap().len(), before);
+
+        repo.begin().unwrap();
+        let task = repo
+            .insert(Task::new("unit".to_string(), String::new()))
+            .unwrap();
+        let id = task.id.unwrap();
+        repo.append_event(TaskEvent::new(id, EventKind::Created, None, None, "alice"))
+            .unwrap();
+        repo.commit().unwrap();
+
+        assert!(repo.get(id).unwrap().is_some());
+        assert_eq!(repo.history(id).unwrap().len(), 1);
+        assert!(matches!(repo.commit(), Err(KanbanError::Storage { .. })));
+        assert!(matches!(repo.rollback(), Err(KanbanError::Storage { .. })));
+    }
+}
@@ -1,203 +1,3121 @@
-use chrono::Utc;
+use std::collections::BTreeMap;
 
-use crate::domain;
-use crate::{
-    domain::{Status, Task},
-    inmemory_repository::InMemoryTaskRepo,
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::analytics::{FlowStats, TaskTimeline};
+use crate::board::{Board, DEFAULT_BOARD_ID};
+use crate::cfd::CumulativeFlow;
+use crate::comment::Comment;
+use crate::dependency::DependencyGraph;
+use crate::domain::{
+    BlockedFlag, ChecklistItem, Priority, Progress, Status, Task, normalize_label,
 };
+use crate::error::{KanbanError, Result};
+use crate::forecast::{CompletionForecast, ForecastOptions, ItemsForecast, MonteCarlo, Throughput};
+use crate::history::{EventKind, TaskEvent};
+use crate::patch::TaskPatch;
+use crate::query::{SortKey, TaskQuery};
+use crate::repository::TaskRepository;
+use crate::user::{User, Workload};
+use crate::workflow::Workflow;
+
+/// Actor recorded in task history when none has been set.
+pub const DEFAULT_ACTOR: &str = "system";
+
+/// Business rules for boards and their tasks.
+///
+/// The service works on one board at a time: new tasks are added to it and
+/// listings and flow metrics only cover its tasks. It starts on the default
+/// board; see [`TaskService::use_board`].
+pub struct TaskService<R: TaskRepository> {
+    repo: R,
+    board: Board,
+    actor: String,
+    /// set while the repository has a unit of work in progress
+    in_unit_of_work: bool,
+}
+
+impl<R: TaskRepository> TaskService<R> {
+    /// Creates a service working on the repository's default board.
+    ///
+    /// # Returns
+    /// * `Ok(TaskService)` - The service working on the default board.
+    /// * `Err(KanbanError::Storage)` - If the default board could not be
+    ///   read.
+    pub fn new(repo: R) -> Result<Self> {
+        let board = repo.get_board(DEFAULT_BOARD_ID)?.unwrap_or_default();
+        Ok(Self {
+            repo,
+            board,
+            actor: DEFAULT_ACTOR.to_string(),
+            in_unit_of_work: false,
+        })
+    }
+
+    /// Creates a service whose default board uses `workflow`.
+    ///
+    /// # Returns
+    /// * `Ok(TaskService)` - The service working on the default board.
+    /// * `Err(KanbanError)` - If tasks on the board sit in columns the
+    ///   workflow does not have, or the board could not be stored.
+    pub fn with_workflow(repo: R, workflow: Workflow) -> Result<Self> {
+        let mut svc = Self::new(repo)?;
+        svc.set_board_workflow(DEFAULT_BOARD_ID, workflow)?;
+        Ok(svc)
+    }
+
+    /// Board the service currently works on.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Workflow of the current board.
+    pub fn workflow(&self) -> &Workflow {
+        &self.board.workflow
+    }
+
+    /// Sets who subsequent changes are attributed to in task history.
+    pub fn set_actor(&mut self, actor: &str) {
+        self.actor = actor.to_string();
+    }
+
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// Lists every board in the workspace.
+    pub fn boards(&self) -> Result<Vec<Board>> {
+        self.repo.list_boards()
+    }
+
+    /// Finds a board by name (case-insensitive).
+    pub fn find_board(&self, name: &str) -> Result<Option<Board>> {
+        Ok(self
+            .repo
+            .list_boards()?
+            .into_iter()
+            .find(|b| b.name.to_lowercase() == name.to_lowercase()))
+    }
+
+    /// Creates a new board.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the board, unique within the workspace.
+    /// * `workflow` - Columns and transitions tasks on the board follow.
+    ///
+    /// # Returns
+    /// * `Ok(Board)` - The created board.
+    /// * `Err(KanbanError)` - If the name is empty or already taken.
+    pub fn create_board(&mut self, name: &str, workflow: Workflow) -> Result<Board> {
+        let board = Board::new(name.trim().to_string(), workflow);
+        board.before_save()?;
+        self.ensure_board_name_free(&board.name, None)?;
+
+        self.repo.insert_board(board)
+    }
+
+    /// Switches the service to another board.
+    ///
+    /// # Returns
+    /// * `Ok(&Board)` - The board now in use.
+    /// * `Err(KanbanError::BoardNotFound)` - If the board does not exist.
+    pub fn use_board(&mut self, id: u32) -> Result<&Board> {
+        self.board = self.get_board(id)?;
+        Ok(&self.board)
+    }
+
+    /// Renames a board.
+    ///
+    /// # Returns
+    /// * `Ok(Board)` - The renamed board.
+    /// * `Err(KanbanError)` - If the board does not exist or the name is
+    ///   empty or already taken.
+    pub fn rename_board(&mut self, id: u32, name: &str) -> Result<Board> {
+        let mut board = self.get_board(id)?;
+        board.name = name.trim().to_string();
+        board.before_save()?;
+        self.ensure_board_name_free(&board.name, Some(id))?;
+
+        self.save_board(board)
+    }
+
+    /// Replaces the workflow of a board.
+    ///
+    /// # Returns
+    /// * `Ok(Board)` - The updated board.
+    /// * `Err(KanbanError)` - If the board does not exist or some of its
+    ///   tasks sit in a column the new workflow does not have.
+    pub fn set_board_workflow(&mut self, id: u32, workflow: Workflow) -> Result<Board> {
+        let mut board = self.get_board(id)?;
+
+        let tasks = self
+            .repo
+            .list(&TaskQuery::new().board(id).including_archived())?;
+        if let Some(task) = tasks
+            .iter()
+            .find(|t| workflow.position(t.status.as_str()).is_none())
+        {
+            return Err(KanbanError::Validation {
+                field: "workflow",
+                reason: format!(
+                    "task {} is in column '{}' which the workflow does not have",
+                    task.id.unwrap_or_default(),
+                    task.status
+                ),
+            });
+        }
+
+        board.workflow = workflow;
+        self.save_board(board)
+    }
+
+    /// Deletes an empty board. The default board cannot be deleted; if the
+    /// deleted board was in use the service switches back to the default one.
+    ///
+    /// # Returns
+    /// * `Ok(Board)` - The deleted board.
+    /// * `Err(KanbanError)` - If the board does not exist, is the default
+    ///   board or still holds tasks.
+    pub fn delete_board(&mut self, id: u32) -> Result<Board> {
+        let board = self.get_board(id)?;
+        if id == DEFAULT_BOARD_ID {
+            return Err(KanbanError::Validation {
+                field: "board",
+                reason: String::from("the default board cannot be deleted"),
+            });
+        }
+        if !self
+            .repo
+            .list(&TaskQuery::new().board(id).including_archived())?
+            .is_empty()
+        {
+            return Err(KanbanError::Validation {
+                field: "board",
+                reason: format!("board '{}' still has tasks", board.name),
+            });
+        }
+
+        let board = self.repo.delete_board(id)?;
+        if self.board.id == Some(id) {
+            self.use_board(DEFAULT_BOARD_ID)?;
+        }
+
+        Ok(board)
+    }
+
+    /// Lists every user in the workspace.
+    pub fn users(&self) -> Result<Vec<User>> {
+        self.repo.list_users()
+    }
+
+    /// Finds a user by name (case-insensitive).
+    pub fn find_user(&self, name: &str) -> Result<Option<User>> {
+        Ok(self
+            .repo
+            .list_users()?
+            .into_iter()
+            .find(|u| u.name.to_lowercase() == name.to_lowercase()))
+    }
+
+    /// Adds a user tasks can be assigned to.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the user, unique within the workspace.
+    /// * `wip_limit` - Maximum number of tasks the user should have in
+    ///   progress at once, if any.
+    ///
+    /// # Returns
+    /// * `Ok(User)` - The created user.
+    /// * `Err(KanbanError)` - If the name is empty or already taken, or the
+    ///   limit is zero.
+    pub fn add_user(&mut self, name: &str, wip_limit: Option<usize>) -> Result<User> {
+        let mut user = User::new(name.trim().to_string());
+        user.wip_limit = wip_limit;
+        user.before_save()?;
+        if let Some(u) = self.find_user(&user.name)? {
+            return Err(KanbanError::DuplicateUserName { name: u.name });
+        }
+
+        self.repo.insert_user(user)
+    }
+
+    /// Sets or clears a user's personal WIP limit.
+    ///
+    /// # Returns
+    /// * `Ok(User)` - The updated user.
+    /// * `Err(KanbanError)` - If the user does not exist or the limit is zero.
+    pub fn set_user_wip_limit(&mut self, id: u32, wip_limit: Option<usize>) -> Result<User> {
+        let mut user = self.get_user(id)?;
+        user.wip_limit = wip_limit;
+        user.before_save()?;
+
+        self.repo.update_user(user)
+    }
+
+    /// Assigns a user to a task; a task may have several assignees.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If the task or user does not exist.
+    pub fn assign(&mut self, task_id: u32, user_id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let user = svc.get_user(user_id)?;
+            let mut task = svc.get_task(task_id)?;
+            if !task.assignees.insert(user_id) {
+                return Ok(task);
+            }
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(&task, EventKind::Assigned, None, Some(user.name))?;
+
+            Ok(task)
+        })
+    }
+
+    /// Removes a user from a task's assignees.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If the task or user does not exist.
+    pub fn unassign(&mut self, task_id: u32, user_id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let user = svc.get_user(user_id)?;
+            let mut task = svc.get_task(task_id)?;
+            if !task.assignees.remove(&user_id) {
+                return Ok(task);
+            }
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(&task, EventKind::Unassigned, Some(user.name), None)?;
+
+            Ok(task)
+        })
+    }
+
+    /// Shows how many tasks every user has in progress, on any board,
+    /// against their personal WIP limit.
+    pub fn workload(&self) -> Result<Vec<Workload>> {
+        let boards = self.repo.list_boards()?;
+        self.repo
+            .list_users()?
+            .into_iter()
+            .map(|user| {
+                let query = TaskQuery::new().assigned_to(user.id.unwrap_or_default());
+                let in_progress = self
+                    .repo
+                    .list(&query)?
+                    .iter()
+                    .filter(|task| {
+                        boards.iter().any(|b| {
+                            b.id == Some(task.board_id)
+                                && b.workflow.is_started(task.status.as_str())
+                                && !b.workflow.is_done(task.status.as_str())
+                        })
+                    })
+                    .count();
+
+                Ok(Workload { user, in_progress })
+            })
+            .collect()
+    }
+
+    /// Adds a medium priority task to the current board.
+    pub fn add_task(&mut self, name: &str, desc: &str) -> Result<Task> {
+        self.add_task_with_priority(name, desc, Priority::default())
+    }
+
+    /// Adds a task with the given priority to the current board.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The created task in the workflow's first column.
+    /// * `Err(KanbanError)` - If the name is empty or already used on the
+    ///   board.
+    pub fn add_task_with_priority(
+        &mut self,
+        name: &str,
+        desc: &str,
+        priority: Priority,
+    ) -> Result<Task> {
+        let mut task = Task::new(name.to_string(), desc.to_string());
+        task.priority = priority;
+
+        let board = self.board.clone();
+        self.insert_task(task, &board)
+    }
+
+    /// Adds a subtask to a task. The subtask is placed on its parent's board
+    /// and counts towards the parent's progress.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The created subtask.
+    /// * `Err(KanbanError)` - If the parent does not exist or the name is
+    ///   empty or already used on the parent's board.
+    pub fn add_subtask(&mut self, parent_id: u32, name: &str, desc: &str) -> Result<Task> {
+        self.atomically(|svc| {
+            let parent = svc.get_task(parent_id)?;
+            let board = svc.get_board(parent.board_id)?;
+
+            let mut task = Task::new(name.to_string(), desc.to_string());
+            task.priority = parent.priority;
+            task.parent_id = Some(parent_id);
+
+            let task = svc.insert_task(task, &board)?;
+            svc.record(
+                &task,
+                EventKind::ParentChanged,
+                None,
+                Some(parent_id.to_string()),
+            )?;
+
+            Ok(task)
+        })
+    }
+
+    /// Makes a task a subtask of another, or detaches it from its parent.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If either task does not exist or the new
+    ///   parent is the task itself or one of its subtasks.
+    pub fn set_parent(&mut self, id: u32, parent_id: Option<u32>) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(id)?;
+            if let Some(parent_id) = parent_id {
+                // walk up from the new parent; meeting the task means a cycle
+                let mut ancestor = Some(svc.get_task(parent_id)?);
+                while let Some(current) = ancestor {
+                    if current.id == Some(id) {
+                        return Err(KanbanError::Validation {
+                            field: "parent",
+                            reason: format!("task {} cannot be its own ancestor", id),
+                        });
+                    }
+                    ancestor = match current.parent_id {
+                        Some(p) => svc.repo.get(p)?,
+                        None => None,
+                    };
+                }
+            }
+            if task.parent_id == parent_id {
+                return Ok(task);
+            }
+
+            let old = std::mem::replace(&mut task.parent_id, parent_id);
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(
+                &task,
+                EventKind::ParentChanged,
+                old.map(|p| p.to_string()),
+                parent_id.map(|p| p.to_string()),
+            )?;
+
+            Ok(task)
+        })
+    }
+
+    /// Lists the direct subtasks of a task, on any board, in ID order.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Task>)` - The subtasks.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn subtasks(&self, id: u32) -> Result<Vec<Task>> {
+        self.get_task(id)?;
+        self.repo.list(&TaskQuery::new().children_of(id))
+    }
+
+    /// Records that task `blocker_id` blocks task `blocked_id`: work on the
+    /// blocked task cannot start until the blocker is done.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The blocked task.
+    /// * `Err(KanbanError)` - If either task does not exist or the link
+    ///   would make a task (indirectly) block itself.
+    pub fn add_dependency(&mut self, blocker_id: u32, blocked_id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(blocked_id)?;
+            svc.get_task(blocker_id)?;
+            if let Some(path) = svc.blocking_path(blocked_id, blocker_id)? {
+                let path: Vec<String> = path.iter().map(u32::to_string).collect();
+                return Err(KanbanError::Validation {
+                    field: "dependency",
+                    reason: format!(
+                        "task {} already blocks task {} ({})",
+                        blocked_id,
+                        blocker_id,
+                        path.join(" -> ")
+                    ),
+                });
+            }
+            if !task.blocked_by.insert(blocker_id) {
+                return Ok(task);
+            }
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(
+                &task,
+                EventKind::DependencyAdded,
+                None,
+                Some(blocker_id.to_string()),
+            )?;
+
+            Ok(task)
+        })
+    }
+
+    /// Removes the link between a blocker and the task it blocks.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The previously blocked task.
+    /// * `Err(KanbanError::NotFound)` - If the blocked task does not exist.
+    pub fn remove_dependency(&mut self, blocker_id: u32, blocked_id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(blocked_id)?;
+            if !task.blocked_by.remove(&blocker_id) {
+                return Ok(task);
+            }
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(
+                &task,
+                EventKind::DependencyRemoved,
+                Some(blocker_id.to_string()),
+                None,
+            )?;
+
+            Ok(task)
+        })
+    }
+
+    /// Lists the tasks blocking a task, finished or not, in ID order.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Task>)` - The blockers.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn blockers(&self, id: u32) -> Result<Vec<Task>> {
+        let task = self.get_task(id)?;
+        task.blocked_by
+            .iter()
+            .filter_map(|blocker| self.repo.get(*blocker).transpose())
+            .collect()
+    }
+
+    /// Builds the dependency graph of the current board. Blockers on other
+    /// boards are included so every link of the board's tasks is shown.
+    pub fn dependency_graph(&self) -> Result<DependencyGraph> {
+        let mut tasks = self.list(&TaskQuery::new())?;
+        let external = tasks
+            .iter()
+            .flat_map(|task| task.blocked_by.iter())
+            .filter(|blocker| !tasks.iter().any(|t| t.id == Some(**blocker)))
+            .filter_map(|blocker| self.repo.get(*blocker).transpose())
+            .collect::<Result<Vec<_>>>()?;
+        tasks.extend(external);
+
+        let boards = self.repo.list_boards()?;
+        Ok(DependencyGraph::build(&tasks, |task| {
+            is_done_on(&boards, task)
+        }))
+    }
+
+    /// Adds an item to the end of a task's checklist.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If the task does not exist or the text is empty.
+    pub fn add_checklist_item(&mut self, id: u32, text: &str) -> Result<Task> {
+        let mut task = self.get_task(id)?;
+        task.before_add_item(text)?;
+
+        let item = ChecklistItem::new(text.trim().to_string());
+        let new = item.to_string();
+        task.checklist.push(item);
+
+        self.save_checklist(task, None, Some(new))
+    }
+
+    /// Marks the checklist item at zero-based `index` as done or open.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If the task or item does not exist.
+    pub fn set_checklist_item_done(&mut self, id: u32, index: usize, done: bool) -> Result<Task> {
+        let mut task = self.get_task(id)?;
+        let item = task.checklist_item(index)?;
+        if item.done == done {
+            return Ok(task);
+        }
+
+        let old = item.to_string();
+        item.done = done;
+        let new = item.to_string();
+
+        self.save_checklist(task, Some(old), Some(new))
+    }
+
+    /// Removes the checklist item at zero-based `index`.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If the task or item does not exist.
+    pub fn remove_checklist_item(&mut self, id: u32, index: usize) -> Result<Task> {
+        let mut task = self.get_task(id)?;
+        let old = task.checklist_item(index)?.to_string();
+        task.checklist.remove(index);
+
+        self.save_checklist(task, Some(old), None)
+    }
+
+    /// Rolls up how much of a task's checklist and direct subtasks is
+    /// complete. A subtask counts as complete once it is in its board's
+    /// done column.
+    ///
+    /// # Returns
+    /// * `Ok(Progress)` - The progress of the task.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn progress(&self, id: u32) -> Result<Progress> {
+        let task = self.get_task(id)?;
+        self.progress_of(&task)
+    }
+
+    /// Lists the archived tasks of the current board, most recently archived
+    /// first.
+    pub fn archived_tasks(&self) -> Result<Vec<Task>> {
+        let mut tasks = self.list(&TaskQuery::new().archived())?;
+        tasks.sort_by(|a, b| b.archived_at.cmp(&a.archived_at).then(a.id.cmp(&b.id)));
+        Ok(tasks)
+    }
+
+    /// Archives a task: it keeps its column, history and comments but no
+    /// longer shows on the board and cannot move until it is restored.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The archived task.
+    /// * `Err(KanbanError)` - If the task does not exist or is already
+    ///   archived.
+    pub fn archive_task(&mut self, id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(id)?;
+            if task.is_archived() {
+                return Err(KanbanError::Validation {
+                    field: "task",
+                    reason: format!("task {} is already archived", id),
+                });
+            }
+
+            let now = Utc::now();
+            task.archived_at = Some(now);
+            task.updated_at = Some(now);
+
+            let task = svc.repo.update(task)?;
+            svc.record(&task, EventKind::Archived, None, None)?;
+
+            Ok(task)
+        })
+    }
+
+    /// Brings an archived task back onto its board, in the column it was
+    /// archived from.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The restored task.
+    /// * `Err(KanbanError)` - If the task does not exist or is not archived.
+    pub fn restore_task(&mut self, id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(id)?;
+            if !task.is_archived() {
+                return Err(KanbanError::Validation {
+                    field: "task",
+                    reason: format!("task {} is not archived", id),
+                });
+            }
+
+            task.archived_at = None;
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(&task, EventKind::Restored, None, None)?;
+
+            Ok(task)
+        })
+    }
+
+    /// Deletes a task for good, along with its history and comments. Its
+    /// subtasks are detached and tasks it blocked no longer wait on it.
+    /// The task's ID is never handed out again.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The deleted task.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn delete_task(&mut self, id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let task = svc.get_task(id)?;
+
+            for mut other in svc.repo.list(&TaskQuery::new().including_archived())? {
+                let detach = other.parent_id == Some(id);
+                let unblock = other.blocked_by.remove(&id);
+                if !detach && !unblock {
+                    continue;
+                }
+                if detach {
+                    other.parent_id = None;
+                }
+                other.updated_at = Some(Utc::now());
+
+                let other = svc.repo.update(other)?;
+                if detach {
+                    svc.record(&other, EventKind::ParentChanged, Some(id.to_string()), None)?;
+                }
+                if unblock {
+                    svc.record(
+                        &other,
+                        EventKind::DependencyRemoved,
+                        Some(id.to_string()),
+                        None,
+                    )?;
+                }
+            }
+
+            svc.repo.delete(task.id.unwrap_or(id))
+        })
+    }
+
+    /// Lists the comments on a task, oldest first.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Comment>)` - The task's comments.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn comments(&self, task_id: u32) -> Result<Vec<Comment>> {
+        self.get_task(task_id)?;
+        self.repo.list_comments(task_id)
+    }
+
+    /// Adds a markdown comment to a task, written by the current actor.
+    ///
+    /// # Arguments
+    /// * `task_id` - The task to comment on.
+    /// * `body` - The comment text, in markdown.
+    ///
+    /// # Returns
+    /// * `Ok(Comment)` - The stored comment.
+    /// * `Err(KanbanError)` - If the task does not exist or the body is empty.
+    pub fn add_comment(&mut self, task_id: u32, body: &str) -> Result<Comment> {
+        self.atomically(|svc| {
+            let task = svc.get_task(task_id)?;
+            let comment = Comment::new(task_id, svc.actor.clone(), body.trim().to_string());
+            comment.before_save()?;
+
+            let comment = svc.repo.insert_comment(comment)?;
+            svc.record(
+                &task,
+                EventKind::Commented,
+                None,
+                Some(comment.body.clone()),
+            )?;
+
+            Ok(comment)
+        })
+    }
+
+    /// Replaces the text of a comment. The previous text is kept as a
+    /// revision of the comment and the edit is attributed to the current
+    /// actor.
+    ///
+    /// # Returns
+    /// * `Ok(Comment)` - The updated comment.
+    /// * `Err(KanbanError)` - If the comment does not exist or the body is
+    ///   empty.
+    pub fn edit_comment(&mut self, id: u32, body: &str) -> Result<Comment> {
+        self.atomically(|svc| {
+            let mut comment = svc.get_comment(id)?;
+            let body = body.trim();
+            if comment.body == body {
+                return Ok(comment);
+            }
+
+            let old = comment.body.clone();
+            comment.edit(body.to_string(), &svc.actor);
+            comment.before_save()?;
+
+            let comment = svc.repo.update_comment(comment)?;
+            if let Some(task) = svc.repo.get(comment.task_id)? {
+                svc.record(
+                    &task,
+                    EventKind::CommentEdited,
+                    Some(old),
+                    Some(comment.body.clone()),
+                )?;
+            }
+
+            Ok(comment)
+        })
+    }
+
+    /// Deletes a comment. Its last text stays in the task's history.
+    ///
+    /// # Returns
+    /// * `Ok(Comment)` - The deleted comment.
+    /// * `Err(KanbanError::CommentNotFound)` - If the comment does not exist.
+    pub fn delete_comment(&mut self, id: u32) -> Result<Comment> {
+        self.atomically(|svc| {
+            let comment = svc.repo.delete_comment(id)?;
+            if let Some(task) = svc.repo.get(comment.task_id)? {
+                svc.record(
+                    &task,
+                    EventKind::CommentDeleted,
+                    Some(comment.body.clone()),
+                    None,
+                )?;
+            }
+
+            Ok(comment)
+        })
+    }
+
+    /// Applies a partial update to a task. The changed task is validated as
+    /// when it was added, its name must stay unique on its board and every
+    /// changed field is recorded in its history.
+    ///
+    /// # Arguments
+    /// * `id` - The task to change.
+    /// * `patch` - The fields to change; the others keep their value.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task, unchanged if the patch changes
+    ///   nothing.
+    /// * `Err(KanbanError)` - If the task does not exist, the new name is
+    ///   empty or already used on the board, or the due date is before the
+    ///   task was created.
+    pub fn update_task(&mut self, id: u32, patch: TaskPatch) -> Result<Task> {
+        self.atomically(|svc| {
+            let current = svc.get_task(id)?;
+            let mut task = current.clone();
+            if let Some(name) = &patch.name {
+                task.name = name.trim().to_string();
+            }
+            if let Some(description) = patch.description {
+                task.description = description;
+            }
+            if let Some(priority) = patch.priority {
+                task.priority = priority;
+            }
+            if let Some(due_at) = patch.due_at {
+                task.due_at = due_at;
+            }
+            if task.name == current.name
+                && task.description == current.description
+                && task.priority == current.priority
+                && task.due_at == current.due_at
+            {
+                return Ok(current);
+            }
+
+            // perform validations
+            task.before_add()?;
+            if let Some(due_at) = &task.due_at
+                && task.due_at != current.due_at
+            {
+                task.before_schedule(due_at)?;
+            }
+            if let Some(t) = svc.repo.find_by_name(task.board_id, &task.name)?
+                && t.id != task.id
+            {
+                return Err(KanbanError::DuplicateName { name: t.name });
+            }
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            if task.name != current.name {
+                svc.record(
+                    &task,
+                    EventKind::Renamed,
+                    Some(current.name.clone()),
+                    Some(task.name.clone()),
+                )?;
+            }
+            if task.description != current.description {
+                svc.record(
+                    &task,
+                    EventKind::Described,
+                    Some(current.description.clone()),
+                    Some(task.description.clone()),
+                )?;
+            }
+            if task.priority != current.priority {
+                svc.record(
+                    &task,
+                    EventKind::Prioritized,
+                    Some(current.priority.to_string()),
+                    Some(task.priority.to_string()),
+                )?;
+            }
+            if task.due_at != current.due_at {
+                svc.record(
+                    &task,
+                    EventKind::Rescheduled,
+                    current.due_at.map(|due| due.to_rfc3339()),
+                    task.due_at.map(|due| due.to_rfc3339()),
+                )?;
+            }
+
+            Ok(task)
+        })
+    }
+
+    /// Renames a task.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If the task does not exist or the name is empty
+    ///   or already used on the board.
+    pub fn rename_task(&mut self, id: u32, name: &str) -> Result<Task> {
+        self.update_task(id, TaskPatch::new().name(name))
+    }
+
+    /// Replaces the description of a task.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn describe_task(&mut self, id: u32, description: &str) -> Result<Task> {
+        self.update_task(id, TaskPatch::new().description(description))
+    }
+
+    /// Changes how important a task is.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn set_priority(&mut self, id: u32, priority: Priority) -> Result<Task> {
+        self.update_task(id, TaskPatch::new().priority(priority))
+    }
+
+    /// Sets or clears the date a task is due.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If the task does not exist or the due date is
+    ///   before the task was created.
+    pub fn set_due_date(&mut self, id: u32, due_at: Option<DateTime<Utc>>) -> Result<Task> {
+        let patch = TaskPatch {
+            due_at: Some(due_at),
+            ..TaskPatch::default()
+        };
+        self.update_task(id, patch)
+    }
+
+    /// Lists unfinished tasks on the current board whose due date has
+    /// passed, most overdue first.
+    pub fn overdue_tasks(&self) -> Result<Vec<Task>> {
+        let query = TaskQuery::new()
+            .due_before(Utc::now())
+            .sort_by(SortKey::DueAt);
+
+        self.unfinished(&query)
+    }
+
+    /// Lists unfinished tasks on the current board that fall due within
+    /// `within` from now, soonest first.
+    pub fn due_soon(&self, within: Duration) -> Result<Vec<Task>> {
+        let now = Utc::now();
+        let query = TaskQuery::new()
+            .due_between(now, now + within)
+            .sort_by(SortKey::DueAt);
+
+        self.unfinished(&query)
+    }
+
+    /// Lists tasks matching `query` that are not in the done column.
+    fn unfinished(&self, query: &TaskQuery) -> Result<Vec<Task>> {
+        Ok(self
+            .list(query)?
+            .into_iter()
+            .filter(|t| !self.workflow().is_done(t.status.as_str()))
+            .collect())
+    }
+
+    /// Adds a label to a task. Labels are stored in lowercase; adding one the
+    /// task already carries changes nothing.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError)` - If the task does not exist or the label is
+    ///   empty or contains whitespace or commas.
+    pub fn add_label(&mut self, id: u32, label: &str) -> Result<Task> {
+        self.atomically(|svc| {
+            let label = normalize_label(label)?;
+            let mut task = svc.get_task(id)?;
+            if !task.labels.insert(label.clone()) {
+                return Ok(task);
+            }
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(&task, EventKind::Labeled, None, Some(label))?;
+
+            Ok(task)
+        })
+    }
+
+    /// Removes a label from a task; removing one the task does not carry
+    /// changes nothing.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The updated task.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn remove_label(&mut self, id: u32, label: &str) -> Result<Task> {
+        self.atomically(|svc| {
+            let label = label.trim().to_lowercase();
+            let mut task = svc.get_task(id)?;
+            if !task.labels.remove(&label) {
+                return Ok(task);
+            }
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(&task, EventKind::Unlabeled, Some(label), None)?;
+
+            Ok(task)
+        })
+    }
+
+    /// Counts how many tasks on the current board carry each label.
+    pub fn labels(&self) -> Result<BTreeMap<String, usize>> {
+        let mut counts = BTreeMap::new();
+        for task in self.list(&TaskQuery::new())? {
+            for label in task.labels {
+                *counts.entry(label).or_insert(0) += 1;
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Renames a label on every task of the current board that carries it.
+    /// Tasks that already carry the new label simply lose the old one.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - The number of tasks changed.
+    /// * `Err(KanbanError)` - If the new label is invalid or a task could
+    ///   not be stored.
+    pub fn rename_label(&mut self, from: &str, to: &str) -> Result<usize> {
+        self.atomically(|svc| {
+            let to = normalize_label(to)?;
+            let from = from.trim().to_lowercase();
+            if from == to {
+                return Ok(0);
+            }
+
+            let tasks = svc.list(&TaskQuery::new().label(&from))?;
+            for mut task in tasks.iter().cloned() {
+                task.labels.remove(&from);
+                task.labels.insert(to.clone());
+                task.updated_at = Some(Utc::now());
+
+                let task = svc.repo.update(task)?;
+                svc.record(
+                    &task,
+                    EventKind::Labeled,
+                    Some(from.clone()),
+                    Some(to.clone()),
+                )?;
+            }
+
+            Ok(tasks.len())
+        })
+    }
+
+    /// Picks the task that should be pulled next: the most urgent task in
+    /// the current board's first column, oldest first among equals.
+    pub fn next_task(&self) -> Result<Option<Task>> {
+        let query = TaskQuery::new()
+            .status(self.workflow().initial().clone())
+            .sort_by(SortKey::Priority)
+            .limit(1);
+
+        Ok(self.list(&query)?.into_iter().next())
+    }
+
+    /// Moves a task to another board, keeping its ID and history.
+    ///
+    /// The task stays in its column if the target board's workflow has one
+    /// with the same name, otherwise it is placed in the target's first
+    /// column.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the task.
+    /// * `board_id` - The board to move the task to.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The transferred task.
+    /// * `Err(KanbanError)` - If the task or board does not exist, the task
+    ///   is archived, the target board already has a task with the same name
+    ///   or the target column is at its WIP limit.
+    pub fn transfer_task(&mut self, id: u32, board_id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(id)?;
+            task.before_transfer()?;
+            let from = svc.get_board(task.board_id)?;
+            let to = svc.get_board(board_id)?;
+            if from.id == to.id {
+                return Ok(task);
+            }
+
+            if let Some(t) = svc.repo.find_by_name(board_id, &task.name)? {
+                return Err(KanbanError::DuplicateName { name: t.name });
+            }
+
+            let status = to
+                .workflow
+                .resolve(task.status.as_str())
+                .unwrap_or_else(|_| to.workflow.initial().clone());
+            svc.check_wip_limit(&to, &status)?;
+            let old_status = std::mem::replace(&mut task.status, status);
+            task.board_id = board_id;
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(
+                &task,
+                EventKind::Transferred,
+                Some(from.name),
+                Some(to.name),
+            )?;
+            if old_status != task.status {
+                svc.record(
+                    &task,
+                    EventKind::Moved,
+                    Some(old_status.to_string()),
+                    Some(task.status.to_string()),
+                )?;
+            }
+
+            Ok(task)
+        })
+    }
+
+    /// Moves a task to another column of the workflow.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the task.
+    /// * `column` - Name of the target column (case-insensitive).
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The moved task.
+    /// * `Err(KanbanError)` - If the task or column does not exist, the
+    ///   workflow does not allow the transition or the column is full.
+    pub fn move_task(&mut self, id: u32, column: &str) -> Result<Task> {
+        self.move_to(id, column, false)
+    }
+
+    /// Moves a task like [`TaskService::move_task`] but ignores the target
+    /// column's WIP limit. The task is flagged as expedited.
+    pub fn expedite_task(&mut self, id: u32, column: &str) -> Result<Task> {
+        self.move_to(id, column, true)
+    }
+
+    fn move_to(&mut self, id: u32, column: &str, expedite: bool) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(id)?;
+            let board = svc.get_board(task.board_id)?;
+            let to = board.workflow.resolve(column)?;
+            task.before_move(&board.workflow, &to)?;
+            if board.workflow.is_started(to.as_str()) {
+                task.before_start(&svc.open_blockers(&task)?)?;
+            }
+            if board.workflow.is_done(to.as_str()) {
+                task.before_move_to_done(&board.workflow, &svc.progress_of(&task)?)?;
+            }
+
+            if !expedite {
+                svc.check_wip_limit(&board, &to)?;
+            }
+
+            let now = Utc::now();
+            if board.workflow.is_done(to.as_str()) {
+                task.completed_late = task.is_past_due(now);
+            }
+            let unblocked = Self::clear_flag_outside_progress(&mut task, &board.workflow, &to);
+            let from = std::mem::replace(&mut task.status, to);
+            task.expedited |= expedite;
+            task.updated_at = Some(now);
+
+            let task = svc.repo.update(task)?;
+            if let Some(flag) = unblocked {
+                svc.record(&task, EventKind::Unblocked, Some(flag.reason), None)?;
+            }
+            svc.record(
+                &task,
+                EventKind::Moved,
+                Some(from.to_string()),
+                Some(task.status.to_string()),
+            )?;
+
+            Ok(task)
+        })
+    }
+
+    /// Sends a finished task back to an earlier column of its workflow so
+    /// work on it can continue. The move is allowed whatever transitions the
+    /// workflow defines, but the target column's WIP limit still applies.
+    ///
+    /// # Arguments
+    /// * `id` - The task to reopen.
+    /// * `column` - The column to reopen it in; defaults to the workflow's
+    ///   start column.
+    /// * `reason` - Why the task is reopened, recorded in its history.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The reopened task.
+    /// * `Err(KanbanError)` - If the task does not exist or is not done, the
+    ///   column is not before the done column, the reason is empty or the
+    ///   column is at its WIP limit.
+    pub fn reopen_task(&mut self, id: u32, column: Option<&str>, reason: &str) -> Result<Task> {
+        let task = self.get_task(id)?;
+        let board = self.get_board(task.board_id)?;
+        let workflow = &board.workflow;
+        if !workflow.is_done(task.status.as_str()) {
+            return Err(KanbanError::Validation {
+                field: "task",
+                reason: format!("task {} is not done", id),
+            });
+        }
+
+        let to = match column {
+            Some(column) => workflow.resolve(column)?,
+            None => workflow.start_column().clone(),
+        };
+        if workflow.position(to.as_str()) >= workflow.position(task.status.as_str()) {
+            return Err(KanbanError::Validation {
+                field: "column",
+                reason: format!("{} does not come before {}", to, task.status),
+            });
+        }
+
+        self.move_back(task, &board, to, EventKind::Reopened, reason)
+    }
+
+    /// Sends a started, unfinished task back to the first column of its
+    /// workflow, e.g. when it was started by mistake.
+    ///
+    /// # Arguments
+    /// * `id` - The task to stop.
+    /// * `reason` - Why work on the task stops, recorded in its history.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The stopped task.
+    /// * `Err(KanbanError)` - If the task does not exist, is not in progress,
+    ///   the reason is empty or the first column is at its WIP limit.
+    pub fn stop_task(&mut self, id: u32, reason: &str) -> Result<Task> {
+        let task = self.get_task(id)?;
+        let board = self.get_board(task.board_id)?;
+        let workflow = &board.workflow;
+        let status = task.status.as_str();
+        if !workflow.is_started(status) || workflow.is_done(status) {
+            return Err(KanbanError::Validation {
+                field: "task",
+                reason: format!("task {} is not in progress", id),
+            });
+        }
+
+        let to = workflow.initial().clone();
+        self.move_back(task, &board, to, EventKind::Stopped, reason)
+    }
+
+    /// Moves `task` back to column `to` and records why.
+    fn move_back(
+        &mut self,
+        mut task: Task,
+        board: &Board,
+        to: Status,
+        kind: EventKind,
+        reason: &str,
+    ) -> Result<Task> {
+        self.atomically(|svc| {
+            task.before_move_back(reason)?;
+            svc.check_wip_limit(board, &to)?;
+
+            let unblocked = Self::clear_flag_outside_progress(&mut task, &board.workflow, &to);
+            let from = std::mem::replace(&mut task.status, to);
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            if let Some(flag) = unblocked {
+                svc.record(&task, EventKind::Unblocked, Some(flag.reason), None)?;
+            }
+            let event = TaskEvent::new(
+                task.id.unwrap_or_default(),
+                kind,
+                Some(from.to_string()),
+                Some(task.status.to_string()),
+                &svc.actor,
+            )
+            .with_reason(reason.trim());
+            svc.repo.append_event(event)?;
+
+            Ok(task)
+        })
+    }
+
+    /// Takes the blocked flag off `task` if it is moving to column `to`
+    /// that is not in progress.
+    fn clear_flag_outside_progress(
+        task: &mut Task,
+        workflow: &Workflow,
+        to: &Status,
+    ) -> Option<BlockedFlag> {
+        if workflow.is_started(to.as_str()) && !workflow.is_done(to.as_str()) {
+            return None;
+        }
+        task.blocked.take()
+    }
+
+    /// Flags a task in progress as blocked. The task keeps its column but
+    /// cannot be finished until it is unblocked, and the time it stays
+    /// blocked is left out of its active time.
+    ///
+    /// # Arguments
+    /// * `id` - The task to flag.
+    /// * `reason` - What the task is waiting on.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The blocked task.
+    /// * `Err(KanbanError)` - If the task does not exist, is not in progress,
+    ///   is already blocked or the reason is empty.
+    pub fn block_task(&mut self, id: u32, reason: &str) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(id)?;
+            let board = svc.get_board(task.board_id)?;
+            task.before_block(&board.workflow, reason)?;
+
+            let now = Utc::now();
+            task.blocked = Some(BlockedFlag {
+                reason: reason.trim().to_string(),
+                since: now,
+            });
+            task.updated_at = Some(now);
+
+            let task = svc.repo.update(task)?;
+            svc.record(
+                &task,
+                EventKind::Blocked,
+                None,
+                Some(reason.trim().to_string()),
+            )?;
+
+            Ok(task)
+        })
+    }
+
+    /// Clears a task's blocked flag.
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The unblocked task.
+    /// * `Err(KanbanError)` - If the task does not exist or is not blocked.
+    pub fn unblock_task(&mut self, id: u32) -> Result<Task> {
+        self.atomically(|svc| {
+            let mut task = svc.get_task(id)?;
+            let Some(flag) = task.blocked.take() else {
+                return Err(KanbanError::Validation {
+                    field: "task",
+                    reason: format!("task {} is not blocked", id),
+                });
+            };
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(&task, EventKind::Unblocked, Some(flag.reason), None)?;
+
+            Ok(task)
+        })
+    }
+
+    /// Lists the blocked tasks of the current board, longest blocked first.
+    pub fn blocked_tasks(&self) -> Result<Vec<Task>> {
+        let mut tasks: Vec<_> = self
+            .list(&TaskQuery::new())?
+            .into_iter()
+            .filter(|task| task.blocked.is_some())
+            .collect();
+        tasks.sort_by_key(|task| (task.blocked.as_ref().map(|flag| flag.since), task.id));
+        Ok(tasks)
+    }
+
+    /// Total time a task has spent blocked, counting a flag that is still
+    /// set up to now.
+    ///
+    /// # Returns
+    /// * `Ok(Duration)` - The time spent blocked.
+    /// * `Err(KanbanError::NotFound)` - If the task does not exist.
+    pub fn blocked_time(&self, id: u32) -> Result<Duration> {
+        let task = self.get_task(id)?;
+        let board = self.get_board(task.board_id)?;
+        let history = self.repo.history(id)?;
+        let timeline = TaskTimeline::from_history(&task, &history, &board.workflow);
+
+        Ok(timeline.blocked_time(Utc::now()))
+    }
+
+    /// Total time the tasks of the current board, archived ones included,
+    /// have spent blocked, counting flags that are still set up to now.
+    pub fn board_blocked_time(&self) -> Result<Duration> {
+        let now = Utc::now();
+        Ok(self
+            .timelines()?
+            .iter()
+            .map(|timeline| timeline.blocked_time(now))
+            .sum())
+    }
+
+    /// Fails if `column` of `board` cannot take another task.
+    fn check_wip_limit(&self, board: &Board, column: &Status) -> Result<()> {
+        let Some(limit) = board.workflow.wip_limit(column) else {
+            return Ok(());
+        };
+
+        let query = TaskQuery::new()
+            .board(board.id.unwrap_or_default())
+            .status(column.clone());
+        if self.repo.list(&query)?.len() >= limit {
+            return Err(KanbanError::WipLimitExceeded {
+                column: column.clone(),
+                limit,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Lists tasks matching the query, on the current board unless the
+    /// query names another one.
+    /// Statuses are matched against the workflow's spelling of each column,
+    /// and sorting by status follows the order of the workflow's columns.
+    pub fn list(&self, query: &TaskQuery) -> Result<Vec<Task>> {
+        let mut query = query.clone();
+        let board_id = *query.board.get_or_insert(self.board_id());
+        let workflow = match self.repo.get_board(board_id)? {
+            Some(board) => board.workflow,
+            None => return Ok(Vec::new()),
+        };
+        query.statuses = query
+            .statuses
+            .into_iter()
+            .map(|status| workflow.resolve(status.as_str()).unwrap_or(status))
+            .collect();
+        if query.columns.is_empty() {
+            query.columns = workflow.columns().to_vec();
+        }
+
+        self.repo.list(&query)
+    }
+
+    /// Lists tasks currently in the given status, most urgent and then
+    /// oldest first.
+    pub fn list_by_status(&self, status: Status) -> Result<Vec<Task>> {
+        self.list(&TaskQuery::new().status(status).sort_by(SortKey::Priority))
+    }
+
+    pub fn find_by_id(&self, id: u32) -> Result<Option<Task>> {
+        self.repo.get(id)
+    }
+
+    /// Returns every change made to a task, oldest first.
+    pub fn history(&self, id: u32) -> Result<Vec<TaskEvent>> {
+        self.get_task(id)?;
+        self.repo.history(id)
+    }
+
+    /// Reconstructs the workflow timeline of every task on the current board
+    /// from its history.
+    pub fn timelines(&self) -> Result<Vec<TaskTimeline>> {
+        self.repo
+            .list(&TaskQuery::new().board(self.board_id()).including_archived())?
+            .iter()
+            .map(|task| {
+                let history = self.repo.history(task.id.unwrap_or_default())?;
+                Ok(TaskTimeline::from_history(task, &history, self.workflow()))
+            })
+            .collect()
+    }
+
+    /// Lead and cycle time percentiles of tasks finished within `[from, until)`.
+    pub fn stats(&self, from: DateTime<Utc>, until: DateTime<Utc>) -> Result<FlowStats> {
+        Ok(FlowStats::compute(&self.timelines()?, from, until))
+    }
+
+    /// Reconstructs the number of tasks in each column of the current board
+    /// at the end of every day within `[from, to]`.
+    ///
+    /// # Returns
+    /// * `Ok(CumulativeFlow)` - Daily counts per workflow column.
+    /// * `Err(KanbanError::Validation)` - If `from` is after `to`.
+    pub fn cumulative_flow(&self, from: NaiveDate, to: NaiveDate) -> Result<CumulativeFlow> {
+        if from > to {
+            return Err(KanbanError::Validation {
+                field: "from",
+                reason: format!("must not be after {}", to),
+            });
+        }
+
+        let tasks = self
+            .repo
+            .list(&TaskQuery::new().board(self.board_id()).including_archived())?
+            .into_iter()
+            .map(|task| {
+                let history = self.repo.history(task.id.unwrap_or_default())?;
+                Ok((task, history))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(CumulativeFlow::build(&tasks, self.workflow(), from, to))
+    }
+
+    /// Forecasts how many tasks will be finished from tomorrow through `by`.
+    ///
+    /// # Returns
+    /// * `Ok(ItemsForecast)` - Minimum number of tasks at each confidence level.
+    /// * `Err(KanbanError::Validation)` - If `by` is not in the future or no
+    ///   task was finished in the sampled history.
+    pub fn forecast_items(
+        &self,
+        by: NaiveDate,
+        options: &ForecastOptions,
+    ) -> Result<ItemsForecast> {
+        let today = Utc::now().date_naive();
+        if by <= today {
+            return Err(KanbanError::Validation {
+                field: "by",
+                reason: String::from("must be in the future"),
+            });
+        }
+
+        let days = (by - today).num_days() as u32;
+        Ok(self.simulator(today, options)?.items_within(days))
+    }
+
+    /// Forecasts when `items` more tasks will be finished.
+    ///
+    /// # Returns
+    /// * `Ok(CompletionForecast)` - Completion date at each confidence level.
+    /// * `Err(KanbanError::Validation)` - If no task was finished in the
+    ///   sampled history.
+    pub fn forecast_completion(
+        &self,
+        items: u32,
+        options: &ForecastOptions,
+    ) -> Result<CompletionForecast> {
+        let today = Utc::now().date_naive();
+        Ok(self.simulator(today, options)?.completion(items, today))
+    }
+
+    /// Builds a simulator from the throughput of the sampled days up to and
+    /// including `today`.
+    fn simulator(&self, today: NaiveDate, options: &ForecastOptions) -> Result<MonteCarlo> {
+        let until = today + Duration::days(1);
+        let from = until - Duration::days(i64::from(options.history_days));
+        let throughput = Throughput::from_timelines(&self.timelines()?, from, until);
+        MonteCarlo::new(&throughput, options)
+    }
+
+    /// Runs `f` as one unit of work of the repository, so a change and the
+    /// history recording it are stored together or not at all. Units of
+    /// work started inside `f` join the one already in progress.
+    fn atomically<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.in_unit_of_work {
+            return f(self);
+        }
+
+        self.repo.begin()?;
+        self.in_unit_of_work = true;
+        let result = f(self);
+        self.in_unit_of_work = false;
+
+        match result {
+            Ok(value) => {
+                self.repo.commit()?;
+                Ok(value)
+            }
+            Err(err) => {
+                self.repo.rollback()?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Appends a change made by the current actor to the task's history.
+    fn record(
+        &mut self,
+        task: &Task,
+        kind: EventKind,
+        old_value: Option<String>,
+        new_value: Option<String>,
+    ) -> Result<()> {
+        let event = TaskEvent::new(
+            task.id.unwrap_or_default(),
+            kind,
+            old_value,
+            new_value,
+            &self.actor,
+        );
+
+        self.repo.append_event(event)
+    }
+
+    /// Stores a new task on `board`, in the first column of its workflow.
+    fn insert_task(&mut self, mut task: Task, board: &Board) -> Result<Task> {
+        self.atomically(|svc| {
+            let board_id = board.id.unwrap_or(DEFAULT_BOARD_ID);
+
+            // ensure task uniqueness within the board
+            if let Some(t) = svc.repo.find_by_name(board_id, &task.name)? {
+                return Err(KanbanError::DuplicateName { name: t.name });
+            }
+
+            // place the task in the workflow's first column
+            task.board_id = board_id;
+            task.status = board.workflow.initial().clone();
+            // perform validations
+            task.before_add()?;
+
+            let task = svc.repo.insert(task)?;
+            svc.record(
+                &task,
+                EventKind::Created,
+                None,
+                Some(task.status.to_string()),
+            )?;
+
+            Ok(task)
+        })
+    }
+
+    /// Stores a task whose checklist changed and records the change.
+    fn save_checklist(
+        &mut self,
+        mut task: Task,
+        old: Option<String>,
+        new: Option<String>,
+    ) -> Result<Task> {
+        self.atomically(|svc| {
+            task.updated_at = Some(Utc::now());
+
+            let task = svc.repo.update(task)?;
+            svc.record(&task, EventKind::ChecklistChanged, old, new)?;
+
+            Ok(task)
+        })
+    }
+
+    fn progress_of(&self, task: &Task) -> Result<Progress> {
+        let mut progress = task.checklist_progress();
+
+        let boards = self.repo.list_boards()?;
+        for child in self
+            .repo
+            .list(&TaskQuery::new().children_of(task.id.unwrap_or_default()))?
+        {
+            progress.total += 1;
+            progress.done += usize::from(is_done_on(&boards, &child));
+        }
+
+        Ok(progress)
+    }
+
+    /// IDs of the blockers of `task` that are not done yet. Blockers that no
+    /// longer exist or are archived are ignored.
+    fn open_blockers(&self, task: &Task) -> Result<Vec<u32>> {
+        let boards = self.repo.list_boards()?;
+        let mut open = Vec::new();
+        for &id in &task.blocked_by {
+            if self
+                .repo
+                .get(id)?
+                .is_some_and(|blocker| !blocker.is_archived() && !is_done_on(&boards, &blocker))
+            {
+                open.push(id);
+            }
+        }
+
+        Ok(open)
+    }
+
+    /// Finds a chain of "blocks" links leading from task `from` to task `to`,
+    /// returned as the IDs along the way.
+    fn blocking_path(&self, from: u32, to: u32) -> Result<Option<Vec<u32>>> {
+        if from == to {
+            return Ok(Some(vec![from]));
+        }
+
+        // depth-first search backwards from `to` through its blockers
+        let mut stack = vec![vec![to]];
+        let mut seen = vec![to];
+        while let Some(path) = stack.pop() {
+            let current = path[path.len() - 1];
+            let blockers = self
+                .repo
+                .get(current)?
+                .map(|t| t.blocked_by)
+                .unwrap_or_default();
+            for blocker in blockers {
+                let mut next = path.clone();
+                next.push(blocker);
+                if blocker == from {
+                    next.reverse();
+                    return Ok(Some(next));
+                }
+                if !seen.contains(&blocker) {
+                    seen.push(blocker);
+                    stack.push(next);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn get_task(&self, id: u32) -> Result<Task> {
+        self.repo.get(id)?.ok_or(KanbanError::NotFound { id })
+    }
+
+    fn get_user(&self, id: u32) -> Result<User> {
+        self.repo
+            .get_user(id)?
+            .ok_or(KanbanError::UserNotFound { id })
+    }
+
+    fn get_comment(&self, id: u32) -> Result<Comment> {
+        self.repo
+            .get_comment(id)?
+            .ok_or(KanbanError::CommentNotFound { id })
+    }
+
+    fn get_board(&self, id: u32) -> Result<Board> {
+        self.repo
+            .get_board(id)?
+            .ok_or(KanbanError::BoardNotFound { id })
+    }
+
+    fn board_id(&self) -> u32 {
+        self.board.id.unwrap_or(DEFAULT_BOARD_ID)
+    }
+
+    /// Stores `board`, keeping the service's copy of the current board fresh.
+    fn save_board(&mut self, board: Board) -> Result<Board> {
+        let board = self.repo.update_board(board)?;
+        if self.board.id == board.id {
+            self.board = board.clone();
+        }
+
+        Ok(board)
+    }
+
+    fn ensure_board_name_free(&self, name: &str, except: Option<u32>) -> Result<()> {
+        match self.find_board(name)? {
+            Some(b) if b.id != except => Err(KanbanError::DuplicateBoardName { name: b.name }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns `true` if `task` is in the done column of its board.
+fn is_done_on(boards: &[Board], task: &Task) -> bool {
+    boards
+        .iter()
+        .find(|b| b.id == Some(task.board_id))
+        .is_some_and(|b| b.workflow.is_done(task.status.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryTaskRepository;
+    use crate::JsonFileTaskRepository;
+    use tempfile::TempDir;
+
+    struct Setup {
+        svc: TaskService<InMemoryTaskRepository>,
+    }
+    const TASK_NAME1: &str = "task1";
+    const TASK_NAME2: &str = "task2";
+    const TASK_NAME3: &str = "task3";
+    const TASK_DESCRIPTION1: &str = "description1";
+    const TASK_DESCRIPTION2: &str = "description2";
+    const TASK_DESCRIPTION3: &str = "description3";
+
+    // task ids
+    // TODO: ensure ID is i32 and not u32(can accept negative integers)
+    const TASK1_ID: u32 = 1;
+    const TASK2_ID: u32 = 2;
+    const TASK3_ID: u32 = 3;
+
+    impl Setup {
+        fn new() -> Self {
+            let mut svc = TaskService::new(InMemoryTaskRepository::new()).unwrap();
+
+            // seed tasks
+            svc.add_task(TASK_NAME1, TASK_DESCRIPTION1)
+                .expect("task not created");
+            svc.add_task(TASK_NAME2, TASK_DESCRIPTION2)
+                .expect("task not created");
+            svc.add_task(TASK_NAME3, TASK_DESCRIPTION3)
+                .expect("task not created");
+
+            // move task2 and task3 to Doing state
+            let _ = svc.move_task(TASK2_ID, "Doing");
+            let _ = svc.move_task(TASK3_ID, "Doing");
+            // move task3 to Done state
+            let _ = svc.move_task(TASK3_ID, "Done");
+            Setup { svc }
+        }
+    }
+    #[test]
+    fn test_add_task_succeeds() {
+        let mut setup = Setup::new();
+        let new_task_name = "new task";
+        let new_task = setup.svc.add_task(new_task_name, "new task description");
+        assert!(new_task.is_ok());
+
+        let new_task = new_task.unwrap();
+        assert_eq!(new_task.name, new_task_name);
+        assert_eq!(new_task.id, Some(4));
+    }
+
+    #[test]
+    fn test_add_task_fails_with_duplicate_task_name() {
+        let mut setup = Setup::new();
+        let res = setup.svc.add_task(TASK_NAME1, TASK_DESCRIPTION1);
+        assert!(res.is_err());
+
+        let err = res.expect_err("should return an error");
+        assert_eq!(
+            err,
+            KanbanError::DuplicateName {
+                name: TASK_NAME1.to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn validation_errors_are_caught() {
+        let mut setup = Setup::new();
+        let res = setup.svc.add_task("", "description");
+        assert!(res.is_err());
+
+        let err = res.expect_err("should return an error");
+        assert_eq!(
+            err,
+            KanbanError::Validation {
+                field: "name",
+                reason: "must not be empty".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn move_to_doing_succeeds() {
+        let mut setup = Setup::new();
+        let res = setup.svc.move_task(TASK1_ID, "doing");
+        assert!(res.is_ok());
+
+        // find task
+        let task1 = setup.svc.find_by_id(TASK1_ID).unwrap().unwrap();
+        // confirm it moved to Doing state
+        assert_eq!(task1.status, Status::DOING);
+        assert!(task1.updated_at.is_some());
+    }
+
+    #[test]
+    fn move_to_doing_fails() {
+        let mut setup = Setup::new();
+        // task of id TASK3_ID(3) is already in the done state
+        // cannot move to doing state
+        let res = setup.svc.move_task(TASK3_ID, "Doing");
+        assert!(res.is_err());
+
+        let err = res.expect_err("should return an error");
+        assert_eq!(
+            err,
+            KanbanError::InvalidTransition {
+                from: Status::DONE,
+                to: Status::DOING
+            }
+        );
+    }
+
+    #[test]
+    fn move_unknown_task_fails() {
+        let mut setup = Setup::new();
+        let res = setup.svc.move_task(42, "Doing");
+
+        let err = res.expect_err("should return an error");
+        assert_eq!(err, KanbanError::NotFound { id: 42 });
+    }
+
+    #[test]
+    fn move_to_done_succeeds() {
+        let mut setup = Setup::new();
+        // create task
+        let res = setup.svc.move_task(TASK2_ID, "Done");
+        assert!(res.is_ok());
+
+        // find task
+        let task2 = setup.svc.find_by_id(TASK2_ID).unwrap().unwrap();
+        // confirm it moved to Done state
+        assert_eq!(task2.status, Status::DONE);
+    }
+
+    #[test]
+    fn move_to_done_fails() {
+        let mut setup = Setup::new();
+        // task of id TASK3_ID(3) is already in the done state
+        // ONLY tasks in progress(Doing state) can be marked as Done
+        let res = setup.svc.move_task(TASK1_ID, "Done");
+        assert!(res.is_err());
+
+        let err = res.expect_err("should return an error");
+        assert_eq!(
+            err,
+            KanbanError::InvalidTransition {
+                from: Status::TODO,
+                to: Status::DONE
+            }
+        );
+    }
+
+    #[test]
+    fn move_to_unknown_column_fails() {
+        let mut setup = Setup::new();
+        let err = setup.svc.move_task(TASK1_ID, "Review").unwrap_err();
+
+        assert!(matches!(
+            err,
+            KanbanError::Validation {
+                field: "status",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn custom_workflow_is_enforced() {
+        let review = Status::new("Review");
+        let workflow = Workflow::new(
+            vec![
+                Status::new("Backlog"),
+                Status::DOING,
+                review.clone(),
+                Status::DONE,
+            ],
+            vec![
+                (Status::new("Backlog"), Status::DOING),
+                (Status::DOING, review.clone()),
+                (review.clone(), Status::DOING),
+                (review.clone(), Status::DONE),
+            ],
+        )
+        .unwrap();
+        let mut svc = TaskService::with_workflow(InMemoryTaskRepository::new(), workflow).unwrap();
+
+        let task = svc.add_task(TASK_NAME1, TASK_DESCRIPTION1).unwrap();
+        assert_eq!(task.status.as_str(), "Backlog");
+
+        let id = task.id.unwrap();
+        svc.move_task(id, "Doing").unwrap();
+        assert_eq!(
+            svc.move_task(id, "Done").unwrap_err(),
+            KanbanError::InvalidTransition {
+                from: Status::DOING,
+                to: Status::DONE
+            }
+        );
+
+        svc.move_task(id, "review").unwrap();
+        svc.move_task(id, "Doing").unwrap();
+        svc.move_task(id, "Review").unwrap();
+        let task = svc.move_task(id, "Done").unwrap();
+        assert_eq!(task.status, Status::DONE);
+    }
+
+    #[test]
+    fn sorting_by_status_follows_the_workflow() {
+        let setup = Setup::new();
+        let ids: Vec<_> = setup
+            .svc
+            .list(&TaskQuery::new().sort_by(SortKey::Status).descending())
+            .unwrap()
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        // alphabetically Todo would come first and Doing last
+        assert_eq!(ids, vec![Some(TASK3_ID), Some(TASK2_ID), Some(TASK1_ID)]);
+    }
+
+    #[test]
+    fn transfer_respects_target_wip_limit() {
+        let mut setup = Setup::new();
+        let workflow = Workflow::default().with_wip_limit("Doing", 1).unwrap();
+        let ops_id = setup.svc.create_board("ops", workflow).unwrap().id.unwrap();
+        setup.svc.transfer_task(TASK2_ID, ops_id).unwrap();
+
+        setup.svc.move_task(TASK1_ID, "Doing").unwrap();
+        assert_eq!(
+            setup.svc.transfer_task(TASK1_ID, ops_id).unwrap_err(),
+            KanbanError::WipLimitExceeded {
+                column: Status::DOING,
+                limit: 1
+            }
+        );
+        let task = setup.svc.find_by_id(TASK1_ID).unwrap().unwrap();
+        assert_ne!(task.board_id, ops_id);
+        assert_eq!(setup.svc.history(TASK1_ID).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn archived_task_cannot_be_transferred() {
+        let mut setup = Setup::new();
+        let ops_id = setup
+            .svc
+            .create_board("ops", Workflow::default())
+            .unwrap()
+            .id
+            .unwrap();
+        setup.svc.archive_task(TASK1_ID).unwrap();
+
+        assert!(matches!(
+            setup.svc.transfer_task(TASK1_ID, ops_id),
+            Err(KanbanError::Validation { field: "task", .. })
+        ));
+        let task = setup.svc.find_by_id(TASK1_ID).unwrap().unwrap();
+        assert_ne!(task.board_id, ops_id);
+    }
+
+    #[test]
+    fn move_respects_wip_limit() {
+        let workflow = Workflow::default().with_wip_limit("Doing", 1).unwrap();
+        let mut svc = TaskService::with_workflow(InMemoryTaskRepository::new(), workflow).unwrap();
+        svc.add_task(TASK_NAME1, TASK_DESCRIPTION1).unwrap();
+        svc.add_task(TASK_NAME2, TASK_DESCRIPTION2).unwrap();
+
+        svc.move_task(TASK1_ID, "Doing").unwrap();
+        let err = svc.move_task(TASK2_ID, "Doing").unwrap_err();
+        assert_eq!(
+            err,
+            KanbanError::WipLimitExceeded {
+                column: Status::DOING,
+                limit: 1
+            }
+        );
+        assert_eq!(
+            svc.find_by_id(TASK2_ID).unwrap().unwrap().status,
+            Status::TODO
+        );
+
+        // finishing work frees up the column
+        svc.move_task(TASK1_ID, "Done").unwrap();
+        svc.move_task(TASK2_ID, "Doing").unwrap();
+    }
+
+    #[test]
+    fn expedite_overrides_wip_limit() {
+        let workflow = Workflow::default().with_wip_limit("Doing", 1).unwrap();
+        let mut svc = TaskService::with_workflow(InMemoryTaskRepository::new(), workflow).unwrap();
+        svc.add_task(TASK_NAME1, TASK_DESCRIPTION1).unwrap();
+        svc.add_task(TASK_NAME2, TASK_DESCRIPTION2).unwrap();
+
+        svc.move_task(TASK1_ID, "Doing").unwrap();
+        let task = svc.expedite_task(TASK2_ID, "Doing").unwrap();
+        assert_eq!(task.status, Status::DOING);
+        assert!(task.expedited);
+        assert!(!svc.find_by_id(TASK1_ID).unwrap().unwrap().expedited);
+
+        // the flag sticks with the task after later moves
+        let task = svc.move_task(TASK2_ID, "Done").unwrap();
+        assert!(task.expedited);
+    }
+
+    #[test]
+    fn changes_are_recorded_in_history() {
+        let mut svc = TaskService::new(InMemoryTaskRepository::new()).unwrap();
+        svc.add_task(TASK_NAME1, TASK_DESCRIPTION1).unwrap();
+        svc.set_actor("alice");
+        svc.move_task(TASK1_ID, "Doing").unwrap();
+        svc.set_actor("bob");
+        svc.move_task(TASK1_ID, "Done").unwrap();
+
+        let history = svc.history(TASK1_ID).unwrap();
+        let summary: Vec<_> = history
+            .iter()
+            .map(|e| {
+                (
+                    e.kind,
+                    e.old_value.as_deref(),
+                    e.new_value.as_deref(),
+                    e.actor.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (EventKind::Created, None, Some("Todo"), DEFAULT_ACTOR),
+                (EventKind::Moved, Some("Todo"), Some("Doing"), "alice"),
+                (EventKind::Moved, Some("Doing"), Some("Done"), "bob"),
+            ]
+        );
+        assert!(history.windows(2).all(|w| w[0].at <= w[1].at));
+    }
 
-pub struct TaskService<R: InMemoryTaskRepo> {
-    repo: R,
-}
+    #[test]
+    fn failed_moves_are_not_recorded() {
+        let mut setup = Setup::new();
+        let before = setup.svc.history(TASK1_ID).unwrap().len();
 
-impl<R: InMemoryTaskRepo> TaskService<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+        assert!(setup.svc.move_task(TASK1_ID, "Done").is_err());
+        assert_eq!(setup.svc.history(TASK1_ID).unwrap().len(), before);
+        assert_eq!(
+            setup.svc.history(42).unwrap_err(),
+            KanbanError::NotFound { id: 42 }
+        );
     }
 
-    pub fn add_task(&mut self, name: &str, desc: &str) -> Result<&Task, String> {
-        // initialize a new task object
-        let task = domain::Task::new(name.to_string(), desc.to_string());
-        // perform validations
-        // task.before_add()?;
+    #[test]
+    fn stats_cover_tasks_done_in_window() {
+        let setup = Setup::new();
+        let now = Utc::now();
 
-        Ok(self
-            .repo
-            .add_task(&*task.name.to_string(), &*task.description.to_string())?)
-        // TODO: add converters
+        let stats = setup
+            .svc
+            .stats(
+                now - chrono::Duration::days(1),
+                now + chrono::Duration::days(1),
+            )
+            .unwrap();
+        // only task3 has been finished
+        assert_eq!(stats.completed, 1);
+        assert!(stats.lead_time.is_some());
+        assert!(stats.cycle_time.unwrap().p50 <= stats.lead_time.unwrap().p50);
+
+        let timelines = setup.svc.timelines().unwrap();
+        assert!(timelines[1].started_at.is_some());
+        assert!(timelines[1].done_at.is_none());
+        assert!(timelines[0].started_at.is_none());
     }
 
-    pub fn move_to_doing(&mut self, id: u32) -> Result<(), String> {
-        let task = self.repo.find_by_id(id);
-        if task.is_none() {
-            return Err("No task found".to_string());
-        }
+    #[test]
+    fn cumulative_flow_counts_todays_board() {
+        let setup = Setup::new();
+        let today = Utc::now().date_naive();
 
-        let task = task.unwrap();
-        // task.before_move_to_doing()?;
+        let cfd = setup
+            .svc
+            .cumulative_flow(today - chrono::Duration::days(1), today)
+            .unwrap();
+        assert_eq!(cfd.days.len(), 2);
+        assert_eq!(cfd.days[0].total(), 0);
+        assert_eq!(cfd.days[1].counts, vec![1, 1, 1]);
+        assert!(
+            setup
+                .svc
+                .cumulative_flow(today, today.pred_opt().unwrap())
+                .is_err()
+        );
+    }
 
-        self.repo.move_to_doing(id)?;
+    #[test]
+    fn forecasts_use_recent_throughput() {
+        let setup = Setup::new();
+        let today = Utc::now().date_naive();
+        let options = ForecastOptions {
+            history_days: 1,
+            trials: 50,
+            seed: Some(3),
+        };
 
-        // TODO: add converters
+        // one task finished today is the whole sample
+        let items = setup
+            .svc
+            .forecast_items(today + Duration::days(4), &options)
+            .unwrap();
+        assert_eq!((items.days, items.p50, items.p95), (4, 4, 4));
 
-        Ok(())
+        let done = setup.svc.forecast_completion(2, &options).unwrap();
+        assert_eq!(done.p85, today + Duration::days(2));
+
+        assert!(setup.svc.forecast_items(today, &options).is_err());
+        let mut empty = TaskService::new(InMemoryTaskRepository::new()).unwrap();
+        empty.add_task("idle", "").unwrap();
+        assert!(empty.forecast_completion(1, &options).is_err());
     }
 
-    pub fn move_to_done(&mut self, id: u32) -> Result<(), String> {
-        let task = self.repo.find_by_id(id);
-        if task.is_none() {
-            return Err("No task found".to_string());
-        }
+    #[test]
+    fn boards_scope_tasks_and_names() {
+        let mut setup = Setup::new();
+        let ops = setup.svc.create_board("ops", Workflow::default()).unwrap();
+        let ops_id = ops.id.unwrap();
+        assert_eq!(
+            setup
+                .svc
+                .create_board("OPS", Workflow::default())
+                .unwrap_err(),
+            KanbanError::DuplicateBoardName {
+                name: "ops".to_string()
+            }
+        );
+
+        setup.svc.use_board(ops_id).unwrap();
+        // names only need to be unique within a board
+        let task = setup.svc.add_task(TASK_NAME1, TASK_DESCRIPTION1).unwrap();
+        assert_eq!(task.board_id, ops_id);
+        assert_eq!(setup.svc.list(&TaskQuery::new()).unwrap().len(), 1);
+        assert_eq!(
+            setup
+                .svc
+                .list(&TaskQuery::new().board(DEFAULT_BOARD_ID))
+                .unwrap()
+                .len(),
+            3
+        );
+
+        setup.svc.use_board(DEFAULT_BOARD_ID).unwrap();
+        assert_eq!(setup.svc.list(&TaskQuery::new()).unwrap().len(), 3);
+        assert_eq!(
+            setup.svc.use_board(42).unwrap_err(),
+            KanbanError::BoardNotFound { id: 42 }
+        );
+    }
 
-        let task = task.unwrap();
-        // task.before_move_to_done()?;
+    #[test]
+    fn transfer_keeps_history_and_adapts_column() {
+        let mut setup = Setup::new();
+        let workflow = Workflow::linear(vec![Status::new("Open"), Status::DONE]).unwrap();
+        let ops_id = setup.svc.create_board("ops", workflow).unwrap().id.unwrap();
 
-        self.repo.move_to_done(id)?;
+        // Done exists on both boards, Doing does not
+        let task = setup.svc.transfer_task(TASK3_ID, ops_id).unwrap();
+        assert_eq!((task.board_id, task.status), (ops_id, Status::DONE));
+        let task = setup.svc.transfer_task(TASK2_ID, ops_id).unwrap();
+        assert_eq!(task.status.as_str(), "Open");
 
-        Ok(())
+        let history = setup.svc.history(TASK2_ID).unwrap();
+        let kinds: Vec<_> = history.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EventKind::Created,
+                EventKind::Moved,
+                EventKind::Transferred,
+                EventKind::Moved
+            ]
+        );
+        assert_eq!(history[2].new_value.as_deref(), Some("ops"));
+
+        setup.svc.add_task(TASK_NAME2, "").unwrap();
+        let new_id = setup
+            .svc
+            .list_by_status(Status::TODO)
+            .unwrap()
+            .last()
+            .unwrap()
+            .id
+            .unwrap();
+        assert_eq!(
+            setup.svc.transfer_task(new_id, ops_id).unwrap_err(),
+            KanbanError::DuplicateName {
+                name: TASK_NAME2.to_string()
+            }
+        );
     }
 
-    pub fn list_by_status(&mut self, status: Status) -> Vec<&Task> {
-        self.repo.list_by_status(status)
+    #[test]
+    fn boards_can_be_renamed_reconfigured_and_deleted() {
+        let mut setup = Setup::new();
+        let ops_id = setup
+            .svc
+            .create_board("ops", Workflow::default())
+            .unwrap()
+            .id
+            .unwrap();
+
+        assert_eq!(
+            setup.svc.rename_board(ops_id, "operations").unwrap().name,
+            "operations"
+        );
+        assert!(setup.svc.rename_board(ops_id, "default").is_err());
+        assert!(setup.svc.rename_board(ops_id, " ").is_err());
+
+        // Doing still holds task2
+        let narrow = Workflow::linear(vec![Status::TODO, Status::DONE]).unwrap();
+        assert!(
+            setup
+                .svc
+                .set_board_workflow(DEFAULT_BOARD_ID, narrow.clone())
+                .is_err()
+        );
+        setup.svc.use_board(ops_id).unwrap();
+        setup.svc.set_board_workflow(ops_id, narrow).unwrap();
+        assert_eq!(setup.svc.workflow().columns().len(), 2);
+
+        setup.svc.add_task("temp", "").unwrap();
+        assert!(setup.svc.delete_board(ops_id).is_err());
+        assert!(setup.svc.delete_board(DEFAULT_BOARD_ID).is_err());
+
+        let temp = setup.svc.find_board("operations").unwrap().unwrap();
+        let task = setup.svc.list(&TaskQuery::new()).unwrap().remove(0);
+        setup
+            .svc
+            .transfer_task(task.id.unwrap(), DEFAULT_BOARD_ID)
+            .unwrap();
+        setup.svc.delete_board(temp.id.unwrap()).unwrap();
+        assert_eq!(setup.svc.board().id, Some(DEFAULT_BOARD_ID));
+        assert_eq!(setup.svc.boards().unwrap().len(), 1);
     }
 
-    pub fn find_by_id(&mut self, id: u32) -> Option<&mut Task> {
-        match self.repo.find_by_id(id) {
-            Some(task) => Some(task),
-            None => None,
-        }
+    #[test]
+    fn priority_orders_todo_and_next_task() {
+        let mut setup = Setup::new();
+        assert_eq!(setup.svc.next_task().unwrap().unwrap().id, Some(TASK1_ID));
+
+        let urgent = setup
+            .svc
+            .add_task_with_priority("hotfix", "", Priority::Critical)
+            .unwrap();
+        let low = setup
+            .svc
+            .add_task_with_priority("cleanup", "", Priority::Low)
+            .unwrap();
+        assert_eq!(urgent.priority, Priority::Critical);
+
+        let ids: Vec<_> = setup
+            .svc
+            .list_by_status(Status::TODO)
+            .unwrap()
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, vec![urgent.id, Some(TASK1_ID), low.id]);
+        assert_eq!(setup.svc.next_task().unwrap().unwrap().name, "hotfix");
+
+        setup.svc.move_task(urgent.id.unwrap(), "Doing").unwrap();
+        let task = setup
+            .svc
+            .set_priority(low.id.unwrap(), Priority::High)
+            .unwrap();
+        assert!(task.updated_at.is_some());
+        assert_eq!(setup.svc.next_task().unwrap().unwrap().name, "cleanup");
+
+        let history = setup.svc.history(low.id.unwrap()).unwrap();
+        let last = history.last().unwrap();
+        assert_eq!(last.kind, EventKind::Prioritized);
+        assert_eq!(last.old_value.as_deref(), Some("low"));
+        assert_eq!(last.new_value.as_deref(), Some("high"));
+        assert!("urgent".parse::<Priority>().is_err());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::InMemoryTaskRepository;
+    #[test]
+    fn due_dates_drive_overdue_and_late_completion() {
+        let mut setup = Setup::new();
+        let now = Utc::now();
 
-    struct Setup {
-        svc: TaskService<InMemoryTaskRepository>,
+        // backdate task2 so it can be given a due date in the past
+        let mut task = setup.svc.repo.get(TASK2_ID).unwrap().unwrap();
+        task.created_at = now - Duration::days(2);
+        setup.svc.repo.update(task).unwrap();
+
+        let task = setup
+            .svc
+            .set_due_date(TASK2_ID, Some(now - Duration::minutes(1)))
+            .unwrap();
+        assert!(task.is_past_due(now));
+        setup
+            .svc
+            .set_due_date(TASK1_ID, Some(now + Duration::hours(5)))
+            .unwrap();
+        assert!(
+            setup
+                .svc
+                .set_due_date(TASK1_ID, Some(now - Duration::days(1)))
+                .is_err()
+        );
+
+        let ids = |tasks: Vec<Task>| tasks.iter().map(|t| t.id.unwrap()).collect::<Vec<_>>();
+        assert_eq!(ids(setup.svc.overdue_tasks().unwrap()), vec![TASK2_ID]);
+        assert_eq!(
+            ids(setup.svc.due_soon(Duration::days(1)).unwrap()),
+            vec![TASK1_ID]
+        );
+        assert!(setup.svc.due_soon(Duration::hours(1)).unwrap().is_empty());
+
+        let task = setup.svc.move_task(TASK2_ID, "Done").unwrap();
+        assert!(task.completed_late);
+        assert!(setup.svc.overdue_tasks().unwrap().is_empty());
+
+        setup.svc.move_task(TASK1_ID, "Doing").unwrap();
+        assert!(
+            !setup
+                .svc
+                .move_task(TASK1_ID, "Done")
+                .unwrap()
+                .completed_late
+        );
+
+        let history = setup.svc.history(TASK1_ID).unwrap();
+        assert_eq!(history[1].kind, EventKind::Rescheduled);
+        assert_eq!(history[1].old_value, None);
+
+        let stats = setup
+            .svc
+            .stats(now - Duration::days(1), now + Duration::days(1))
+            .unwrap();
+        assert_eq!((stats.due_dated, stats.on_time), (2, 1));
+        assert_eq!(stats.on_time_rate(), Some(0.5));
     }
-    const TASK_NAME1: &str = "task1";
-    const TASK_NAME2: &str = "task2";
-    const TASK_NAME3: &str = "task3";
-    const TASK_DESCRIPTION1: &str = "description1";
-    const TASK_DESCRIPTION2: &str = "description2";
-    const TASK_DESCRIPTION3: &str = "description3";
 
-    // task ids
-    // TODO: ensure ID is i32 and not u32(can accept negative integers)
-    const TASK1_ID: u32 = 1;
-    const TASK2_ID: u32 = 2;
-    const TASK3_ID: u32 = 3;
+    #[test]
+    fn labels_are_added_counted_renamed_and_filtered() {
+        let mut setup = Setup::new();
 
-    impl Setup {
-        fn new() -> Self {
-            let mut svc = TaskService::new(InMemoryTaskRepository::new());
+        let task = setup.svc.add_label(TASK1_ID, " Bug ").unwrap();
+        assert!(task.has_label("bug"));
+        setup.svc.add_label(TASK1_ID, "bug").unwrap();
+        setup.svc.add_label(TASK1_ID, "frontend").unwrap();
+        setup.svc.add_label(TASK2_ID, "frontend").unwrap();
+        setup.svc.add_label(TASK3_ID, "fe").unwrap();
+        assert!(setup.svc.add_label(TASK1_ID, "two words").is_err());
+        assert!(setup.svc.add_label(TASK1_ID, " ").is_err());
 
-            // seed tasks
-            svc.add_task(TASK_NAME1, TASK_DESCRIPTION1)
-                .expect("task not created");
-            svc.add_task(TASK_NAME2, TASK_DESCRIPTION2)
-                .expect("task not created");
-            svc.add_task(TASK_NAME3, TASK_DESCRIPTION3)
-                .expect("task not created");
+        let counts = |svc: &TaskService<InMemoryTaskRepository>| {
+            svc.labels().unwrap().into_iter().collect::<Vec<_>>()
+        };
+        assert_eq!(
+            counts(&setup.svc),
+            vec![
+                ("bug".to_string(), 1),
+                ("fe".to_string(), 1),
+                ("frontend".to_string(), 2)
+            ]
+        );
 
-            // move task2 and task3 to Doing state
-            let _ = svc.repo.move_to_doing(TASK2_ID);
-            let _ = svc.repo.move_to_doing(TASK3_ID);
-            // move task3 to Done state
-            let _ = svc.repo.move_to_done(TASK3_ID);
-            Setup { svc }
-        }
+        assert_eq!(setup.svc.rename_label("fe", "Frontend").unwrap(), 1);
+        assert_eq!(
+            counts(&setup.svc),
+            vec![("bug".to_string(), 1), ("frontend".to_string(), 3)]
+        );
+
+        let all = setup
+            .svc
+            .list(&TaskQuery::new().label("frontend").label("bug"))
+            .unwrap();
+        assert_eq!(all.len(), 1);
+        let any = setup
+            .svc
+            .list(
+                &TaskQuery::new()
+                    .status(Status::TODO)
+                    .status(Status::DOING)
+                    .any_label("bug")
+                    .any_label("frontend"),
+            )
+            .unwrap();
+        assert_eq!(any.len(), 2);
+
+        setup.svc.remove_label(TASK1_ID, "BUG").unwrap();
+        setup.svc.remove_label(TASK1_ID, "bug").unwrap();
+        assert!(!setup.svc.labels().unwrap().contains_key("bug"));
+
+        let kinds: Vec<_> = setup
+            .svc
+            .history(TASK1_ID)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EventKind::Created,
+                EventKind::Labeled,
+                EventKind::Labeled,
+                EventKind::Unlabeled
+            ]
+        );
+        let renamed = setup.svc.history(TASK3_ID).unwrap().pop().unwrap();
+        assert_eq!(renamed.old_value.as_deref(), Some("fe"));
+        assert_eq!(renamed.new_value.as_deref(), Some("frontend"));
     }
+
     #[test]
-    fn test_add_task_succeeds() {
+    fn assignees_drive_workload_and_required_assignment() {
         let mut setup = Setup::new();
-        let new_task_name = "new task";
-        let new_description = "new description";
-        let new_task = setup.svc.add_task(new_task_name, "new task description");
-        assert!(new_task.is_ok());
+        let alice = setup.svc.add_user("alice", Some(1)).unwrap();
+        let bob = setup.svc.add_user("bob", None).unwrap();
+        let (alice_id, bob_id) = (alice.id.unwrap(), bob.id.unwrap());
+        assert!(matches!(
+            setup.svc.add_user("ALICE", None),
+            Err(KanbanError::DuplicateUserName { .. })
+        ));
+        assert!(setup.svc.add_user("carol", Some(0)).is_err());
+        assert_eq!(setup.svc.find_user("Bob").unwrap(), Some(bob));
 
-        let new_task = new_task.unwrap();
-        assert_eq!(new_task.name, new_task_name);
+        setup.svc.assign(TASK2_ID, alice_id).unwrap();
+        setup.svc.assign(TASK2_ID, bob_id).unwrap();
+        let task = setup.svc.assign(TASK3_ID, alice_id).unwrap();
+        assert_eq!(task.assignees.len(), 1);
+        assert!(matches!(
+            setup.svc.assign(TASK1_ID, 99),
+            Err(KanbanError::UserNotFound { id: 99 })
+        ));
+
+        // task3 is done, so only task2 counts as in progress
+        let workload = setup.svc.workload().unwrap();
+        assert_eq!(workload[0].in_progress, 1);
+        assert!(workload[0].is_at_limit() && !workload[0].is_over_limit());
+        assert!(!workload[1].is_at_limit());
+
+        setup
+            .svc
+            .set_board_workflow(DEFAULT_BOARD_ID, Workflow::default().requiring_assignee())
+            .unwrap();
+        assert!(matches!(
+            setup.svc.move_task(TASK1_ID, "Doing"),
+            Err(KanbanError::Validation {
+                field: "assignee",
+                ..
+            })
+        ));
+        setup.svc.assign(TASK1_ID, alice_id).unwrap();
+        setup.svc.move_task(TASK1_ID, "Doing").unwrap();
+        assert!(setup.svc.workload().unwrap()[0].is_over_limit());
+
+        setup.svc.unassign(TASK2_ID, alice_id).unwrap();
+        setup.svc.set_user_wip_limit(alice_id, Some(2)).unwrap();
+        assert!(!setup.svc.workload().unwrap()[0].is_at_limit());
+        assert_eq!(
+            setup
+                .svc
+                .list(&TaskQuery::new().assigned_to(bob_id))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let history = setup.svc.history(TASK2_ID).unwrap();
+        let last = history.last().unwrap();
+        assert_eq!(last.kind, EventKind::Unassigned);
+        assert_eq!(last.old_value.as_deref(), Some("alice"));
     }
 
     #[test]
-    fn test_add_task_fails_with_duplicate_task_name() {
+    fn checklist_and_subtasks_roll_up_and_guard_done() {
         let mut setup = Setup::new();
-        let res = setup.svc.add_task(TASK_NAME1, TASK_DESCRIPTION1);
-        assert!(res.is_err());
 
-        let err = res.expect_err("should return an error");
+        setup
+            .svc
+            .add_checklist_item(TASK2_ID, "write tests")
+            .unwrap();
+        setup
+            .svc
+            .add_checklist_item(TASK2_ID, "update docs")
+            .unwrap();
+        assert!(setup.svc.add_checklist_item(TASK2_ID, "  ").is_err());
+        let child = setup.svc.add_subtask(TASK2_ID, "review", "").unwrap();
+        assert_eq!(child.parent_id, Some(TASK2_ID));
+        assert_eq!(child.status, Status::TODO);
+
+        let progress = setup.svc.progress(TASK2_ID).unwrap();
+        assert_eq!((progress.done, progress.total), (0, 3));
+        assert_eq!(progress.percent(), Some(0));
+        assert_eq!(setup.svc.progress(TASK1_ID).unwrap().percent(), None);
+
+        assert!(matches!(
+            setup.svc.move_task(TASK2_ID, "Done"),
+            Err(KanbanError::Validation {
+                field: "checklist",
+                ..
+            })
+        ));
+
+        setup
+            .svc
+            .set_checklist_item_done(TASK2_ID, 0, true)
+            .unwrap();
+        setup.svc.remove_checklist_item(TASK2_ID, 1).unwrap();
+        assert!(
+            setup
+                .svc
+                .set_checklist_item_done(TASK2_ID, 1, true)
+                .is_err()
+        );
+        assert_eq!(setup.svc.progress(TASK2_ID).unwrap().percent(), Some(50));
+
+        let child_id = child.id.unwrap();
+        setup.svc.move_task(child_id, "Doing").unwrap();
+        setup.svc.move_task(child_id, "Done").unwrap();
+        assert!(setup.svc.progress(TASK2_ID).unwrap().is_complete());
+        setup.svc.move_task(TASK2_ID, "Done").unwrap();
+
+        let history = setup.svc.history(TASK2_ID).unwrap();
+        let checked = history
+            .iter()
+            .find(|e| e.old_value.as_deref() == Some("[ ] write tests"))
+            .unwrap();
+        assert_eq!(checked.kind, EventKind::ChecklistChanged);
+        assert_eq!(checked.new_value.as_deref(), Some("[x] write tests"));
+    }
+
+    #[test]
+    fn subtask_guard_is_configurable_and_parents_cannot_cycle() {
+        let mut setup = Setup::new();
+        let child = setup.svc.add_subtask(TASK2_ID, "child", "").unwrap();
+        let child_id = child.id.unwrap();
+
+        assert!(setup.svc.set_parent(TASK2_ID, Some(child_id)).is_err());
+        assert!(setup.svc.set_parent(TASK2_ID, Some(TASK2_ID)).is_err());
+        setup.svc.set_parent(TASK1_ID, Some(child_id)).unwrap();
+        assert_eq!(setup.svc.subtasks(child_id).unwrap().len(), 1);
+        assert!(setup.svc.set_parent(TASK2_ID, Some(TASK1_ID)).is_err());
+
+        setup
+            .svc
+            .set_board_workflow(
+                DEFAULT_BOARD_ID,
+                Workflow::default().allowing_incomplete_subtasks(),
+            )
+            .unwrap();
+        setup.svc.move_task(TASK2_ID, "Done").unwrap();
+
+        setup.svc.set_parent(TASK1_ID, None).unwrap();
+        assert!(setup.svc.subtasks(child_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn dependencies_block_start_until_done() {
+        let mut setup = Setup::new();
+        let deploy = setup.svc.add_task("deploy", "").unwrap().id.unwrap();
+
+        setup.svc.add_dependency(TASK1_ID, deploy).unwrap();
+        setup.svc.add_dependency(TASK3_ID, deploy).unwrap();
+        let task = setup.svc.add_dependency(TASK1_ID, deploy).unwrap();
+        assert_eq!(task.blocked_by.len(), 2);
+        assert!(matches!(
+            setup.svc.add_dependency(TASK1_ID, 99),
+            Err(KanbanError::NotFound { id: 99 })
+        ));
+
+        // task3 is done, task1 is not
         assert_eq!(
-            err.as_str(),
-            format!("Task with name '{}' already exists", TASK_NAME1)
+            setup.svc.move_task(deploy, "Doing").unwrap_err(),
+            KanbanError::Blocked {
+                id: deploy,
+                blockers: vec![TASK1_ID]
+            }
         );
+        assert_eq!(setup.svc.blockers(deploy).unwrap().len(), 2);
+        assert!(setup.svc.dependency_graph().unwrap().is_blocked(deploy));
+
+        setup.svc.move_task(TASK1_ID, "Doing").unwrap();
+        setup.svc.move_task(TASK1_ID, "Done").unwrap();
+        setup.svc.move_task(deploy, "Doing").unwrap();
+
+        setup.svc.remove_dependency(TASK3_ID, deploy).unwrap();
+        let history = setup.svc.history(deploy).unwrap();
+        assert_eq!(history[1].kind, EventKind::DependencyAdded);
+        let removed = history.last().unwrap();
+        assert_eq!(removed.kind, EventKind::DependencyRemoved);
+        assert_eq!(removed.old_value.as_deref(), Some("3"));
     }
 
     #[test]
-    fn validation_errors_are_caught() {
+    fn dependency_cycles_are_refused() {
         let mut setup = Setup::new();
-        let task1 = Task::new("".to_string(), "description1".to_string());
-        let res = setup.svc.add_task("", "description");
-        assert!(res.is_err());
 
-        let err = res.expect_err("should return an error");
-        assert_eq!(err.as_str(), "Task name is required");
+        setup.svc.add_dependency(TASK1_ID, TASK2_ID).unwrap();
+        setup.svc.add_dependency(TASK2_ID, TASK3_ID).unwrap();
+        assert!(setup.svc.add_dependency(TASK1_ID, TASK1_ID).is_err());
+        let err = setup.svc.add_dependency(TASK3_ID, TASK1_ID).unwrap_err();
+        assert_eq!(
+            err,
+            KanbanError::Validation {
+                field: "dependency",
+                reason: String::from("task 1 already blocks task 3 (1 -> 2 -> 3)")
+            }
+        );
+        setup.svc.add_dependency(TASK1_ID, TASK3_ID).unwrap();
+
+        let graph = setup.svc.dependency_graph().unwrap();
+        assert_eq!(graph.edges, vec![(1, 2), (1, 3), (2, 3)]);
+        assert!(graph.to_dot().contains(r#""1" -> "2";"#));
     }
 
     #[test]
-    fn move_to_doing_succeeds() {
+    fn comments_keep_revisions_and_history() {
         let mut setup = Setup::new();
-        let res = setup.svc.move_to_doing(TASK1_ID);
-        assert!(res.is_ok());
+        setup.svc.set_actor("alice");
 
-        // find task
-        let task1 = setup.svc.find_by_id(TASK1_ID).unwrap();
-        // confirm it moved to Doing state
-        assert_eq!(task1.status, Status::Doing);
+        let comment = setup
+            .svc
+            .add_comment(TASK1_ID, "  needs **review**\n")
+            .unwrap();
+        assert_eq!(comment.author, "alice");
+        assert_eq!(comment.body, "needs **review**");
+        assert!(setup.svc.add_comment(TASK1_ID, "   ").is_err());
+        assert_eq!(
+            setup.svc.add_comment(99, "hi").unwrap_err(),
+            KanbanError::NotFound { id: 99 }
+        );
+
+        setup.svc.set_actor("bob");
+        let id = comment.id.unwrap();
+        let edited = setup.svc.edit_comment(id, "reviewed").unwrap();
+        assert_eq!(edited.author, "alice");
+        assert_eq!(edited.edited_by.as_deref(), Some("bob"));
+        assert_eq!(edited.revisions.len(), 1);
+        assert_eq!(edited.revisions[0].body, "needs **review**");
+        assert_eq!(edited.revisions[0].author, "alice");
+        // an edit that changes nothing keeps no revision
+        let unchanged = setup.svc.edit_comment(id, "reviewed").unwrap();
+        assert_eq!(unchanged.revisions.len(), 1);
+        assert!(setup.svc.edit_comment(id, "").is_err());
+
+        setup.svc.add_comment(TASK1_ID, "second").unwrap();
+        assert_eq!(setup.svc.comments(TASK1_ID).unwrap().len(), 2);
+
+        setup.svc.delete_comment(id).unwrap();
+        assert_eq!(
+            setup.svc.delete_comment(id).unwrap_err(),
+            KanbanError::CommentNotFound { id }
+        );
+        let bodies: Vec<_> = setup
+            .svc
+            .comments(TASK1_ID)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.body)
+            .collect();
+        assert_eq!(bodies, vec!["second"]);
+
+        let events: Vec<_> = setup
+            .svc
+            .history(TASK1_ID)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.kind != EventKind::Created)
+            .map(|e| (e.kind, e.old_value, e.new_value, e.actor))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (
+                    EventKind::Commented,
+                    None,
+                    Some("needs **review**".to_string()),
+                    "alice".to_string()
+                ),
+                (
+                    EventKind::CommentEdited,
+                    Some("needs **review**".to_string()),
+                    Some("reviewed".to_string()),
+                    "bob".to_string()
+                ),
+                (
+                    EventKind::Commented,
+                    None,
+                    Some("second".to_string()),
+                    "bob".to_string()
+                ),
+                (
+                    EventKind::CommentDeleted,
+                    Some("reviewed".to_string()),
+                    None,
+                    "bob".to_string()
+                ),
+            ]
+        );
     }
 
     #[test]
-    fn move_to_doing_fails() {
+    fn update_task_applies_patch_and_records_changes() {
         let mut setup = Setup::new();
-        // task of id TASK3_ID(3) is already in the done state
-        // cannot move to doing state
-        let res = setup.svc.move_to_doing(TASK3_ID);
-        assert!(res.is_err());
 
-        let err = res.expect_err("should return an error");
+        let before = setup.svc.find_by_id(TASK1_ID).unwrap().unwrap();
+        let due = before.created_at + Duration::days(2);
+        let patch = TaskPatch::new()
+            .name("  renamed ")
+            .description("new description")
+            .priority(Priority::High)
+            .due(due);
+        let task = setup.svc.update_task(TASK1_ID, patch).unwrap();
+        assert_eq!(task.name, "renamed");
+        assert_eq!(task.description, "new description");
+        assert_eq!(task.priority, Priority::High);
+        assert_eq!(task.due_at, Some(due));
+        assert!(task.updated_at.is_some());
+        assert_eq!(
+            setup.svc.find_by_id(TASK1_ID).unwrap().unwrap().name,
+            "renamed"
+        );
+
+        let kinds: Vec<_> = setup
+            .svc
+            .history(TASK1_ID)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.kind)
+            .collect();
         assert_eq!(
-            err.as_str(),
-            "Task must be in the Todo state before marking as in progress"
+            kinds,
+            vec![
+                EventKind::Created,
+                EventKind::Renamed,
+                EventKind::Described,
+                EventKind::Prioritized,
+                EventKind::Rescheduled
+            ]
         );
+
+        // a patch that changes nothing leaves the task and history alone
+        let same = setup
+            .svc
+            .update_task(TASK1_ID, TaskPatch::new().name("renamed"))
+            .unwrap();
+        assert_eq!(same.updated_at, task.updated_at);
+        assert_eq!(setup.svc.history(TASK1_ID).unwrap().len(), 5);
+
+        // only the fields in the patch change
+        let task = setup.svc.describe_task(TASK1_ID, "").unwrap();
+        assert_eq!(task.name, "renamed");
+        assert_eq!(task.priority, Priority::High);
+        let task = setup
+            .svc
+            .update_task(TASK1_ID, TaskPatch::new().clear_due())
+            .unwrap();
+        assert_eq!(task.due_at, None);
     }
 
     #[test]
-    fn move_to_done_succeeds() {
+    fn update_task_validates_patch() {
         let mut setup = Setup::new();
-        // create task
-        let res = setup.svc.move_to_done(TASK2_ID);
-        assert!(res.is_ok());
 
-        // find task
-        let task2 = setup.svc.find_by_id(TASK2_ID).unwrap();
-        // confirm it moved to Done state
-        assert_eq!(task2.status, Status::Done);
+        assert!(matches!(
+            setup.svc.rename_task(TASK1_ID, "   "),
+            Err(KanbanError::Validation { field: "name", .. })
+        ));
+        assert_eq!(
+            setup.svc.rename_task(TASK1_ID, "TASK2").unwrap_err(),
+            KanbanError::DuplicateName {
+                name: TASK_NAME2.to_string()
+            }
+        );
+        // changing only the case of its own name is fine
+        assert_eq!(
+            setup.svc.rename_task(TASK1_ID, "Task1").unwrap().name,
+            "Task1"
+        );
+
+        let created_at = setup.svc.find_by_id(TASK1_ID).unwrap().unwrap().created_at;
+        let patch = TaskPatch::new()
+            .description("ignored")
+            .due(created_at - Duration::days(1));
+        assert!(matches!(
+            setup.svc.update_task(TASK1_ID, patch),
+            Err(KanbanError::Validation {
+                field: "due_at",
+                ..
+            })
+        ));
+        assert_eq!(
+            setup.svc.find_by_id(TASK1_ID).unwrap().unwrap().description,
+            TASK_DESCRIPTION1
+        );
+        assert_eq!(
+            setup.svc.rename_task(99, "ghost").unwrap_err(),
+            KanbanError::NotFound { id: 99 }
+        );
     }
 
     #[test]
-    fn move_to_done_fails() {
+    fn archived_tasks_leave_the_board_until_restored() {
         let mut setup = Setup::new();
-        // task of id TASK3_ID(3) is already in the done state
-        // ONLY tasks in progress(Doing state) can be marked as Done
-        let res = setup.svc.move_to_done(TASK1_ID);
-        assert!(res.is_err());
 
-        let err = res.expect_err("should return an error");
+        let task = setup.svc.archive_task(TASK2_ID).unwrap();
+        assert!(task.is_archived());
+        assert!(setup.svc.archive_task(TASK2_ID).is_err());
+        assert!(setup.svc.list_by_status(Status::DOING).unwrap().is_empty());
+        let archived: Vec<_> = setup
+            .svc
+            .archived_tasks()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(archived, vec![Some(TASK2_ID)]);
+        assert!(matches!(
+            setup.svc.move_task(TASK2_ID, "Done"),
+            Err(KanbanError::Validation { field: "task", .. })
+        ));
+
+        // archived tasks still count for the board's analytics and keep it
+        // from being deleted
+        assert_eq!(setup.svc.timelines().unwrap().len(), 3);
+
+        let task = setup.svc.restore_task(TASK2_ID).unwrap();
+        assert!(!task.is_archived());
+        assert_eq!(task.status, Status::DOING);
+        assert!(setup.svc.restore_task(TASK2_ID).is_err());
+        assert!(setup.svc.archived_tasks().unwrap().is_empty());
+
+        let kinds: Vec<_> = setup
+            .svc
+            .history(TASK2_ID)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.kind)
+            .skip(2)
+            .collect();
+        assert_eq!(kinds, vec![EventKind::Archived, EventKind::Restored]);
+    }
+
+    #[test]
+    fn boards_with_archived_tasks_cannot_be_deleted() {
+        let mut setup = Setup::new();
+
+        let board = setup.svc.create_board("ops", Workflow::default()).unwrap();
+        let board_id = board.id.unwrap();
+        setup.svc.transfer_task(TASK1_ID, board_id).unwrap();
+        setup.svc.archive_task(TASK1_ID).unwrap();
+
+        assert!(setup.svc.delete_board(board_id).is_err());
+        setup.svc.delete_task(TASK1_ID).unwrap();
+        setup.svc.delete_board(board_id).unwrap();
+    }
+
+    #[test]
+    fn delete_task_removes_it_and_links_to_it() {
+        let mut setup = Setup::new();
+
+        let child = setup.svc.add_subtask(TASK1_ID, "child", "").unwrap();
+        let child_id = child.id.unwrap();
+        setup.svc.add_dependency(TASK1_ID, TASK2_ID).unwrap();
+        setup.svc.add_comment(TASK1_ID, "bye").unwrap();
+
+        let removed = setup.svc.delete_task(TASK1_ID).unwrap();
+        assert_eq!(removed.name, TASK_NAME1);
+        assert!(setup.svc.find_by_id(TASK1_ID).unwrap().is_none());
+        assert_eq!(
+            setup.svc.history(TASK1_ID).unwrap_err(),
+            KanbanError::NotFound { id: TASK1_ID }
+        );
+        assert_eq!(
+            setup.svc.delete_task(TASK1_ID).unwrap_err(),
+            KanbanError::NotFound { id: TASK1_ID }
+        );
+
+        assert_eq!(
+            setup.svc.find_by_id(child_id).unwrap().unwrap().parent_id,
+            None
+        );
+        assert!(
+            setup
+                .svc
+                .find_by_id(TASK2_ID)
+                .unwrap()
+                .unwrap()
+                .blocked_by
+                .is_empty()
+        );
+        let last = setup.svc.history(TASK2_ID).unwrap().pop().unwrap();
+        assert_eq!(last.kind, EventKind::DependencyRemoved);
+
+        // the deleted task's ID is not handed out again
+        setup.svc.delete_task(child_id).unwrap();
+        let task = setup.svc.add_task("fresh", "").unwrap();
+        assert_eq!(task.id, Some(child_id + 1));
+    }
+
+    #[test]
+    fn reopen_and_stop_send_tasks_back_with_a_reason() {
+        let mut setup = Setup::new();
+
+        assert!(matches!(
+            setup.svc.reopen_task(TASK2_ID, None, "not done yet"),
+            Err(KanbanError::Validation { field: "task", .. })
+        ));
+        assert!(matches!(
+            setup.svc.reopen_task(TASK3_ID, None, "  "),
+            Err(KanbanError::Validation {
+                field: "reason",
+                ..
+            })
+        ));
+        assert!(matches!(
+            setup.svc.reopen_task(TASK3_ID, Some("Done"), "again"),
+            Err(KanbanError::Validation {
+                field: "column",
+                ..
+            })
+        ));
+
+        let task = setup.svc.reopen_task(TASK3_ID, None, "bug found").unwrap();
+        assert_eq!(task.status, Status::DOING);
+        let event = setup.svc.history(TASK3_ID).unwrap().pop().unwrap();
+        assert_eq!(event.kind, EventKind::Reopened);
+        assert_eq!(event.old_value.as_deref(), Some("Done"));
+        assert_eq!(event.new_value.as_deref(), Some("Doing"));
+        assert_eq!(event.reason.as_deref(), Some("bug found"));
+
+        let task = setup.svc.stop_task(TASK3_ID, "waiting on design").unwrap();
+        assert_eq!(task.status, Status::TODO);
+        assert!(setup.svc.stop_task(TASK3_ID, "again").is_err());
+        assert!(setup.svc.stop_task(TASK1_ID, "never started").is_err());
+
+        setup.svc.move_task(TASK3_ID, "Doing").unwrap();
+        setup.svc.move_task(TASK3_ID, "Done").unwrap();
+        let timeline = setup
+            .svc
+            .timelines()
+            .unwrap()
+            .into_iter()
+            .find(|t| t.task_id == TASK3_ID)
+            .unwrap();
+        assert_eq!(timeline.reopened, 1);
+        let stats = setup
+            .svc
+            .stats(
+                Utc::now() - Duration::hours(1),
+                Utc::now() + Duration::hours(1),
+            )
+            .unwrap();
+        assert_eq!(stats.reworked, 1);
+    }
+
+    #[test]
+    fn reopening_respects_wip_limits() {
+        let workflow = Workflow::default().with_wip_limit("Todo", 1).unwrap();
+        let mut svc = TaskService::with_workflow(InMemoryTaskRepository::new(), workflow).unwrap();
+        svc.add_task(TASK_NAME1, "").unwrap();
+        svc.move_task(TASK1_ID, "Doing").unwrap();
+        svc.add_task(TASK_NAME2, "").unwrap();
+
+        assert!(matches!(
+            svc.stop_task(TASK1_ID, "wrong task"),
+            Err(KanbanError::WipLimitExceeded { limit: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn blocked_task_cannot_finish_until_unblocked() {
+        let mut setup = Setup::new();
+
+        assert!(setup.svc.block_task(TASK1_ID, "not started").is_err());
+        assert!(setup.svc.block_task(TASK3_ID, "already done").is_err());
+        assert!(matches!(
+            setup.svc.block_task(TASK2_ID, " "),
+            Err(KanbanError::Validation {
+                field: "reason",
+                ..
+            })
+        ));
+
+        let task = setup.svc.block_task(TASK2_ID, " waiting on ops ").unwrap();
+        assert_eq!(task.blocked.unwrap().reason, "waiting on ops");
+        assert!(setup.svc.block_task(TASK2_ID, "again").is_err());
+        assert_eq!(setup.svc.blocked_tasks().unwrap().len(), 1);
+        assert!(matches!(
+            setup.svc.move_task(TASK2_ID, "Done"),
+            Err(KanbanError::Validation { field: "task", .. })
+        ));
+
+        let task = setup.svc.unblock_task(TASK2_ID).unwrap();
+        assert_eq!(task.blocked, None);
+        assert!(setup.svc.unblock_task(TASK2_ID).is_err());
+        assert!(setup.svc.blocked_tasks().unwrap().is_empty());
+        setup.svc.move_task(TASK2_ID, "Done").unwrap();
+
+        let kinds: Vec<_> = setup
+            .svc
+            .history(TASK2_ID)
+            .unwrap()
+            .into_iter()
+            .map(|event| event.kind)
+            .collect();
         assert_eq!(
-            err.as_str(),
-            "Task must be in progress state before marking as Done"
+            kinds[kinds.len() - 3..],
+            [EventKind::Blocked, EventKind::Unblocked, EventKind::Moved]
         );
+        let timeline = setup
+            .svc
+            .timelines()
+            .unwrap()
+            .into_iter()
+            .find(|t| t.task_id == TASK2_ID)
+            .unwrap();
+        assert_eq!(timeline.blocked.len(), 1);
+        assert!(timeline.blocked[0].until.is_some());
+        assert!(setup.svc.blocked_time(TASK2_ID).unwrap() >= Duration::zero());
+        assert!(setup.svc.blocked_time(99).is_err());
+    }
+
+    #[test]
+    fn stopping_a_blocked_task_clears_the_flag() {
+        let mut setup = Setup::new();
+        setup.svc.block_task(TASK2_ID, "waiting on design").unwrap();
+
+        let task = setup.svc.stop_task(TASK2_ID, "dropped for now").unwrap();
+        assert_eq!(task.blocked, None);
+        let history = setup.svc.history(TASK2_ID).unwrap();
+        let unblocked = &history[history.len() - 2];
+        assert_eq!(unblocked.kind, EventKind::Unblocked);
+        assert_eq!(unblocked.old_value.as_deref(), Some("waiting on design"));
+        assert!(setup.svc.blocked_tasks().unwrap().is_empty());
+    }
+
+    #[test]
+    fn list_by_status_returns_matching_tasks() {
+        let setup = Setup::new();
+        assert_eq!(setup.svc.list_by_status(Status::TODO).unwrap().len(), 1);
+        assert_eq!(setup.svc.list_by_status(Status::DOING).unwrap().len(), 1);
+        assert_eq!(setup.svc.list_by_status(Status::DONE).unwrap().len(), 1);
+        assert_eq!(setup.svc.list(&TaskQuery::new()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn list_accepts_query() {
+        let setup = Setup::new();
+
+        let query = TaskQuery::new()
+            .status(Status::new("todo"))
+            .status(Status::new("DOING"))
+            .sort_by(crate::query::SortKey::Name)
+            .descending();
+        let names: Vec<_> = setup
+            .svc
+            .list(&query)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec![TASK_NAME2, TASK_NAME1]);
+    }
+
+    #[test]
+    fn failed_save_keeps_neither_change_nor_history() {
+        let dir = TempDir::new().unwrap();
+        let repo = JsonFileTaskRepository::open(dir.path().join("board.json")).unwrap();
+        let mut svc = TaskService::new(repo).unwrap();
+        let id = svc.add_task("deploy", "").unwrap().id.unwrap();
+
+        // a directory in the way of the temp file makes the next save fail
+        std::fs::create_dir(dir.path().join("board.json.tmp")).unwrap();
+        assert!(matches!(
+            svc.move_task(id, "Doing"),
+            Err(KanbanError::Storage { .. })
+        ));
+
+        assert_eq!(svc.find_by_id(id).unwrap().unwrap().status, Status::TODO);
+        assert_eq!(svc.history(id).unwrap().len(), 1);
     }
 }
@@ -0,0 +1,1117 @@
+//! SQLite backed task repository.
+//!
+//! The database schema is versioned through SQLite's `user_version` pragma.
+//! Every entry in [`MIGRATIONS`] upgrades the schema by one version and is
+//! applied inside its own transaction when the database is opened, so older
+//! databases are brought up to date automatically.
+//!
+//! Timestamps are stored as RFC 3339 text in UTC with full sub-second
+//! precision, which keeps them sortable and round-trips `chrono` values
+//! exactly. Tables use `AUTOINCREMENT` keys, so the IDs of deleted rows are
+//! never handed out again. Board workflows, task labels, assignees,
+//! checklists and blockers, and comment revisions are stored as JSON text.
+
+use std::path::Path;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, Row, params, params_from_iter};
+
+use crate::board::Board;
+use crate::comment::Comment;
+use crate::error::{KanbanError, Result};
+use crate::history::{EventKind, TaskEvent};
+use crate::query::TaskQuery;
+use crate::repository::TaskRepository;
+use crate::user::User;
+use crate::{BlockedFlag, Priority, Status, Task};
+
+/// Schema migrations; the migration at index `n` upgrades version `n` to `n + 1`.
+const MIGRATIONS: &[&str] = &[
+    // 1: tasks table
+    "CREATE TABLE tasks (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        name        TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status      TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        updated_at  TEXT
+    );",
+    // 2: lookups by status and (case-insensitive) name
+    "CREATE INDEX idx_tasks_status ON tasks (status);
+     CREATE INDEX idx_tasks_name ON tasks (name COLLATE NOCASE);",
+    // 3: expedite flag for tasks moved past a WIP limit
+    "ALTER TABLE tasks ADD COLUMN expedited INTEGER NOT NULL DEFAULT 0;",
+    // 4: per-task audit history
+    "CREATE TABLE task_events (
+        id        INTEGER PRIMARY KEY AUTOINCREMENT,
+        task_id   INTEGER NOT NULL,
+        kind      TEXT NOT NULL,
+        old_value TEXT,
+        new_value TEXT,
+        at        TEXT NOT NULL,
+        actor     TEXT NOT NULL
+    );
+    CREATE INDEX idx_task_events_task ON task_events (task_id);",
+    // 5: boards; existing tasks belong to the default board
+    "CREATE TABLE boards (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        name       TEXT NOT NULL,
+        workflow   TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    ALTER TABLE tasks ADD COLUMN board_id INTEGER NOT NULL DEFAULT 1;
+    CREATE INDEX idx_tasks_board ON tasks (board_id);",
+    // 6: task priority
+    "ALTER TABLE tasks ADD COLUMN priority TEXT NOT NULL DEFAULT 'medium';",
+    // 7: due dates and late completion
+    "ALTER TABLE tasks ADD COLUMN due_at TEXT;
+     ALTER TABLE tasks ADD COLUMN completed_late INTEGER NOT NULL DEFAULT 0;",
+    // 8: task labels as a JSON array
+    "ALTER TABLE tasks ADD COLUMN labels TEXT NOT NULL DEFAULT '[]';",
+    // 9: users and task assignees as a JSON array of user IDs
+    "CREATE TABLE users (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        name       TEXT NOT NULL,
+        wip_limit  INTEGER,
+        created_at TEXT NOT NULL
+    );
+    ALTER TABLE tasks ADD COLUMN assignees TEXT NOT NULL DEFAULT '[]';",
+    // 10: checklists as a JSON array and subtask links
+    "ALTER TABLE tasks ADD COLUMN checklist TEXT NOT NULL DEFAULT '[]';
+     ALTER TABLE tasks ADD COLUMN parent_id INTEGER;",
+    // 11: dependencies as a JSON array of blocking task IDs
+    "ALTER TABLE tasks ADD COLUMN blocked_by TEXT NOT NULL DEFAULT '[]';",
+    // 12: task comments with their earlier revisions as a JSON array
+    "CREATE TABLE comments (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        task_id    INTEGER NOT NULL,
+        author     TEXT NOT NULL,
+        body       TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT,
+        edited_by  TEXT,
+        revisions  TEXT NOT NULL DEFAULT '[]'
+    );
+    CREATE INDEX idx_comments_task ON comments (task_id);",
+    // 13: archived tasks
+    "ALTER TABLE tasks ADD COLUMN archived_at TEXT;",
+    // 14: reasons given for changes such as reopening a task
+    "ALTER TABLE task_events ADD COLUMN reason TEXT;",
+    // 15: blocked flag
+    "ALTER TABLE tasks ADD COLUMN blocked_reason TEXT;
+     ALTER TABLE tasks ADD COLUMN blocked_since TEXT;",
+];
+
+const TASK_COLUMNS: &str = "id, name, description, status, created_at, updated_at, expedited, \
+                            board_id, priority, due_at, completed_late, labels, assignees, \
+                            checklist, parent_id, blocked_by, archived_at, blocked_reason, \
+                            blocked_since";
+
+const BOARD_COLUMNS: &str = "id, name, workflow, created_at";
+
+const USER_COLUMNS: &str = "id, name, wip_limit, created_at";
+
+const COMMENT_COLUMNS: &str =
+    "id, task_id, author, body, created_at, updated_at, edited_by, revisions";
+
+/// Task repository persisted in a SQLite database.
+pub struct SqliteTaskRepository {
+    conn: Connection,
+}
+
+impl SqliteTaskRepository {
+    /// Opens (or creates) the database at `path` and migrates it to the
+    /// latest schema version.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Creates a throwaway database that lives only in memory.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self> {
+        register_functions(&conn)?;
+        migrate(&mut conn)?;
+        let mut repo = Self { conn };
+
+        if repo.list_boards()?.is_empty() {
+            repo.insert_board(Board::default())?;
+        }
+
+        Ok(repo)
+    }
+
+    /// Current schema version of the database.
+    pub fn schema_version(&self) -> Result<usize> {
+        schema_version(&self.conn)
+    }
+}
+
+/// Applies every migration newer than the database's current version.
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let current = schema_version(conn)?;
+    if current > MIGRATIONS.len() {
+        return Err(KanbanError::Storage {
+            reason: format!(
+                "database schema version {} is newer than supported version {}",
+                current,
+                MIGRATIONS.len()
+            ),
+        });
+    }
+
+    for (version, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", (version + 1) as i64)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Registers `unicode_lower`, which lowercases text the way Rust does.
+/// SQLite's own `lower()` and `NOCASE` only fold ASCII letters, while the
+/// other backends compare names with [`str::to_lowercase`].
+fn register_functions(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "unicode_lower",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| Ok(ctx.get::<String>(0)?.to_lowercase()),
+    )?;
+
+    Ok(())
+}
+
+fn schema_version(conn: &Connection) -> Result<usize> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    Ok(version as usize)
+}
+
+fn format_timestamp(ts: &DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn parse_timestamp(idx: usize, raw: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|ts| ts.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, e.into())
+        })
+}
+
+fn task_from_row(row: &Row<'_>) -> rusqlite::Result<Task> {
+    let status: String = row.get(3)?;
+    let created_at: String = row.get(4)?;
+    let updated_at: Option<String> = row.get(5)?;
+    let priority: String = row.get(8)?;
+    let due_at: Option<String> = row.get(9)?;
+    let labels: String = row.get(11)?;
+    let assignees: String = row.get(12)?;
+    let checklist: String = row.get(13)?;
+    let blocked_by: String = row.get(15)?;
+    let archived_at: Option<String> = row.get(16)?;
+    let blocked_reason: Option<String> = row.get(17)?;
+    let blocked_since: Option<String> = row.get(18)?;
+
+    Ok(Task {
+        id: Some(row.get(0)?),
+        name: row.get(1)?,
+        description: row.get(2)?,
+        status: Status::new(status),
+        created_at: parse_timestamp(4, &created_at)?,
+        updated_at: updated_at.map(|raw| parse_timestamp(5, &raw)).transpose()?,
+        expedited: row.get(6)?,
+        board_id: row.get(7)?,
+        priority: priority.parse::<Priority>().map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(8, rusqlite::types::Type::Text, e.into())
+        })?,
+        due_at: due_at.map(|raw| parse_timestamp(9, &raw)).transpose()?,
+        completed_late: row.get(10)?,
+        labels: serde_json::from_str(&labels).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(11, rusqlite::types::Type::Text, e.into())
+        })?,
+        assignees: serde_json::from_str(&assignees).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(12, rusqlite::types::Type::Text, e.into())
+        })?,
+        checklist: serde_json::from_str(&checklist).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(13, rusqlite::types::Type::Text, e.into())
+        })?,
+        parent_id: row.get(14)?,
+        blocked_by: serde_json::from_str(&blocked_by).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(15, rusqlite::types::Type::Text, e.into())
+        })?,
+        archived_at: archived_at
+            .map(|raw| parse_timestamp(16, &raw))
+            .transpose()?,
+        blocked: match (blocked_reason, blocked_since) {
+            (Some(reason), Some(since)) => Some(BlockedFlag {
+                reason,
+                since: parse_timestamp(18, &since)?,
+            }),
+            _ => None,
+        },
+    })
+}
+
+fn board_from_row(row: &Row<'_>) -> rusqlite::Result<Board> {
+    let workflow: String = row.get(2)?;
+    let created_at: String = row.get(3)?;
+
+    Ok(Board {
+        id: Some(row.get(0)?),
+        name: row.get(1)?,
+        workflow: serde_json::from_str(&workflow).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, e.into())
+        })?,
+        created_at: parse_timestamp(3, &created_at)?,
+    })
+}
+
+fn user_from_row(row: &Row<'_>) -> rusqlite::Result<User> {
+    let wip_limit: Option<i64> = row.get(2)?;
+    let created_at: String = row.get(3)?;
+
+    Ok(User {
+        id: Some(row.get(0)?),
+        name: row.get(1)?,
+        wip_limit: wip_limit.map(|limit| limit as usize),
+        created_at: parse_timestamp(3, &created_at)?,
+    })
+}
+
+fn comment_from_row(row: &Row<'_>) -> rusqlite::Result<Comment> {
+    let created_at: String = row.get(4)?;
+    let updated_at: Option<String> = row.get(5)?;
+    let revisions: String = row.get(7)?;
+
+    Ok(Comment {
+        id: Some(row.get(0)?),
+        task_id: row.get(1)?,
+        author: row.get(2)?,
+        body: row.get(3)?,
+        created_at: parse_timestamp(4, &created_at)?,
+        updated_at: updated_at.map(|raw| parse_timestamp(5, &raw)).transpose()?,
+        edited_by: row.get(6)?,
+        revisions: serde_json::from_str(&revisions).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(7, rusqlite::types::Type::Text, e.into())
+        })?,
+    })
+}
+
+fn event_from_row(row: &Row<'_>) -> rusqlite::Result<TaskEvent> {
+    let kind: String = row.get(1)?;
+    let at: String = row.get(4)?;
+
+    Ok(TaskEvent {
+        task_id: row.get(0)?,
+        kind: kind.parse::<EventKind>().map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, e.into())
+        })?,
+        old_value: row.get(2)?,
+        new_value: row.get(3)?,
+        at: parse_timestamp(4, &at)?,
+        actor: row.get(5)?,
+        reason: row.get(6)?,
+    })
+}
+
+impl TaskRepository for SqliteTaskRepository {
+    fn get(&self, id: u32) -> Result<Option<Task>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS),
+                params![id],
+                task_from_row,
+            )
+            .optional()?)
+    }
+
+    fn find_by_name(&self, board_id: u32, name: &str) -> Result<Option<Task>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM tasks WHERE board_id = ?1 AND unicode_lower(name) = ?2",
+                    TASK_COLUMNS
+                ),
+                params![board_id, name.to_lowercase()],
+                task_from_row,
+            )
+            .optional()?)
+    }
+
+    fn insert(&mut self, mut task: Task) -> Result<Task> {
+        self.conn.execute(
+            "INSERT INTO tasks
+                 (name, description, status, created_at, updated_at, expedited, board_id,
+                  priority, due_at, completed_late, labels, assignees, checklist, parent_id,
+                  blocked_by, archived_at, blocked_reason, blocked_since)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                     ?17, ?18)",
+            params![
+                task.name,
+                task.description,
+                task.status.to_string(),
+                format_timestamp(&task.created_at),
+                task.updated_at.as_ref().map(format_timestamp),
+                task.expedited,
+                task.board_id,
+                task.priority.as_str(),
+                task.due_at.as_ref().map(format_timestamp),
+                task.completed_late,
+                serde_json::to_string(&task.labels)?,
+                serde_json::to_string(&task.assignees)?,
+                serde_json::to_string(&task.checklist)?,
+                task.parent_id,
+                serde_json::to_string(&task.blocked_by)?,
+                task.archived_at.as_ref().map(format_timestamp),
+                task.blocked.as_ref().map(|flag| flag.reason.as_str()),
+                task.blocked
+                    .as_ref()
+                    .map(|flag| format_timestamp(&flag.since)),
+            ],
+        )?;
+
+        task.id = Some(self.conn.last_insert_rowid() as u32);
+        Ok(task)
+    }
+
+    fn update(&mut self, task: Task) -> Result<Task> {
+        let id = task.id.unwrap_or_default();
+        let changed = self.conn.execute(
+            "UPDATE tasks
+             SET name = ?1, description = ?2, status = ?3, created_at = ?4, updated_at = ?5,
+                 expedited = ?6, board_id = ?7, priority = ?8, due_at = ?9,
+                 completed_late = ?10, labels = ?11, assignees = ?12, checklist = ?13,
+                 parent_id = ?14, blocked_by = ?15, archived_at = ?16, blocked_reason = ?17,
+                 blocked_since = ?18
+             WHERE id = ?19",
+            params![
+                task.name,
+                task.description,
+                task.status.to_string(),
+                format_timestamp(&task.created_at),
+                task.updated_at.as_ref().map(format_timestamp),
+                task.expedited,
+                task.board_id,
+                task.priority.as_str(),
+                task.due_at.as_ref().map(format_timestamp),
+                task.completed_late,
+                serde_json::to_string(&task.labels)?,
+                serde_json::to_string(&task.assignees)?,
+                serde_json::to_string(&task.checklist)?,
+                task.parent_id,
+                serde_json::to_string(&task.blocked_by)?,
+                task.archived_at.as_ref().map(format_timestamp),
+                task.blocked.as_ref().map(|flag| flag.reason.as_str()),
+                task.blocked
+                    .as_ref()
+                    .map(|flag| format_timestamp(&flag.since)),
+                id,
+            ],
+        )?;
+
+        if changed == 0 {
+            return Err(KanbanError::NotFound { id });
+        }
+
+        Ok(task)
+    }
+
+    fn delete(&mut self, id: u32) -> Result<Task> {
+        let task = self.get(id)?.ok_or(KanbanError::NotFound { id })?;
+
+        let tx = self.conn.savepoint()?;
+        tx.execute("DELETE FROM task_events WHERE task_id = ?1", params![id])?;
+        tx.execute("DELETE FROM comments WHERE task_id = ?1", params![id])?;
+        tx.execute("DELETE FROM tasks WHERE id = ?1", params![id])?;
+        tx.commit()?;
+
+        Ok(task)
+    }
+
+    fn list(&self, query: &TaskQuery) -> Result<Vec<Task>> {
+        // narrow down by board, status and name in SQL, the rest is applied
+        // in memory
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        if let Some(board_id) = query.board {
+            conditions.push(String::from("board_id = ?"));
+            values.push(Value::Integer(board_id.into()));
+        }
+        if !query.statuses.is_empty() {
+            let placeholders = vec!["?"; query.statuses.len()].join(", ");
+            conditions.push(format!("status IN ({})", placeholders));
+            values.extend(
+                query
+                    .statuses
+                    .iter()
+                    .map(|s| Value::Text(s.as_str().to_string())),
+            );
+        }
+        if let Some(name) = &query.name {
+            conditions.push(String::from("instr(unicode_lower(name), ?) > 0"));
+            values.push(Value::Text(name.to_lowercase()));
+        }
+
+        let mut sql = format!("SELECT {} FROM tasks", TASK_COLUMNS);
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+
+        let load = |conn: &Connection| -> rusqlite::Result<Vec<Task>> {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(values.iter()), task_from_row)?;
+            rows.collect()
+        };
+
+        Ok(query.apply(load(&self.conn)?))
+    }
+
+    fn append_event(&mut self, event: TaskEvent) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO task_events (task_id, kind, old_value, new_value, at, actor, reason)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                event.task_id,
+                event.kind.as_str(),
+                event.old_value,
+                event.new_value,
+                format_timestamp(&event.at),
+                event.actor,
+                event.reason,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn history(&self, task_id: u32) -> Result<Vec<TaskEvent>> {
+        let load = |conn: &Connection| -> rusqlite::Result<Vec<TaskEvent>> {
+            let mut stmt = conn.prepare(
+                "SELECT task_id, kind, old_value, new_value, at, actor, reason
+                 FROM task_events WHERE task_id = ?1 ORDER BY id",
+            )?;
+            let rows = stmt.query_map(params![task_id], event_from_row)?;
+            rows.collect()
+        };
+
+        Ok(load(&self.conn)?)
+    }
+
+    fn get_board(&self, id: u32) -> Result<Option<Board>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {} FROM boards WHERE id = ?1", BOARD_COLUMNS),
+                params![id],
+                board_from_row,
+            )
+            .optional()?)
+    }
+
+    fn list_boards(&self) -> Result<Vec<Board>> {
+        let load = |conn: &Connection| -> rusqlite::Result<Vec<Board>> {
+            let mut stmt =
+                conn.prepare(&format!("SELECT {} FROM boards ORDER BY id", BOARD_COLUMNS))?;
+            let rows = stmt.query_map([], board_from_row)?;
+            rows.collect()
+        };
+
+        Ok(load(&self.conn)?)
+    }
+
+    fn insert_board(&mut self, mut board: Board) -> Result<Board> {
+        self.conn.execute(
+            "INSERT INTO boards (id, name, workflow, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                board.id,
+                board.name,
+                serde_json::to_string(&board.workflow)?,
+                format_timestamp(&board.created_at),
+            ],
+        )?;
+
+        board.id = Some(self.conn.last_insert_rowid() as u32);
+        Ok(board)
+    }
+
+    fn update_board(&mut self, board: Board) -> Result<Board> {
+        let id = board.id.unwrap_or_default();
+        let changed = self.conn.execute(
+            "UPDATE boards SET name = ?1, workflow = ?2, created_at = ?3 WHERE id = ?4",
+            params![
+                board.name,
+                serde_json::to_string(&board.workflow)?,
+                format_timestamp(&board.created_at),
+                id,
+            ],
+        )?;
+
+        if changed == 0 {
+            return Err(KanbanError::BoardNotFound { id });
+        }
+
+        Ok(board)
+    }
+
+    fn delete_board(&mut self, id: u32) -> Result<Board> {
+        let board = self
+            .get_board(id)?
+            .ok_or(KanbanError::BoardNotFound { id })?;
+        self.conn
+            .execute("DELETE FROM boards WHERE id = ?1", params![id])?;
+
+        Ok(board)
+    }
+
+    fn get_user(&self, id: u32) -> Result<Option<User>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {} FROM users WHERE id = ?1", USER_COLUMNS),
+                params![id],
+                user_from_row,
+            )
+            .optional()?)
+    }
+
+    fn list_users(&self) -> Result<Vec<User>> {
+        let load = |conn: &Connection| -> rusqlite::Result<Vec<User>> {
+            let mut stmt =
+                conn.prepare(&format!("SELECT {} FROM users ORDER BY id", USER_COLUMNS))?;
+            let rows = stmt.query_map([], user_from_row)?;
+            rows.collect()
+        };
+
+        Ok(load(&self.conn)?)
+    }
+
+    fn insert_user(&mut self, mut user: User) -> Result<User> {
+        self.conn.execute(
+            "INSERT INTO users (name, wip_limit, created_at) VALUES (?1, ?2, ?3)",
+            params![
+                user.name,
+                user.wip_limit.map(|limit| limit as i64),
+                format_timestamp(&user.created_at),
+            ],
+        )?;
+
+        user.id = Some(self.conn.last_insert_rowid() as u32);
+        Ok(user)
+    }
+
+    fn update_user(&mut self, user: User) -> Result<User> {
+        let id = user.id.unwrap_or_default();
+        let changed = self.conn.execute(
+            "UPDATE users SET name = ?1, wip_limit = ?2, created_at = ?3 WHERE id = ?4",
+            params![
+                user.name,
+                user.wip_limit.map(|limit| limit as i64),
+                format_timestamp(&user.created_at),
+                id,
+            ],
+        )?;
+
+        if changed == 0 {
+            return Err(KanbanError::UserNotFound { id });
+        }
+
+        Ok(user)
+    }
+
+    fn get_comment(&self, id: u32) -> Result<Option<Comment>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {} FROM comments WHERE id = ?1", COMMENT_COLUMNS),
+                params![id],
+                comment_from_row,
+            )
+            .optional()?)
+    }
+
+    fn list_comments(&self, task_id: u32) -> Result<Vec<Comment>> {
+        let load = |conn: &Connection| -> rusqlite::Result<Vec<Comment>> {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM comments WHERE task_id = ?1 ORDER BY id",
+                COMMENT_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![task_id], comment_from_row)?;
+            rows.collect()
+        };
+
+        Ok(load(&self.conn)?)
+    }
+
+    fn insert_comment(&mut self, mut comment: Comment) -> Result<Comment> {
+        self.conn.execute(
+            "INSERT INTO comments (task_id, author, body, created_at, updated_at, edited_by, \
+             revisions) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                comment.task_id,
+                comment.author,
+                comment.body,
+                format_timestamp(&comment.created_at),
+                comment.updated_at.as_ref().map(format_timestamp),
+                comment.edited_by,
+                serde_json::to_string(&comment.revisions)?,
+            ],
+        )?;
+
+        comment.id = Some(self.conn.last_insert_rowid() as u32);
+        Ok(comment)
+    }
+
+    fn update_comment(&mut self, comment: Comment) -> Result<Comment> {
+        let id = comment.id.unwrap_or_default();
+        let changed = self.conn.execute(
+            "UPDATE comments SET task_id = ?1, author = ?2, body = ?3, created_at = ?4, \
+             updated_at = ?5, edited_by = ?6, revisions = ?7 WHERE id = ?8",
+            params![
+                comment.task_id,
+                comment.author,
+                comment.body,
+                format_timestamp(&comment.created_at),
+                comment.updated_at.as_ref().map(format_timestamp),
+                comment.edited_by,
+                serde_json::to_string(&comment.revisions)?,
+                id,
+            ],
+        )?;
+
+        if changed == 0 {
+            return Err(KanbanError::CommentNotFound { id });
+        }
+
+        Ok(comment)
+    }
+
+    fn delete_comment(&mut self, id: u32) -> Result<Comment> {
+        let comment = self
+            .get_comment(id)?
+            .ok_or(KanbanError::CommentNotFound { id })?;
+        self.conn
+            .execute("DELETE FROM comments WHERE id = ?1", params![id])?;
+
+        Ok(comment)
+    }
+
+    fn begin(&mut self) -> Result<()> {
+        if !self.conn.is_autocommit() {
+            return Err(KanbanError::Storage {
+                reason: String::from("a unit of work is already in progress"),
+            });
+        }
+
+        self.conn.execute_batch("BEGIN IMMEDIATE")?;
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        if self.conn.is_autocommit() {
+            return Err(KanbanError::Storage {
+                reason: String::from("no unit of work in progress"),
+            });
+        }
+
+        if let Err(err) = self.conn.execute_batch("COMMIT") {
+            // a failed COMMIT may leave the transaction open
+            if !self.conn.is_autocommit() {
+                self.conn.execute_batch("ROLLBACK")?;
+            }
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        if self.conn.is_autocommit() {
+            return Err(KanbanError::Storage {
+                reason: String::from("no unit of work in progress"),
+            });
+        }
+
+        self.conn.execute_batch("ROLLBACK")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod sqlite_repo_tests {
+    use super::*;
+    use crate::ChecklistItem;
+    use crate::board::DEFAULT_BOARD_ID;
+    use crate::repository::contract_tests;
+    use crate::workflow::Workflow;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    struct Setup {
+        repo: SqliteTaskRepository,
+    }
+
+    impl Setup {
+        fn new() -> Self {
+            let mut repo = SqliteTaskRepository::open_in_memory().expect("db not opened");
+
+            for name in ["task1", "task 2", "task 3"] {
+                repo.insert(Task::new(name.to_string(), format!("{} description", name)))
+                    .expect("task not inserted");
+            }
+
+            let mut task3 = repo.get(3).unwrap().unwrap();
+            task3.status = Status::DONE;
+            repo.update(task3).unwrap();
+
+            Setup { repo }
+        }
+    }
+
+    #[test]
+    fn migrations_reach_latest_version() {
+        let setup = Setup::new();
+        assert_eq!(setup.repo.schema_version().unwrap(), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn migrations_upgrade_older_schema() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("board.db");
+
+        // simulate a database created before the indexes existed
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(MIGRATIONS[0]).unwrap();
+            conn.pragma_update(None, "user_version", 1).unwrap();
+            conn.execute(
+                "INSERT INTO tasks (name, description, status, created_at)
+                 VALUES ('old', '', 'Todo', '2025-01-01T00:00:00Z')",
+                [],
+            )
+            .unwrap();
+        }
+
+        let repo = SqliteTaskRepository::open(&path).unwrap();
+        assert_eq!(repo.schema_version().unwrap(), MIGRATIONS.len());
+        assert_eq!(
+            repo.find_by_name(DEFAULT_BOARD_ID, "OLD")
+                .unwrap()
+                .unwrap()
+                .id,
+            Some(1)
+        );
+        assert_eq!(repo.list_boards().unwrap().len(), 1);
+
+        let indexes: i64 = repo
+            .conn
+            .query_row(
+                "SELECT count(*) FROM sqlite_master WHERE type = 'index' AND name LIKE 'idx_%'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes, 5);
+    }
+
+    #[test]
+    fn timestamps_round_trip() {
+        let mut repo = SqliteTaskRepository::open_in_memory().unwrap();
+
+        let mut task = Task::new("precise".to_string(), "".to_string());
+        task.created_at = Utc.timestamp_opt(1_700_000_000, 123_456_789).unwrap();
+        task.updated_at = Some(Utc.timestamp_opt(1_700_000_100, 1).unwrap());
+        task.due_at = Some(Utc.timestamp_opt(1_700_086_400, 500).unwrap());
+        task.labels.insert("bug".to_string());
+        task.checklist.push(ChecklistItem::new("step".to_string()));
+        task.parent_id = Some(7);
+        task.blocked_by.insert(5);
+        let stored = repo.insert(task.clone()).unwrap();
+
+        let loaded = repo.get(stored.id.unwrap()).unwrap().unwrap();
+        assert_eq!(loaded.priority, Priority::Medium);
+        assert_eq!(loaded.created_at, task.created_at);
+        assert_eq!(loaded.updated_at, task.updated_at);
+        assert_eq!(loaded.due_at, task.due_at);
+        assert_eq!(loaded.labels, task.labels);
+        assert_eq!(loaded.checklist, task.checklist);
+        assert_eq!(loaded.parent_id, Some(7));
+        assert_eq!(loaded.blocked_by, task.blocked_by);
+    }
+
+    #[test]
+    fn update_and_delete() {
+        let mut setup = Setup::new();
+
+        let mut task = setup.repo.get(1).unwrap().unwrap();
+        task.status = Status::DOING;
+        task.priority = Priority::Critical;
+        setup.repo.update(task).unwrap();
+        assert_eq!(setup.repo.get(1).unwrap().unwrap().status, Status::DOING);
+        assert_eq!(
+            setup.repo.get(1).unwrap().unwrap().priority,
+            Priority::Critical
+        );
+
+        assert_eq!(setup.repo.delete(2).unwrap().name, "task 2");
+        assert!(setup.repo.get(2).unwrap().is_none());
+        assert_eq!(
+            setup.repo.delete(2).unwrap_err(),
+            KanbanError::NotFound { id: 2 }
+        );
+    }
+
+    #[test]
+    fn history_round_trips_and_is_deleted_with_task() {
+        let mut setup = Setup::new();
+
+        let mut created = TaskEvent::new(1, EventKind::Created, None, Some("Todo".into()), "bob");
+        created.at = Utc.timestamp_opt(1_700_000_000, 42).unwrap();
+        let moved = TaskEvent::new(
+            1,
+            EventKind::Moved,
+            Some("Todo".into()),
+            Some("Doing".into()),
+            "alice",
+        );
+        let reopened = TaskEvent::new(
+            1,
+            EventKind::Reopened,
+            Some("Done".into()),
+            Some("Doing".into()),
+            "alice",
+        )
+        .with_reason("tests fail");
+        setup.repo.append_event(created.clone()).unwrap();
+        setup.repo.append_event(moved.clone()).unwrap();
+        setup.repo.append_event(reopened.clone()).unwrap();
+
+        assert_eq!(
+            setup.repo.history(1).unwrap(),
+            vec![created, moved, reopened]
+        );
+        assert!(setup.repo.history(2).unwrap().is_empty());
+
+        setup.repo.delete(1).unwrap();
+        assert!(setup.repo.history(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn list_applies_query() {
+        let setup = Setup::new();
+
+        let done = TaskQuery::new().status(Status::DONE);
+        assert_eq!(setup.repo.list(&done).unwrap().len(), 1);
+
+        let by_name = TaskQuery::new().name_contains("TASK ");
+        assert_eq!(setup.repo.list(&by_name).unwrap().len(), 2);
+        let wildcard = TaskQuery::new().name_contains("_");
+        assert!(setup.repo.list(&wildcard).unwrap().is_empty());
+        assert_eq!(setup.repo.list(&TaskQuery::new()).unwrap().len(), 3);
+
+        let newest_todo = TaskQuery::new()
+            .status(Status::TODO)
+            .sort_by(crate::query::SortKey::Id)
+            .descending()
+            .limit(1);
+        assert_eq!(setup.repo.list(&newest_todo).unwrap()[0].id, Some(2));
+    }
+
+    #[test]
+    fn boards_round_trip() {
+        let mut setup = Setup::new();
+        assert_eq!(setup.repo.list_boards().unwrap()[0].name, "Default");
+
+        let workflow = Workflow::linear(vec![Status::new("Open"), Status::new("Closed")])
+            .unwrap()
+            .with_wip_limit("Open", 2)
+            .unwrap();
+        let board = setup
+            .repo
+            .insert_board(Board::new("ops".to_string(), workflow))
+            .unwrap();
+        assert_eq!(board.id, Some(2));
+        assert_eq!(setup.repo.get_board(2).unwrap(), Some(board.clone()));
+
+        let mut task = setup.repo.get(1).unwrap().unwrap();
+        task.board_id = 2;
+        setup.repo.update(task).unwrap();
+        assert_eq!(
+            setup.repo.list(&TaskQuery::new().board(2)).unwrap().len(),
+            1
+        );
+        assert_eq!(
+            setup
+                .repo
+                .list(
+                    &TaskQuery::new()
+                        .board(DEFAULT_BOARD_ID)
+                        .status(Status::TODO)
+                )
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(
+            setup
+                .repo
+                .find_by_name(DEFAULT_BOARD_ID, "task1")
+                .unwrap()
+                .is_none()
+        );
+
+        let mut renamed = board;
+        renamed.name = "operations".to_string();
+        setup.repo.update_board(renamed).unwrap();
+        assert_eq!(setup.repo.get_board(2).unwrap().unwrap().name, "operations");
+        setup.repo.delete_board(2).unwrap();
+        assert_eq!(
+            setup.repo.delete_board(2).unwrap_err(),
+            KanbanError::BoardNotFound { id: 2 }
+        );
+    }
+
+    #[test]
+    fn users_and_assignees_round_trip() {
+        let mut setup = Setup::new();
+        assert!(setup.repo.list_users().unwrap().is_empty());
+
+        let mut user = User::new("alice".to_string());
+        user.wip_limit = Some(2);
+        let user = setup.repo.insert_user(user).unwrap();
+        assert_eq!(setup.repo.get_user(1).unwrap(), Some(user.clone()));
+
+        let mut task = setup.repo.get(2).unwrap().unwrap();
+        task.assignees.insert(1);
+        setup.repo.update(task).unwrap();
+        assert_eq!(setup.repo.get(2).unwrap().unwrap().assignees.len(), 1);
+
+        let mut unlimited = user;
+        unlimited.wip_limit = None;
+        setup.repo.update_user(unlimited.clone()).unwrap();
+        assert_eq!(setup.repo.list_users().unwrap(), vec![unlimited]);
+        assert!(setup.repo.get_user(2).unwrap().is_none());
+    }
+
+    #[test]
+    fn comments_round_trip_and_are_deleted_with_task() {
+        let mut setup = Setup::new();
+
+        let comment = Comment::new(2, "alice".to_string(), "# Notes\n- one".to_string());
+        let mut comment = setup.repo.insert_comment(comment).unwrap();
+        assert_eq!(comment.id, Some(1));
+        assert_eq!(setup.repo.get_comment(1).unwrap(), Some(comment.clone()));
+
+        comment.edit("# Notes\n- one\n- two".to_string(), "bob");
+        setup.repo.update_comment(comment.clone()).unwrap();
+        assert_eq!(setup.repo.list_comments(2).unwrap(), vec![comment.clone()]);
+        assert!(setup.repo.list_comments(1).unwrap().is_empty());
+
+        setup.repo.delete(2).unwrap();
+        assert!(setup.repo.get_comment(1).unwrap().is_none());
+        assert_eq!(
+            setup.repo.update_comment(comment).unwrap_err(),
+            KanbanError::CommentNotFound { id: 1 }
+        );
+        assert_eq!(
+            setup.repo.delete_comment(1).unwrap_err(),
+            KanbanError::CommentNotFound { id: 1 }
+        );
+    }
+
+    #[test]
+    fn ids_of_deleted_comments_are_not_reused() {
+        let mut setup = Setup::new();
+        let comment = |body: &str| Comment::new(1, "alice".to_string(), body.to_string());
+
+        setup.repo.insert_comment(comment("first")).unwrap();
+        setup.repo.insert_comment(comment("second")).unwrap();
+        setup.repo.delete_comment(2).unwrap();
+
+        let third = setup.repo.insert_comment(comment("third")).unwrap();
+        assert_eq!(third.id, Some(3));
+    }
+
+    #[test]
+    fn archived_tasks_round_trip_and_ids_are_not_reused() {
+        let mut setup = Setup::new();
+
+        let mut task = setup.repo.get(2).unwrap().unwrap();
+        task.archived_at = Some(Utc::now());
+        setup.repo.update(task.clone()).unwrap();
+        assert_eq!(
+            setup.repo.get(2).unwrap().unwrap().archived_at,
+            task.archived_at
+        );
+
+        let mut task = setup.repo.get(1).unwrap().unwrap();
+        let flag = BlockedFlag {
+            reason: "waiting on ops".to_string(),
+            since: Utc.timestamp_opt(1_700_000_000, 7).unwrap(),
+        };
+        task.blocked = Some(flag.clone());
+        setup.repo.update(task).unwrap();
+        assert_eq!(setup.repo.get(1).unwrap().unwrap().blocked, Some(flag));
+        assert_eq!(setup.repo.list(&TaskQuery::new()).unwrap().len(), 2);
+        assert_eq!(
+            setup.repo.list(&TaskQuery::new().archived()).unwrap().len(),
+            1
+        );
+
+        setup.repo.delete(3).unwrap();
+        let task = setup
+            .repo
+            .insert(Task::new("task 4".to_string(), String::new()))
+            .unwrap();
+        assert_eq!(task.id, Some(4));
+    }
+
+    #[test]
+    fn undecodable_rows_are_reported() {
+        let setup = Setup::new();
+        setup
+            .repo
+            .conn
+            .execute("UPDATE tasks SET created_at = 'yesterday' WHERE id = 2", [])
+            .unwrap();
+        setup
+            .repo
+            .conn
+            .execute("UPDATE boards SET workflow = '{}'", [])
+            .unwrap();
+
+        assert!(matches!(
+            setup.repo.get(2),
+            Err(KanbanError::Storage { .. })
+        ));
+        assert!(matches!(
+            setup.repo.list(&TaskQuery::new()),
+            Err(KanbanError::Storage { .. })
+        ));
+        assert!(matches!(
+            setup.repo.get_board(DEFAULT_BOARD_ID),
+            Err(KanbanError::Storage { .. })
+        ));
+        assert!(setup.repo.get(1).unwrap().is_some());
+    }
+
+    #[test]
+    fn units_of_work_commit_or_roll_back() {
+        let mut setup = Setup::new();
+        contract_tests::units_of_work_commit_or_roll_back(&mut setup.repo);
+    }
+
+    #[test]
+    fn names_match_ignoring_unicode_case() {
+        let mut setup = Setup::new();
+        contract_tests::names_match_ignoring_unicode_case(&mut setup.repo);
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KanbanError, Result};
+
+/// A person tasks can be assigned to.
+///
+/// Users are shared by every board in the workspace and their names are
+/// unique within it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct User {
+    /// unique identifier
+    pub id: Option<u32>,
+    /// user name, unique within the workspace
+    pub name: String,
+    /// maximum number of tasks the user should have in progress at once
+    #[serde(default)]
+    pub wip_limit: Option<usize>,
+    /// when the user was added
+    pub created_at: DateTime<Utc>,
+}
+
+impl User {
+    /// Creates a new user without a personal WIP limit.
+    pub fn new(name: String) -> Self {
+        User {
+            id: None,
+            name,
+            wip_limit: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    // validations for a new or changed user
+    pub fn before_save(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(KanbanError::Validation {
+                field: "user",
+                reason: String::from("name must not be empty"),
+            });
+        }
+        if self.wip_limit == Some(0) {
+            return Err(KanbanError::Validation {
+                field: "wip_limit",
+                reason: String::from("must be at least 1"),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// How much work a user has in progress compared to their WIP limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workload {
+    pub user: User,
+    /// number of assigned tasks that are started but not done, on any board
+    pub in_progress: usize,
+}
+
+impl Workload {
+    /// Returns `true` if the user has more tasks in progress than their
+    /// WIP limit allows.
+    pub fn is_over_limit(&self) -> bool {
+        self.user
+            .wip_limit
+            .is_some_and(|limit| self.in_progress > limit)
+    }
+
+    /// Returns `true` if the user cannot take on another task without
+    /// exceeding their WIP limit.
+    pub fn is_at_limit(&self) -> bool {
+        self.user
+            .wip_limit
+            .is_some_and(|limit| self.in_progress >= limit)
+    }
+}
@@ -0,0 +1,414 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Status;
+use crate::error::{KanbanError, Result};
+
+/// Board-level definition of the columns a task moves through.
+///
+/// A workflow is an ordered list of columns plus the directed transitions
+/// allowed between them. New tasks always start in the first column.
+/// Columns may optionally cap how many tasks they hold at once.
+///
+/// For flow metrics, work counts as started once a task reaches the start
+/// column (or any column after it) and finished when it enters the done
+/// column. Unless set explicitly these are the second and last columns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "StoredWorkflow")]
+pub struct Workflow {
+    /// columns in board order
+    columns: Vec<Status>,
+    /// allowed `(from, to)` moves
+    transitions: Vec<(Status, Status)>,
+    /// maximum number of tasks per column; columns not listed are unlimited
+    wip_limits: Vec<(Status, usize)>,
+    /// column where work is considered started
+    start_column: Option<Status>,
+    /// column where work is considered finished
+    done_column: Option<Status>,
+    /// whether tasks need an assignee before work on them starts
+    require_assignee: bool,
+    /// whether tasks may be done while checklist items or subtasks are open
+    allow_incomplete_subtasks: bool,
+}
+
+/// A workflow as stored, checked with the same rules as
+/// [`Workflow::new`] before it is used.
+#[derive(Deserialize)]
+struct StoredWorkflow {
+    columns: Vec<Status>,
+    transitions: Vec<(Status, Status)>,
+    #[serde(default)]
+    wip_limits: Vec<(Status, usize)>,
+    #[serde(default)]
+    start_column: Option<Status>,
+    #[serde(default)]
+    done_column: Option<Status>,
+    #[serde(default)]
+    require_assignee: bool,
+    #[serde(default)]
+    allow_incomplete_subtasks: bool,
+}
+
+impl TryFrom<StoredWorkflow> for Workflow {
+    type Error = KanbanError;
+
+    fn try_from(stored: StoredWorkflow) -> Result<Self> {
+        let mut workflow = Workflow::new(stored.columns, stored.transitions)?;
+        for (column, limit) in &stored.wip_limits {
+            workflow = workflow.with_wip_limit(column.as_str(), *limit)?;
+        }
+        if let Some(column) = &stored.start_column {
+            workflow = workflow.with_start_column(column.as_str())?;
+        }
+        if let Some(column) = &stored.done_column {
+            workflow = workflow.with_done_column(column.as_str())?;
+        }
+        workflow.require_assignee = stored.require_assignee;
+        workflow.allow_incomplete_subtasks = stored.allow_incomplete_subtasks;
+
+        Ok(workflow)
+    }
+}
+
+impl Workflow {
+    /// Creates a workflow from its columns and allowed transitions.
+    ///
+    /// # Returns
+    /// * `Ok(Workflow)` - The validated workflow.
+    /// * `Err(KanbanError::Validation)` - If there are no columns, a column
+    ///   name is repeated or a transition refers to an unknown column.
+    pub fn new(columns: Vec<Status>, transitions: Vec<(Status, Status)>) -> Result<Self> {
+        if columns.is_empty() {
+            return Err(KanbanError::Validation {
+                field: "workflow",
+                reason: String::from("at least one column is required"),
+            });
+        }
+
+        for (pos, column) in columns.iter().enumerate() {
+            if columns[..pos].iter().any(|c| c.matches(column.as_str())) {
+                return Err(KanbanError::Validation {
+                    field: "workflow",
+                    reason: format!("column '{}' is defined more than once", column),
+                });
+            }
+        }
+
+        let workflow = Workflow {
+            columns,
+            transitions: Vec::new(),
+            wip_limits: Vec::new(),
+            start_column: None,
+            done_column: None,
+            require_assignee: false,
+            allow_incomplete_subtasks: false,
+        };
+
+        let mut resolved = Vec::with_capacity(transitions.len());
+        for (from, to) in &transitions {
+            resolved.push((
+                workflow.resolve(from.as_str())?,
+                workflow.resolve(to.as_str())?,
+            ));
+        }
+
+        Ok(Workflow {
+            transitions: resolved,
+            ..workflow
+        })
+    }
+
+    /// Creates a workflow where tasks move forward one column at a time.
+    pub fn linear(columns: Vec<Status>) -> Result<Self> {
+        let transitions = columns
+            .windows(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+
+        Workflow::new(columns, transitions)
+    }
+
+    /// Columns in board order.
+    pub fn columns(&self) -> &[Status] {
+        &self.columns
+    }
+
+    /// Allowed `(from, to)` transitions.
+    pub fn transitions(&self) -> &[(Status, Status)] {
+        &self.transitions
+    }
+
+    /// Column new tasks are placed in.
+    pub fn initial(&self) -> &Status {
+        &self.columns[0]
+    }
+
+    /// Looks up a column by name (case-insensitive).
+    ///
+    /// # Returns
+    /// * `Ok(Status)` - The column as spelled in the workflow.
+    /// * `Err(KanbanError::Validation)` - If the workflow has no such column.
+    pub fn resolve(&self, name: &str) -> Result<Status> {
+        self.columns
+            .iter()
+            .find(|c| c.matches(name))
+            .cloned()
+            .ok_or_else(|| KanbanError::Validation {
+                field: "status",
+                reason: format!("unknown column '{}'", name),
+            })
+    }
+
+    /// Caps the number of tasks `column` may hold at once.
+    ///
+    /// # Returns
+    /// * `Ok(Workflow)` - The workflow with the limit applied.
+    /// * `Err(KanbanError::Validation)` - If the column is unknown or the
+    ///   limit is zero.
+    pub fn with_wip_limit(mut self, column: &str, limit: usize) -> Result<Self> {
+        let column = self.resolve(column)?;
+        if limit == 0 {
+            return Err(KanbanError::Validation {
+                field: "wip_limit",
+                reason: String::from("must be at least 1"),
+            });
+        }
+
+        self.wip_limits.retain(|(c, _)| *c != column);
+        self.wip_limits.push((column, limit));
+        Ok(self)
+    }
+
+    /// Maximum number of tasks allowed in `column`, if it is limited.
+    pub fn wip_limit(&self, column: &Status) -> Option<usize> {
+        self.wip_limits
+            .iter()
+            .find(|(c, _)| c.matches(column.as_str()))
+            .map(|(_, limit)| *limit)
+    }
+
+    /// Marks `column` as the point where work is considered started.
+    pub fn with_start_column(mut self, column: &str) -> Result<Self> {
+        self.start_column = Some(self.resolve(column)?);
+        Ok(self)
+    }
+
+    /// Marks `column` as the point where work is considered finished.
+    pub fn with_done_column(mut self, column: &str) -> Result<Self> {
+        self.done_column = Some(self.resolve(column)?);
+        Ok(self)
+    }
+
+    /// Requires tasks to have an assignee before they reach the start
+    /// column or any column after it.
+    pub fn requiring_assignee(mut self) -> Self {
+        self.require_assignee = true;
+        self
+    }
+
+    /// Returns `true` if tasks need an assignee before work on them starts.
+    pub fn requires_assignee(&self) -> bool {
+        self.require_assignee
+    }
+
+    /// Lets tasks reach the done column while checklist items or subtasks
+    /// are still open. By default they may not.
+    pub fn allowing_incomplete_subtasks(mut self) -> Self {
+        self.allow_incomplete_subtasks = true;
+        self
+    }
+
+    /// Returns `true` if tasks may be done with open checklist items or
+    /// subtasks.
+    pub fn allows_incomplete_subtasks(&self) -> bool {
+        self.allow_incomplete_subtasks
+    }
+
+    /// Column where work is considered started.
+    pub fn start_column(&self) -> &Status {
+        self.start_column
+            .as_ref()
+            .unwrap_or_else(|| self.columns.get(1).unwrap_or(&self.columns[0]))
+    }
+
+    /// Column where work is considered finished.
+    pub fn done_column(&self) -> &Status {
+        self.done_column
+            .as_ref()
+            .unwrap_or_else(|| &self.columns[self.columns.len() - 1])
+    }
+
+    /// Position of `column` in board order.
+    pub fn position(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.matches(column))
+    }
+
+    /// Returns `true` if `column` is the start column or comes after it.
+    pub fn is_started(&self, column: &str) -> bool {
+        let start = self.position(self.start_column().as_str());
+        self.position(column).is_some_and(|pos| Some(pos) >= start)
+    }
+
+    /// Returns `true` if `column` is the done column.
+    pub fn is_done(&self, column: &str) -> bool {
+        self.done_column().matches(column)
+    }
+
+    /// Returns `true` if a task may move directly from `from` to `to`.
+    pub fn allows(&self, from: &Status, to: &Status) -> bool {
+        self.transitions
+            .iter()
+            .any(|(f, t)| f.matches(from.as_str()) && t.matches(to.as_str()))
+    }
+}
+
+impl Default for Workflow {
+    /// The classic Todo -> Doing -> Done flow.
+    fn default() -> Self {
+        Workflow {
+            columns: vec![Status::TODO, Status::DOING, Status::DONE],
+            transitions: vec![(Status::TODO, Status::DOING), (Status::DOING, Status::DONE)],
+            wip_limits: Vec::new(),
+            start_column: None,
+            done_column: None,
+            require_assignee: false,
+            allow_incomplete_subtasks: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team_workflow() -> Workflow {
+        let backlog = Status::new("Backlog");
+        let review = Status::new("Review");
+        let blocked = Status::new("Blocked");
+
+        Workflow::new(
+            vec![
+                backlog.clone(),
+                Status::TODO,
+                Status::DOING,
+                review.clone(),
+                blocked.clone(),
+                Status::DONE,
+            ],
+            vec![
+                (backlog, Status::TODO),
+                (Status::TODO, Status::DOING),
+                (Status::DOING, review.clone()),
+                (review.clone(), Status::DOING),
+                (review, Status::DONE),
+                (Status::DOING, blocked.clone()),
+                (blocked, Status::DOING),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn default_workflow_is_todo_doing_done() {
+        let workflow = Workflow::default();
+
+        assert_eq!(workflow.initial(), &Status::TODO);
+        assert!(workflow.allows(&Status::TODO, &Status::DOING));
+        assert!(workflow.allows(&Status::DOING, &Status::DONE));
+        assert!(!workflow.allows(&Status::TODO, &Status::DONE));
+        assert!(!workflow.allows(&Status::DONE, &Status::DOING));
+        assert_eq!(
+            Workflow::linear(workflow.columns().to_vec()).unwrap(),
+            workflow
+        );
+    }
+
+    #[test]
+    fn custom_workflow_follows_transition_graph() {
+        let workflow = team_workflow();
+
+        assert_eq!(workflow.initial(), &Status::new("Backlog"));
+        assert!(workflow.allows(&Status::DOING, &Status::new("review")));
+        assert!(workflow.allows(&Status::new("Review"), &Status::DOING));
+        assert!(!workflow.allows(&Status::DOING, &Status::DONE));
+    }
+
+    #[test]
+    fn resolve_is_case_insensitive() {
+        let workflow = team_workflow();
+
+        assert_eq!(workflow.resolve("REVIEW").unwrap().as_str(), "Review");
+        assert!(matches!(
+            workflow.resolve("QA"),
+            Err(KanbanError::Validation {
+                field: "status",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn wip_limits_are_per_column() {
+        let workflow = Workflow::default()
+            .with_wip_limit("doing", 3)
+            .unwrap()
+            .with_wip_limit("Doing", 2)
+            .unwrap();
+
+        assert_eq!(workflow.wip_limit(&Status::DOING), Some(2));
+        assert_eq!(workflow.wip_limit(&Status::TODO), None);
+        assert!(Workflow::default().with_wip_limit("Review", 1).is_err());
+        assert!(Workflow::default().with_wip_limit("Doing", 0).is_err());
+    }
+
+    #[test]
+    fn start_and_done_columns() {
+        let workflow = Workflow::default();
+        assert_eq!(workflow.start_column(), &Status::DOING);
+        assert_eq!(workflow.done_column(), &Status::DONE);
+        assert!(!workflow.is_started("Todo"));
+        assert!(workflow.is_started("done"));
+
+        let workflow = team_workflow().with_start_column("doing").unwrap();
+        assert_eq!(workflow.start_column(), &Status::DOING);
+        assert_eq!(workflow.done_column(), &Status::DONE);
+        assert!(workflow.is_started("Review"));
+        assert!(!workflow.is_started("Todo"));
+        assert!(team_workflow().with_done_column("Shipped").is_err());
+    }
+
+    #[test]
+    fn stored_workflow_is_validated() {
+        let workflow = team_workflow()
+            .with_wip_limit("Doing", 2)
+            .unwrap()
+            .with_start_column("Doing")
+            .unwrap()
+            .requiring_assignee();
+        let json = serde_json::to_string(&workflow).unwrap();
+        assert_eq!(serde_json::from_str::<Workflow>(&json).unwrap(), workflow);
+
+        for json in [
+            r#"{"columns": [], "transitions": []}"#,
+            r#"{"columns": ["Todo", "todo"], "transitions": []}"#,
+            r#"{"columns": ["Todo"], "transitions": [["Todo", "QA"]]}"#,
+            r#"{"columns": ["Todo"], "transitions": [], "wip_limits": [["Todo", 0]]}"#,
+            r#"{"columns": ["Todo"], "transitions": [], "done_column": "Done"}"#,
+        ] {
+            assert!(serde_json::from_str::<Workflow>(json).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn invalid_definitions_are_rejected() {
+        assert!(Workflow::new(vec![], vec![]).is_err());
+        assert!(Workflow::linear(vec![Status::TODO, Status::new("todo")]).is_err());
+        assert!(
+            Workflow::new(
+                vec![Status::TODO, Status::DONE],
+                vec![(Status::TODO, Status::new("QA"))]
+            )
+            .is_err()
+        );
+    }
+}